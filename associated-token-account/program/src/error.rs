@@ -0,0 +1,30 @@
+//! Error types
+
+use huione_program::{decode_error::DecodeError, program_error::ProgramError};
+use num_derive::FromPrimitive;
+use thiserror::Error;
+
+/// Errors that may be returned by the AssociatedTokenAccount program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum AssociatedTokenAccountError {
+    // 0
+    /// Associated token account owner does not match the wallet address
+    #[error("Associated token account owner does not match the wallet address")]
+    InvalidOwner,
+    /// Token mint is not owned by the token program
+    #[error("Token mint is not owned by the token program")]
+    InvalidMintOwner,
+    /// Account is not a token account of the token program
+    #[error("Account is not a token account of the token program")]
+    NotTokenAccount,
+}
+impl From<AssociatedTokenAccountError> for ProgramError {
+    fn from(e: AssociatedTokenAccountError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+impl<T> DecodeError<T> for AssociatedTokenAccountError {
+    fn type_of() -> &'static str {
+        "AssociatedTokenAccountError"
+    }
+}
@@ -0,0 +1,120 @@
+//! Program instructions
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use huione_program::{msg, program_error::ProgramError};
+
+/// Instructions supported by the AssociatedTokenAccount program
+///
+/// Serialized with borsh, so each instruction is its one byte variant index followed by its
+/// fields, if any.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq)]
+pub enum AssociatedTokenAccountInstruction {
+    /// Creates an associated token account for the given wallet address and token mint.
+    /// Fails if the account exists.
+    ///
+    /// Empty instruction data is also read as `Create`, for the clients built before this
+    /// enum existed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable,signer]` Funding account (must be a system account)
+    ///   1. `[writeable]` Associated token account address to be created
+    ///   2. `[]` Wallet address for the new associated token account
+    ///   3. `[]` The token mint for the new associated token account
+    ///   4. `[]` System program
+    ///   5. `[]` HPL Token program
+    ///   6. `[]` Rent sysvar
+    ///
+    Create,
+
+    /// Creates an associated token account for the given wallet address and token mint,
+    /// unless it exists. Succeeds without changes if the account exists and belongs to the
+    /// wallet and mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable,signer]` Funding account (must be a system account)
+    ///   1. `[writeable]` Associated token account address to be created
+    ///   2. `[]` Wallet address for the new associated token account
+    ///   3. `[]` The token mint for the new associated token account
+    ///   4. `[]` System program
+    ///   5. `[]` HPL Token program
+    ///   6. `[]` Rent sysvar
+    ///
+    CreateIdempotent,
+
+    /// Transfers the tokens of a nested associated token account, that is an associated token
+    /// account owned by another associated token account, to the wallet's associated token
+    /// account for the same mint, and closes the nested account. The lamports of the nested
+    /// account go to the wallet.
+    ///
+    /// The wallet owning the owner associated token account must sign. The destination account
+    /// must exist; clients may create it first with `CreateIdempotent`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]` Nested associated token account, owned by account 3
+    ///   1. `[]` Token mint of the nested associated token account
+    ///   2. `[writeable]` Wallet's associated token account for the mint of account 1
+    ///   3. `[]` Owner associated token account, owned by account 5
+    ///   4. `[]` Token mint of the owner associated token account
+    ///   5. `[writeable,signer]` Wallet address
+    ///   6. `[]` HPL Token program
+    ///
+    RecoverNested,
+}
+
+impl AssociatedTokenAccountInstruction {
+    /// Serialize the instruction
+    pub fn pack(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
+
+    /// Deserialize an instruction
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.is_empty() {
+            return Ok(Self::Create);
+        }
+        Self::try_from_slice(input).map_err(|_| {
+            msg!("Error: invalid associated token account instruction");
+            ProgramError::InvalidInstructionData
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        for instruction in [
+            AssociatedTokenAccountInstruction::Create,
+            AssociatedTokenAccountInstruction::CreateIdempotent,
+            AssociatedTokenAccountInstruction::RecoverNested,
+        ]
+        .iter()
+        {
+            assert_eq!(
+                AssociatedTokenAccountInstruction::unpack(&instruction.pack()),
+                Ok(*instruction)
+            );
+        }
+        assert_eq!(
+            AssociatedTokenAccountInstruction::unpack(&[]),
+            Ok(AssociatedTokenAccountInstruction::Create)
+        );
+        assert_eq!(
+            AssociatedTokenAccountInstruction::pack(&AssociatedTokenAccountInstruction::Create),
+            vec![0]
+        );
+        assert_eq!(
+            AssociatedTokenAccountInstruction::unpack(&[3]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            AssociatedTokenAccountInstruction::unpack(&[1, 0]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -3,6 +3,8 @@
 #![forbid(unsafe_code)]
 
 mod entrypoint;
+pub mod error;
+pub mod instruction;
 pub mod processor;
 
 // Export current SDK types for downstream users building with a different SDK version
@@ -16,25 +18,32 @@ use huione_program::{
 
 huione_program::declare_id!("HuiATA1111111111111111111111111111111111111");
 
-pub(crate) fn get_associated_token_address_and_bump_seed(
+/// Derives the associated token account address for the given wallet address and token mint
+pub fn get_associated_token_address(
     wallet_address: &Pubkey,
     hpl_token_mint_address: &Pubkey,
-    program_id: &Pubkey,
-) -> (Pubkey, u8) {
-    get_associated_token_address_and_bump_seed_internal(
+) -> Pubkey {
+    get_associated_token_address_with_program_id(
         wallet_address,
         hpl_token_mint_address,
-        program_id,
         &hpl_token::id(),
     )
 }
 
 /// Derives the associated token account address for the given wallet address and token mint
-pub fn get_associated_token_address(
+/// of `token_program_id`, a program following the HPL Token interface
+pub fn get_associated_token_address_with_program_id(
     wallet_address: &Pubkey,
-    hpl_token_mint_address: &Pubkey,
+    token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
 ) -> Pubkey {
-    get_associated_token_address_and_bump_seed(wallet_address, hpl_token_mint_address, &id()).0
+    get_associated_token_address_and_bump_seed_internal(
+        wallet_address,
+        token_mint_address,
+        &id(),
+        token_program_id,
+    )
+    .0
 }
 
 fn get_associated_token_address_and_bump_seed_internal(
@@ -62,7 +71,7 @@ fn get_associated_token_address_and_bump_seed_internal(
 ///   2. `[]` Wallet address for the new associated token account
 ///   3. `[]` The token mint for the new associated token account
 ///   4. `[]` System program
-///   5. `[]` HPL Token program
+///   5. `[]` HPL Token program, or another program following its interface
 ///   6. `[]` Rent sysvar
 ///
 pub fn create_associated_token_account(
@@ -70,8 +79,84 @@ pub fn create_associated_token_account(
     wallet_address: &Pubkey,
     hpl_token_mint_address: &Pubkey,
 ) -> Instruction {
-    let associated_account_address =
-        get_associated_token_address(wallet_address, hpl_token_mint_address);
+    create_associated_token_account_with_program_id(
+        funding_address,
+        wallet_address,
+        hpl_token_mint_address,
+        &hpl_token::id(),
+    )
+}
+
+/// Create an associated token account for the given wallet address and token mint of
+/// `token_program_id`, a program following the HPL Token interface
+///
+/// Takes the same accounts as `create_associated_token_account`, with `token_program_id`
+/// in place of the HPL Token program.
+pub fn create_associated_token_account_with_program_id(
+    funding_address: &Pubkey,
+    wallet_address: &Pubkey,
+    token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    // Empty data is read as `Create`, which programs deployed before the
+    // instruction enum also understand
+    build_associated_token_account_instruction(
+        funding_address,
+        wallet_address,
+        token_mint_address,
+        token_program_id,
+        vec![],
+    )
+}
+
+/// Create an associated token account for the given wallet address and token mint,
+/// unless it already exists
+///
+/// Succeeds without changes if the account exists and belongs to the wallet and mint,
+/// so clients don't need to check for the account first. Takes the same accounts as
+/// `create_associated_token_account`.
+pub fn create_associated_token_account_idempotent(
+    funding_address: &Pubkey,
+    wallet_address: &Pubkey,
+    hpl_token_mint_address: &Pubkey,
+) -> Instruction {
+    create_associated_token_account_idempotent_with_program_id(
+        funding_address,
+        wallet_address,
+        hpl_token_mint_address,
+        &hpl_token::id(),
+    )
+}
+
+/// Create an associated token account for the given wallet address and token mint of
+/// `token_program_id`, unless it already exists
+pub fn create_associated_token_account_idempotent_with_program_id(
+    funding_address: &Pubkey,
+    wallet_address: &Pubkey,
+    token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    build_associated_token_account_instruction(
+        funding_address,
+        wallet_address,
+        token_mint_address,
+        token_program_id,
+        instruction::AssociatedTokenAccountInstruction::CreateIdempotent.pack(),
+    )
+}
+
+fn build_associated_token_account_instruction(
+    funding_address: &Pubkey,
+    wallet_address: &Pubkey,
+    token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    let associated_account_address = get_associated_token_address_with_program_id(
+        wallet_address,
+        token_mint_address,
+        token_program_id,
+    );
 
     Instruction {
         program_id: id(),
@@ -79,11 +164,72 @@ pub fn create_associated_token_account(
             AccountMeta::new(*funding_address, true),
             AccountMeta::new(associated_account_address, false),
             AccountMeta::new_readonly(*wallet_address, false),
-            AccountMeta::new_readonly(*hpl_token_mint_address, false),
+            AccountMeta::new_readonly(*token_mint_address, false),
             AccountMeta::new_readonly(huione_program::system_program::id(), false),
-            AccountMeta::new_readonly(hpl_token::id(), false),
+            AccountMeta::new_readonly(*token_program_id, false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
         ],
-        data: vec![],
+        data,
+    }
+}
+
+/// Recover the tokens of a nested associated token account, the associated token account for
+/// `nested_token_mint_address` of the wallet's associated token account for
+/// `owner_token_mint_address`, into the wallet's associated token account for
+/// `nested_token_mint_address`, and close the nested account
+///
+/// The wallet must sign, and its associated token account for `nested_token_mint_address`
+/// must exist.
+pub fn recover_nested(
+    wallet_address: &Pubkey,
+    owner_token_mint_address: &Pubkey,
+    nested_token_mint_address: &Pubkey,
+) -> Instruction {
+    recover_nested_with_program_id(
+        wallet_address,
+        owner_token_mint_address,
+        nested_token_mint_address,
+        &hpl_token::id(),
+    )
+}
+
+/// Recover the tokens of a nested associated token account of `token_program_id`, a program
+/// following the HPL Token interface
+///
+/// Both mints must belong to `token_program_id`.
+pub fn recover_nested_with_program_id(
+    wallet_address: &Pubkey,
+    owner_token_mint_address: &Pubkey,
+    nested_token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let owner_associated_account_address = get_associated_token_address_with_program_id(
+        wallet_address,
+        owner_token_mint_address,
+        token_program_id,
+    );
+    let destination_associated_account_address = get_associated_token_address_with_program_id(
+        wallet_address,
+        nested_token_mint_address,
+        token_program_id,
+    );
+    let nested_associated_account_address = get_associated_token_address_with_program_id(
+        &owner_associated_account_address,
+        nested_token_mint_address,
+        token_program_id,
+    );
+
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(nested_associated_account_address, false),
+            AccountMeta::new_readonly(*nested_token_mint_address, false),
+            AccountMeta::new(destination_associated_account_address, false),
+            AccountMeta::new_readonly(owner_associated_account_address, false),
+            AccountMeta::new_readonly(*owner_token_mint_address, false),
+            AccountMeta::new(*wallet_address, true),
+            AccountMeta::new_readonly(*token_program_id, false),
+        ],
+        data: instruction::AssociatedTokenAccountInstruction::RecoverNested.pack(),
     }
 }
@@ -1,9 +1,12 @@
 //! Program state processor
 
-use crate::*;
+use crate::{
+    error::AssociatedTokenAccountError, instruction::AssociatedTokenAccountInstruction, *,
+};
 use huione_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    instruction::Instruction,
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
@@ -13,38 +16,102 @@ use huione_program::{
     sysvar::Sysvar,
 };
 
+/// Whether creating an account that exists is an error
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CreateMode {
+    Always,
+    Idempotent,
+}
+
+/// Points an HPL Token instruction at `token_program_id`. The HPL Token builders only accept
+/// the HPL Token program id, and programs following its interface take the same instructions.
+fn for_token_program(
+    token_program_id: &Pubkey,
+    instruction: Result<Instruction, ProgramError>,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = instruction?;
+    instruction.program_id = *token_program_id;
+    Ok(instruction)
+}
+
 /// Instruction processor
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _input: &[u8],
+    input: &[u8],
+) -> ProgramResult {
+    match AssociatedTokenAccountInstruction::unpack(input)? {
+        AssociatedTokenAccountInstruction::Create => {
+            msg!("Instruction: Create");
+            process_create_associated_token_account(program_id, accounts, CreateMode::Always)
+        }
+        AssociatedTokenAccountInstruction::CreateIdempotent => {
+            msg!("Instruction: CreateIdempotent");
+            process_create_associated_token_account(program_id, accounts, CreateMode::Idempotent)
+        }
+        AssociatedTokenAccountInstruction::RecoverNested => {
+            msg!("Instruction: RecoverNested");
+            process_recover_nested(program_id, accounts)
+        }
+    }
+}
+
+fn process_create_associated_token_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    create_mode: CreateMode,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let funder_info = next_account_info(account_info_iter)?;
     let associated_token_account_info = next_account_info(account_info_iter)?;
     let wallet_account_info = next_account_info(account_info_iter)?;
-    let hpl_token_mint_info = next_account_info(account_info_iter)?;
+    let token_mint_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
-    let hpl_token_program_info = next_account_info(account_info_iter)?;
-    let hpl_token_program_id = hpl_token_program_info.key;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let token_program_id = token_program_info.key;
     let rent_sysvar_info = next_account_info(account_info_iter)?;
 
     let (associated_token_address, bump_seed) = get_associated_token_address_and_bump_seed_internal(
         wallet_account_info.key,
-        hpl_token_mint_info.key,
+        token_mint_info.key,
         program_id,
-        hpl_token_program_id,
+        token_program_id,
     );
     if associated_token_address != *associated_token_account_info.key {
         msg!("Error: Associated address does not match seed derivation");
         return Err(ProgramError::InvalidSeeds);
     }
 
+    if token_mint_info.owner != token_program_id {
+        msg!("Error: Token mint is not owned by the token program");
+        return Err(AssociatedTokenAccountError::InvalidMintOwner.into());
+    }
+
+    if create_mode == CreateMode::Idempotent
+        && associated_token_account_info.owner == token_program_id
+    {
+        if let Ok(associated_token_account) =
+            hpl_token::state::Account::unpack(&associated_token_account_info.data.borrow())
+        {
+            if associated_token_account.mint != *token_mint_info.key {
+                msg!("Error: Associated token account belongs to another mint");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            // The owner of the account may have been changed with `SetAuthority`
+            if associated_token_account.owner != *wallet_account_info.key {
+                msg!("Error: Associated token account belongs to another wallet");
+                return Err(AssociatedTokenAccountError::InvalidOwner.into());
+            }
+            msg!("Associated token account already exists");
+            return Ok(());
+        }
+    }
+
     let associated_token_account_signer_seeds: &[&[_]] = &[
         &wallet_account_info.key.to_bytes(),
-        &hpl_token_program_id.to_bytes(),
-        &hpl_token_mint_info.key.to_bytes(),
+        &token_program_id.to_bytes(),
+        &token_mint_info.key.to_bytes(),
         &[bump_seed],
     ];
 
@@ -87,9 +154,9 @@ pub fn process_instruction(
         &[associated_token_account_signer_seeds],
     )?;
 
-    msg!("Assign the associated token account to the HPL Token program");
+    msg!("Assign the associated token account to the token program");
     invoke_signed(
-        &system_instruction::assign(associated_token_account_info.key, hpl_token_program_id),
+        &system_instruction::assign(associated_token_account_info.key, token_program_id),
         &[
             associated_token_account_info.clone(),
             system_program_info.clone(),
@@ -99,18 +166,152 @@ pub fn process_instruction(
 
     msg!("Initialize the associated token account");
     invoke(
-        &hpl_token::instruction::initialize_account(
-            hpl_token_program_id,
-            associated_token_account_info.key,
-            hpl_token_mint_info.key,
-            wallet_account_info.key,
+        &for_token_program(
+            token_program_id,
+            hpl_token::instruction::initialize_account(
+                &hpl_token::id(),
+                associated_token_account_info.key,
+                token_mint_info.key,
+                wallet_account_info.key,
+            ),
         )?,
         &[
             associated_token_account_info.clone(),
-            hpl_token_mint_info.clone(),
+            token_mint_info.clone(),
             wallet_account_info.clone(),
             rent_sysvar_info.clone(),
-            hpl_token_program_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+fn process_recover_nested(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let nested_associated_token_account_info = next_account_info(account_info_iter)?;
+    let nested_token_mint_info = next_account_info(account_info_iter)?;
+    let destination_associated_token_account_info = next_account_info(account_info_iter)?;
+    let owner_associated_token_account_info = next_account_info(account_info_iter)?;
+    let owner_token_mint_info = next_account_info(account_info_iter)?;
+    let wallet_account_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let token_program_id = token_program_info.key;
+
+    let (owner_associated_token_address, bump_seed) =
+        get_associated_token_address_and_bump_seed_internal(
+            wallet_account_info.key,
+            owner_token_mint_info.key,
+            program_id,
+            token_program_id,
+        );
+    if owner_associated_token_address != *owner_associated_token_account_info.key {
+        msg!("Error: Owner associated address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (nested_associated_token_address, _) = get_associated_token_address_and_bump_seed_internal(
+        owner_associated_token_account_info.key,
+        nested_token_mint_info.key,
+        program_id,
+        token_program_id,
+    );
+    if nested_associated_token_address != *nested_associated_token_account_info.key {
+        msg!("Error: Nested associated address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let (destination_associated_token_address, _) =
+        get_associated_token_address_and_bump_seed_internal(
+            wallet_account_info.key,
+            nested_token_mint_info.key,
+            program_id,
+            token_program_id,
+        );
+    if destination_associated_token_address != *destination_associated_token_account_info.key {
+        msg!("Error: Destination associated address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !wallet_account_info.is_signer {
+        msg!("Error: Wallet of the owner associated token account must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The owner associated token account signs for the nested account, so it
+    // must hold the tokens of the signing wallet
+    if owner_associated_token_account_info.owner != token_program_id {
+        msg!("Error: Owner associated token account is not a token account");
+        return Err(AssociatedTokenAccountError::NotTokenAccount.into());
+    }
+    let owner_associated_token_account =
+        hpl_token::state::Account::unpack(&owner_associated_token_account_info.data.borrow())?;
+    if owner_associated_token_account.owner != *wallet_account_info.key {
+        msg!("Error: Owner associated token account does not belong to the wallet");
+        return Err(AssociatedTokenAccountError::InvalidOwner.into());
+    }
+
+    if nested_associated_token_account_info.owner != token_program_id {
+        msg!("Error: Nested associated token account is not a token account");
+        return Err(AssociatedTokenAccountError::NotTokenAccount.into());
+    }
+    let amount =
+        hpl_token::state::Account::unpack(&nested_associated_token_account_info.data.borrow())?
+            .amount;
+    let decimals = hpl_token::state::Mint::unpack(&nested_token_mint_info.data.borrow())?.decimals;
+
+    let owner_associated_token_account_signer_seeds: &[&[_]] = &[
+        &wallet_account_info.key.to_bytes(),
+        &token_program_id.to_bytes(),
+        &owner_token_mint_info.key.to_bytes(),
+        &[bump_seed],
+    ];
+
+    msg!(
+        "Transfer {} tokens out of the nested associated token account",
+        amount
+    );
+    invoke_signed(
+        &for_token_program(
+            token_program_id,
+            hpl_token::instruction::transfer_checked(
+                &hpl_token::id(),
+                nested_associated_token_account_info.key,
+                nested_token_mint_info.key,
+                destination_associated_token_account_info.key,
+                owner_associated_token_account_info.key,
+                &[],
+                amount,
+                decimals,
+            ),
+        )?,
+        &[
+            nested_associated_token_account_info.clone(),
+            nested_token_mint_info.clone(),
+            destination_associated_token_account_info.clone(),
+            owner_associated_token_account_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[owner_associated_token_account_signer_seeds],
+    )?;
+
+    msg!("Close the nested associated token account");
+    invoke_signed(
+        &for_token_program(
+            token_program_id,
+            hpl_token::instruction::close_account(
+                &hpl_token::id(),
+                nested_associated_token_account_info.key,
+                wallet_account_info.key,
+                owner_associated_token_account_info.key,
+                &[],
+            ),
+        )?,
+        &[
+            nested_associated_token_account_info.clone(),
+            wallet_account_info.clone(),
+            owner_associated_token_account_info.clone(),
+            token_program_info.clone(),
         ],
+        &[owner_associated_token_account_signer_seeds],
     )
 }
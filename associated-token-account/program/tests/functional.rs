@@ -1,32 +1,57 @@
 // Mark this test as BPF-only due to current `ProgramTest` limitations when CPIing into the system program
 #![cfg(feature = "test-bpf")]
 
-use huione_program::{instruction::*, program_pack::Pack, pubkey::Pubkey, system_instruction};
+use hpl_associated_token_account::{error::AssociatedTokenAccountError, *};
+use hpl_token::state::{Account as TokenAccount, AccountState, Mint};
+use huione_program::{
+    hash::Hash, instruction::*, program_pack::Pack, pubkey::Pubkey, rent::Rent, system_instruction,
+};
 use huione_program_test::*;
 use huione_sdk::{
-    signature::Signer,
+    account::Account,
+    signature::{Keypair, Signer},
     transaction::{Transaction, TransactionError},
+    transport::TransportError,
 };
-use spl_associated_token_account::*;
+
+/// A second build of the HPL Token program, deployed at another address
+fn second_token_program_id() -> Pubkey {
+    Pubkey::new(&[7; 32])
+}
 
 fn program_test(token_mint_address: Pubkey) -> ProgramTest {
     let mut pc = ProgramTest::new(
-        "spl_associated_token_account",
+        "hpl_associated_token_account",
         id(),
         processor!(processor::process_instruction),
     );
+    pc.add_program(
+        "hpl_token",
+        hpl_token::id(),
+        processor!(hpl_token::processor::Processor::process),
+    );
+    pc.add_program(
+        "hpl_token_second",
+        second_token_program_id(),
+        processor!(hpl_token::processor::Processor::process),
+    );
 
     // Add a token mint account
-    //
-    // The account data was generated by running:
-    //      $ huione account EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v \
-    //                       --output-file tests/fixtures/token-mint-data.bin
-    //
-    pc.add_account_with_file_data(
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut data);
+    pc.add_account(
         token_mint_address,
-        1461600,
-        spl_token::id(),
-        "token-mint-data.bin",
+        Account {
+            lamports: 1461600,
+            data,
+            owner: hpl_token::id(),
+            ..Account::default()
+        },
     );
 
     // Dial down the BPF compute budget to detect if the program gets bloated in the future
@@ -35,6 +60,60 @@ fn program_test(token_mint_address: Pubkey) -> ProgramTest {
     pc
 }
 
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+/// Creates a mint of `token_program_id` whose mint authority is the payer
+async fn create_mint(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    token_program_id: &Pubkey,
+) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                token_program_id,
+            ),
+            Instruction {
+                program_id: *token_program_id,
+                ..hpl_token::instruction::initialize_mint(
+                    &hpl_token::id(),
+                    &mint.pubkey(),
+                    &payer.pubkey(),
+                    None,
+                    0,
+                )
+                .unwrap()
+            },
+        ],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+    mint.pubkey()
+}
+
 #[tokio::test]
 async fn test_associated_token_address() {
     let wallet_address = Pubkey::new_unique();
@@ -45,7 +124,7 @@ async fn test_associated_token_address() {
     let (mut banks_client, payer, recent_blockhash) =
         program_test(token_mint_address).start().await;
     let rent = banks_client.get_rent().await.unwrap();
-    let expected_token_account_balance = rent.minimum_balance(spl_token::state::Account::LEN);
+    let expected_token_account_balance = rent.minimum_balance(TokenAccount::LEN);
 
     // Associated account does not exist
     assert_eq!(
@@ -73,11 +152,8 @@ async fn test_associated_token_address() {
         .await
         .expect("get_account")
         .expect("associated_account not none");
-    assert_eq!(
-        associated_account.data.len(),
-        spl_token::state::Account::LEN
-    );
-    assert_eq!(associated_account.owner, spl_token::id());
+    assert_eq!(associated_account.data.len(), TokenAccount::LEN);
+    assert_eq!(associated_account.owner, hpl_token::id());
     assert_eq!(associated_account.lamports, expected_token_account_balance);
 }
 
@@ -91,7 +167,7 @@ async fn test_create_with_a_lamport() {
     let (mut banks_client, payer, recent_blockhash) =
         program_test(token_mint_address).start().await;
     let rent = banks_client.get_rent().await.unwrap();
-    let expected_token_account_balance = rent.minimum_balance(spl_token::state::Account::LEN);
+    let expected_token_account_balance = rent.minimum_balance(TokenAccount::LEN);
 
     // Transfer 1 lamport into `associated_token_address` before creating it
     let mut transaction = Transaction::new_with_payer(
@@ -144,7 +220,7 @@ async fn test_create_with_excess_lamports() {
     let (mut banks_client, payer, recent_blockhash) =
         program_test(token_mint_address).start().await;
     let rent = banks_client.get_rent().await.unwrap();
-    let expected_token_account_balance = rent.minimum_balance(spl_token::state::Account::LEN);
+    let expected_token_account_balance = rent.minimum_balance(TokenAccount::LEN);
 
     // Transfer 1 lamport into `associated_token_address` before creating it
     let mut transaction = Transaction::new_with_payer(
@@ -242,3 +318,593 @@ async fn test_create_account_mismatch() {
         TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
     );
 }
+
+#[tokio::test]
+async fn test_create_idempotent() {
+    let wallet_address = Pubkey::new_unique();
+    let token_mint_address = Pubkey::new_unique();
+    let associated_token_address =
+        get_associated_token_address(&wallet_address, &token_mint_address);
+
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(token_mint_address).start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let expected_token_account_balance = rent.minimum_balance(TokenAccount::LEN);
+
+    // The idempotent instruction creates the account like the regular one
+    let mut transaction = Transaction::new_with_payer(
+        &[create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let associated_account = banks_client
+        .get_account(associated_token_address)
+        .await
+        .expect("get_account")
+        .expect("associated_account not none");
+    assert_eq!(associated_account.owner, hpl_token::id());
+    assert_eq!(associated_account.lamports, expected_token_account_balance);
+
+    // Creating it again fails with the regular instruction
+    let recent_blockhash = banks_client
+        .get_new_blockhash(&recent_blockhash)
+        .await
+        .unwrap()
+        .0;
+    let mut transaction = Transaction::new_with_payer(
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_err());
+
+    // ...and succeeds without changes with the idempotent one, even in the
+    // same transaction as another
+    let instruction = create_associated_token_account_idempotent(
+        &payer.pubkey(),
+        &wallet_address,
+        &token_mint_address,
+    );
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction.clone(), instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let unchanged_account = banks_client
+        .get_account(associated_token_address)
+        .await
+        .expect("get_account")
+        .expect("associated_account not none");
+    assert_eq!(unchanged_account, associated_account);
+}
+
+#[tokio::test]
+async fn test_create_idempotent_invalid_owner() {
+    let wallet = Keypair::new();
+    let token_mint_address = Pubkey::new_unique();
+    let associated_token_address =
+        get_associated_token_address(&wallet.pubkey(), &token_mint_address);
+
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(token_mint_address).start().await;
+
+    // The wallet hands its associated token account over to another owner
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            create_associated_token_account(&payer.pubkey(), &wallet.pubkey(), &token_mint_address),
+            hpl_token::instruction::set_authority(
+                &hpl_token::id(),
+                &associated_token_address,
+                Some(&Pubkey::new_unique()),
+                hpl_token::instruction::AuthorityType::AccountOwner,
+                &wallet.pubkey(),
+                &[],
+            )
+            .unwrap(),
+        ],
+        &[&wallet],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                &wallet.pubkey(),
+                &token_mint_address,
+            )],
+            &[],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AssociatedTokenAccountError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_create_idempotent_mint_mismatch() {
+    let wallet_address = Pubkey::new_unique();
+    let token_mint_address = Pubkey::new_unique();
+    let associated_token_address =
+        get_associated_token_address(&wallet_address, &token_mint_address);
+
+    // A token account of another mint sits at the associated address
+    let mut pc = program_test(token_mint_address);
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: Pubkey::new_unique(),
+        owner: wallet_address,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    }
+    .pack_into_slice(&mut data);
+    pc.add_account(
+        associated_token_address,
+        Account {
+            lamports: Rent::default().minimum_balance(TokenAccount::LEN),
+            data,
+            owner: hpl_token::id(),
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = pc.start().await;
+
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                &wallet_address,
+                &token_mint_address,
+            )],
+            &[],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_instruction_data() {
+    let wallet_address = Pubkey::new_unique();
+    let token_mint_address = Pubkey::new_unique();
+
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(token_mint_address).start().await;
+
+    let mut instruction =
+        create_associated_token_account(&payer.pubkey(), &wallet_address, &token_mint_address);
+    instruction.data = vec![9]; // <-- Unknown instruction
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[instruction],
+            &[]
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+}
+
+#[tokio::test]
+async fn test_create_with_second_token_program() {
+    let wallet_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(Pubkey::new_unique()).start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let token_program_id = second_token_program_id();
+    let token_mint_address = create_mint(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &token_program_id,
+    )
+    .await;
+
+    // The token program is part of the derivation
+    let associated_token_address = get_associated_token_address_with_program_id(
+        &wallet_address,
+        &token_mint_address,
+        &token_program_id,
+    );
+    assert_ne!(
+        associated_token_address,
+        get_associated_token_address(&wallet_address, &token_mint_address)
+    );
+
+    // The mint does not belong to the HPL Token program
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[create_associated_token_account(
+                &payer.pubkey(),
+                &wallet_address,
+                &token_mint_address,
+            )],
+            &[],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AssociatedTokenAccountError::InvalidMintOwner as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[create_associated_token_account_with_program_id(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+            &token_program_id,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let associated_account = banks_client
+        .get_account(associated_token_address)
+        .await
+        .expect("get_account")
+        .expect("associated_account not none");
+    assert_eq!(associated_account.owner, token_program_id);
+    assert_eq!(
+        associated_account.lamports,
+        rent.minimum_balance(TokenAccount::LEN)
+    );
+    let token_account = TokenAccount::unpack(&associated_account.data).unwrap();
+    assert_eq!(token_account.owner, wallet_address);
+    assert_eq!(token_account.mint, token_mint_address);
+
+    // The existing account is recognized as the second token program's
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[create_associated_token_account_idempotent_with_program_id(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+            &token_program_id,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_recover_nested() {
+    let wallet = Keypair::new();
+    let owner_token_mint_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(owner_token_mint_address).start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let nested_token_mint_address = create_mint(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &hpl_token::id(),
+    )
+    .await;
+
+    let owner_associated_token_address =
+        get_associated_token_address(&wallet.pubkey(), &owner_token_mint_address);
+    let nested_associated_token_address =
+        get_associated_token_address(&owner_associated_token_address, &nested_token_mint_address);
+    let destination_associated_token_address =
+        get_associated_token_address(&wallet.pubkey(), &nested_token_mint_address);
+
+    // Tokens sent to the associated token address of the wallet's associated
+    // token account rather than of the wallet
+    for (wallet_address, token_mint_address) in [
+        (wallet.pubkey(), owner_token_mint_address),
+        (owner_associated_token_address, nested_token_mint_address),
+        (wallet.pubkey(), nested_token_mint_address),
+    ]
+    .iter()
+    {
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[create_associated_token_account(
+                &payer.pubkey(),
+                wallet_address,
+                token_mint_address,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[hpl_token::instruction::mint_to(
+            &hpl_token::id(),
+            &nested_token_mint_address,
+            &nested_associated_token_address,
+            &payer.pubkey(),
+            &[],
+            100,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Only the wallet can recover them
+    let mut instruction = recover_nested(
+        &wallet.pubkey(),
+        &owner_token_mint_address,
+        &nested_token_mint_address,
+    );
+    instruction.accounts[5] = AccountMeta::new(wallet.pubkey(), false);
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[instruction],
+            &[]
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[recover_nested(
+            &wallet.pubkey(),
+            &owner_token_mint_address,
+            &nested_token_mint_address,
+        )],
+        &[&wallet],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        banks_client
+            .get_account(nested_associated_token_address)
+            .await
+            .expect("get_account"),
+        None,
+    );
+    let destination_account = banks_client
+        .get_account(destination_associated_token_address)
+        .await
+        .expect("get_account")
+        .expect("destination_account not none");
+    assert_eq!(
+        TokenAccount::unpack(&destination_account.data)
+            .unwrap()
+            .amount,
+        100
+    );
+    assert_eq!(
+        banks_client.get_balance(wallet.pubkey()).await.unwrap(),
+        rent.minimum_balance(TokenAccount::LEN)
+    );
+}
+
+#[tokio::test]
+async fn test_recover_nested_errors() {
+    let wallet = Keypair::new();
+    let owner_token_mint_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(owner_token_mint_address).start().await;
+    let nested_token_mint_address = create_mint(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &hpl_token::id(),
+    )
+    .await;
+    let other_token_mint_address = create_mint(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &hpl_token::id(),
+    )
+    .await;
+
+    let owner_associated_token_address =
+        get_associated_token_address(&wallet.pubkey(), &owner_token_mint_address);
+    let expect_error = |error: AssociatedTokenAccountError| {
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    };
+
+    for (wallet_address, token_mint_address) in [
+        (owner_associated_token_address, nested_token_mint_address),
+        (wallet.pubkey(), nested_token_mint_address),
+        (wallet.pubkey(), other_token_mint_address),
+    ]
+    .iter()
+    {
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[create_associated_token_account(
+                &payer.pubkey(),
+                wallet_address,
+                token_mint_address,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    let instruction = recover_nested(
+        &wallet.pubkey(),
+        &owner_token_mint_address,
+        &nested_token_mint_address,
+    );
+
+    // The owner associated, nested associated and destination associated
+    // accounts must all derive from the wallet and the mints
+    for account_index in [3, 0, 2].iter() {
+        let mut instruction = instruction.clone();
+        instruction.accounts[*account_index].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            process(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[instruction],
+                &[&wallet],
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+    }
+
+    // The wallet must sign
+    let mut unsigned_instruction = instruction.clone();
+    unsigned_instruction.accounts[5].is_signer = false;
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[unsigned_instruction],
+            &[],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+
+    // The owner associated token account does not exist
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[instruction.clone()],
+            &[&wallet],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        expect_error(AssociatedTokenAccountError::NotTokenAccount)
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet.pubkey(),
+            &owner_token_mint_address,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // The nested associated token account does not exist
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[recover_nested(
+                &wallet.pubkey(),
+                &owner_token_mint_address,
+                &other_token_mint_address,
+            )],
+            &[&wallet],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        expect_error(AssociatedTokenAccountError::NotTokenAccount)
+    );
+
+    // The owner associated token account was handed over to another owner
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[hpl_token::instruction::set_authority(
+            &hpl_token::id(),
+            &owner_associated_token_address,
+            Some(&Pubkey::new_unique()),
+            hpl_token::instruction::AuthorityType::AccountOwner,
+            &wallet.pubkey(),
+            &[],
+        )
+        .unwrap()],
+        &[&wallet],
+    )
+    .await
+    .unwrap();
+    let recent_blockhash = banks_client
+        .get_new_blockhash(&recent_blockhash)
+        .await
+        .unwrap()
+        .0;
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[instruction],
+            &[&wallet],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        expect_error(AssociatedTokenAccountError::InvalidOwner)
+    );
+}
@@ -0,0 +1,168 @@
+//! Memos encrypted to a recipient's key
+//!
+//! The memo is encrypted with ChaCha20-Poly1305 under a key agreed between a one-time x25519
+//! key and the recipient's ed25519 key, converted to x25519. The program only checks the
+//! envelope, so the plaintext never reaches the logs; the recipient's wallet decrypts it
+//! with `decrypt_memo`.
+//!
+//! Envelope: `ENCRYPTED_MEMO_PREFIX`, the one-time public key (32 bytes), then the
+//! ciphertext followed by its tag (16 bytes).
+
+/// First byte of an encrypted memo. No UTF-8 string starts with it, so plain memos are never
+/// read as encrypted memos.
+pub const ENCRYPTED_MEMO_PREFIX: u8 = 0xFC;
+
+/// Length of an encrypted empty memo: the prefix, the one-time public key and the tag
+pub const ENCRYPTED_MEMO_MIN_LEN: usize = 1 + 32 + 16;
+
+#[cfg(not(target_arch = "bpf"))]
+pub use client::*;
+
+#[cfg(not(target_arch = "bpf"))]
+mod client {
+    use super::*;
+    use chacha20poly1305::{
+        aead::{Aead, NewAead},
+        ChaCha20Poly1305, Key, Nonce,
+    };
+    use curve25519_dalek::{
+        constants::X25519_BASEPOINT, edwards::CompressedEdwardsY, montgomery::MontgomeryPoint,
+        scalar::Scalar,
+    };
+    use huione_program::pubkey::Pubkey;
+    use rand::{rngs::OsRng, RngCore};
+    use sha2::{Digest, Sha256, Sha512};
+    use thiserror::Error;
+
+    /// Errors encrypting or decrypting a memo
+    #[derive(Clone, Debug, Eq, Error, PartialEq)]
+    pub enum EncryptionError {
+        /// The recipient address is not a usable ed25519 public key
+        #[error("Recipient is not an ed25519 public key")]
+        InvalidRecipient,
+        /// The data is not an encrypted memo envelope
+        #[error("Not an encrypted memo")]
+        InvalidEnvelope,
+        /// The memo was not encrypted to this key, or was altered
+        #[error("Memo could not be decrypted with this key")]
+        DecryptionFailed,
+    }
+
+    /// Whether `data` is an encrypted memo envelope
+    pub fn is_encrypted_memo(data: &[u8]) -> bool {
+        data.len() >= ENCRYPTED_MEMO_MIN_LEN && data[0] == ENCRYPTED_MEMO_PREFIX
+    }
+
+    /// Encrypts `memo` to the holder of the ed25519 key `recipient`, for use as instruction
+    /// data of a memo
+    pub fn encrypt_memo(memo: &[u8], recipient: &Pubkey) -> Result<Vec<u8>, EncryptionError> {
+        let recipient = CompressedEdwardsY(recipient.to_bytes())
+            .decompress()
+            .ok_or(EncryptionError::InvalidRecipient)?
+            .to_montgomery();
+
+        let mut ephemeral_secret = [0; 32];
+        OsRng.fill_bytes(&mut ephemeral_secret);
+        let ephemeral_secret = clamp(ephemeral_secret);
+        let ephemeral = X25519_BASEPOINT * ephemeral_secret;
+        let shared = recipient * ephemeral_secret;
+        if shared == MontgomeryPoint([0; 32]) {
+            return Err(EncryptionError::InvalidRecipient);
+        }
+
+        let ciphertext = cipher(&shared, &ephemeral, &recipient)
+            .encrypt(Nonce::from_slice(&[0; 12]), memo)
+            .map_err(|_| EncryptionError::InvalidRecipient)?;
+        let mut data = Vec::with_capacity(ENCRYPTED_MEMO_MIN_LEN + memo.len());
+        data.push(ENCRYPTED_MEMO_PREFIX);
+        data.extend_from_slice(ephemeral.as_bytes());
+        data.extend(ciphertext);
+        Ok(data)
+    }
+
+    /// Decrypts an encrypted memo with the recipient's 32 byte ed25519 secret key, as held by
+    /// wallets
+    pub fn decrypt_memo(data: &[u8], secret_key: &[u8; 32]) -> Result<Vec<u8>, EncryptionError> {
+        if !is_encrypted_memo(data) {
+            return Err(EncryptionError::InvalidEnvelope);
+        }
+        let mut ephemeral = [0; 32];
+        ephemeral.copy_from_slice(&data[1..33]);
+        let ephemeral = MontgomeryPoint(ephemeral);
+
+        // The x25519 secret is the scalar that ed25519 derives from the secret key
+        let mut scalar = [0; 32];
+        scalar.copy_from_slice(&Sha512::digest(secret_key)[..32]);
+        let secret = clamp(scalar);
+        let recipient = X25519_BASEPOINT * secret;
+        let shared = ephemeral * secret;
+
+        cipher(&shared, &ephemeral, &recipient)
+            .decrypt(Nonce::from_slice(&[0; 12]), &data[33..])
+            .map_err(|_| EncryptionError::DecryptionFailed)
+    }
+
+    fn clamp(mut bytes: [u8; 32]) -> Scalar {
+        bytes[0] &= 248;
+        bytes[31] &= 127;
+        bytes[31] |= 64;
+        Scalar::from_bits(bytes)
+    }
+
+    /// Cipher keyed by the shared secret and both public keys. Each key encrypts a single
+    /// memo, since the one-time key is new for every memo, so the nonce is fixed.
+    fn cipher(
+        shared: &MontgomeryPoint,
+        ephemeral: &MontgomeryPoint,
+        recipient: &MontgomeryPoint,
+    ) -> ChaCha20Poly1305 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"hpl-memo encryption");
+        hasher.update(shared.as_bytes());
+        hasher.update(ephemeral.as_bytes());
+        hasher.update(recipient.as_bytes());
+        ChaCha20Poly1305::new(Key::from_slice(hasher.finalize().as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use huione_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let recipient = Keypair::new();
+        let memo = "INV-1042 for Jo".as_bytes();
+
+        let data = encrypt_memo(memo, &recipient.pubkey()).unwrap();
+        assert!(is_encrypted_memo(&data));
+        assert_eq!(data.len(), ENCRYPTED_MEMO_MIN_LEN + memo.len());
+        assert!(std::str::from_utf8(&data).is_err());
+        assert_eq!(
+            decrypt_memo(&data, &recipient.secret().to_bytes()),
+            Ok(memo.to_vec())
+        );
+
+        // Each memo uses a new one-time key
+        assert_ne!(encrypt_memo(memo, &recipient.pubkey()).unwrap(), data);
+
+        let other = Keypair::new();
+        assert_eq!(
+            decrypt_memo(&data, &other.secret().to_bytes()),
+            Err(EncryptionError::DecryptionFailed)
+        );
+
+        let mut altered = data.clone();
+        *altered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            decrypt_memo(&altered, &recipient.secret().to_bytes()),
+            Err(EncryptionError::DecryptionFailed)
+        );
+
+        assert_eq!(
+            decrypt_memo(memo, &recipient.secret().to_bytes()),
+            Err(EncryptionError::InvalidEnvelope)
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Error types
+
+use huione_program::{decode_error::DecodeError, program_error::ProgramError};
+use num_derive::FromPrimitive;
+use thiserror::Error;
+
+/// Errors that may be returned by the Memo program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum MemoError {
+    // 0
+    /// The v2 memo envelope could not be parsed
+    #[error("Invalid v2 memo envelope")]
+    InvalidEnvelope,
+    /// The v2 memo is longer than the size limit
+    #[error("Memo exceeds the v2 size limit")]
+    MemoTooLong,
+    /// The invoice id is empty or longer than the size limit
+    #[error("Invoice id is empty or too long")]
+    InvalidInvoiceId,
+    /// The memo lists more reference keys than allowed
+    #[error("Too many reference keys")]
+    TooManyReferences,
+    /// The expiry of the memo has passed
+    #[error("Memo expired")]
+    Expired,
+
+    // 5
+    /// A reference account is writable
+    #[error("Reference account must be read-only")]
+    WritableReference,
+}
+impl From<MemoError> for ProgramError {
+    fn from(e: MemoError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+impl<T> DecodeError<T> for MemoError {
+    fn type_of() -> &'static str {
+        "MemoError"
+    }
+}
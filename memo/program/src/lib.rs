@@ -1,10 +1,15 @@
 #![deny(missing_docs)]
 
 //! A program that accepts a string of encoded characters and verifies that it parses,
-//! while verifying and logging signers. Currently handles UTF-8 characters.
+//! while verifying and logging signers. Currently handles UTF-8 characters, signed v2
+//! memos carrying an invoice id, reference keys and an expiry, and memos encrypted to a
+//! recipient.
 
+pub mod encryption;
 mod entrypoint;
+pub mod error;
 pub mod processor;
+pub mod v2;
 
 // Export current sdk types for downstream users building with a different sdk version
 pub use huione_program;
@@ -20,6 +25,19 @@ use huione_program::{
 
 huione_program::declare_id!("HuiMemo111111111111111111111111111111111111");
 
+/// First byte of a memo followed by reference accounts. The next byte is the number of
+/// reference accounts, which are the last accounts of the instruction, and the memo follows.
+/// No UTF-8 string starts with it, so plain memos are never read this way.
+pub const MEMO_REFERENCES_PREFIX: u8 = 0xFD;
+
+/// First byte of a UTF-8 memo logged in bounded form: memos longer than
+/// `MAX_LOGGED_MEMO_LEN` are logged as their SHA-256 hash and first `MAX_LOGGED_MEMO_LEN`
+/// bytes, which keeps the compute cost of long memos low.
+pub const MEMO_BOUNDED_LOG_PREFIX: u8 = 0xFB;
+
+/// Longest memo logged in full in bounded log mode, in bytes
+pub const MAX_LOGGED_MEMO_LEN: usize = 128;
+
 /// Build a memo instruction, possibly signed
 ///
 /// Accounts expected by this instruction:
@@ -37,3 +55,59 @@ pub fn build_memo(memo: &[u8], signer_pubkeys: &[&Pubkey]) -> Instruction {
         data: memo.to_vec(),
     }
 }
+
+/// Build a signed v2 memo instruction, with the envelope serialized with borsh
+///
+/// Accounts expected by this instruction:
+///
+///   0. ..0+N. `[signer]` Expected signers; at least one is required
+///
+pub fn build_memo_v2(memo: &v2::MemoV2, signer_pubkeys: &[&Pubkey]) -> Instruction {
+    build_memo(&memo.pack(v2::MemoV2Format::Borsh), signer_pubkeys)
+}
+
+/// Build a memo instruction, possibly signed, that lists read-only reference accounts
+///
+/// The references need not sign; they let the transaction be looked up by address, for
+/// example with a key generated for each invoice.
+///
+/// Accounts expected by this instruction:
+///
+///   0. ..0+N. `[signer]` Expected signers
+///   N+1. ..N+1+M. `[]` Reference accounts, logged by the program
+///
+pub fn build_memo_with_references(
+    memo: &[u8],
+    signer_pubkeys: &[&Pubkey],
+    reference_pubkeys: &[&Pubkey],
+) -> Instruction {
+    if reference_pubkeys.is_empty() {
+        return build_memo(memo, signer_pubkeys);
+    }
+    assert!(
+        reference_pubkeys.len() <= u8::MAX as usize,
+        "too many reference accounts"
+    );
+
+    let mut instruction = build_memo(memo, signer_pubkeys);
+    instruction.accounts.extend(
+        reference_pubkeys
+            .iter()
+            .map(|&pubkey| AccountMeta::new_readonly(*pubkey, false)),
+    );
+    instruction.data = [MEMO_REFERENCES_PREFIX, reference_pubkeys.len() as u8]
+        .iter()
+        .chain(memo)
+        .cloned()
+        .collect();
+    instruction
+}
+
+/// Build a memo instruction, possibly signed, whose memo the program logs in bounded form
+///
+/// Takes the same accounts as `build_memo`.
+pub fn build_memo_with_bounded_log(memo: &[u8], signer_pubkeys: &[&Pubkey]) -> Instruction {
+    let mut instruction = build_memo(memo, signer_pubkeys);
+    instruction.data.insert(0, MEMO_BOUNDED_LOG_PREFIX);
+    instruction
+}
@@ -1,8 +1,14 @@
 //! Program state processor
 
+use crate::{
+    encryption::{ENCRYPTED_MEMO_MIN_LEN, ENCRYPTED_MEMO_PREFIX},
+    error::MemoError,
+    v2::{MemoV2, MEMO_V2_VERSION},
+    MAX_LOGGED_MEMO_LEN, MEMO_BOUNDED_LOG_PREFIX, MEMO_REFERENCES_PREFIX,
+};
 use huione_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
-    pubkey::Pubkey,
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, hash::hash, msg,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
 };
 use std::str::from_utf8;
 
@@ -12,6 +18,30 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     input: &[u8],
 ) -> ProgramResult {
+    let (accounts, input) = match input {
+        [MEMO_REFERENCES_PREFIX, count, memo @ ..] => {
+            let count = *count as usize;
+            if count == 0 {
+                msg!("Error: no reference accounts");
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            if count > accounts.len() {
+                msg!("Error: expected {} reference accounts", count);
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+            let (signers, references) = accounts.split_at(accounts.len() - count);
+            for reference in references {
+                if reference.is_writable {
+                    msg!("Error: reference {} is writable", reference.key);
+                    return Err(MemoError::WritableReference.into());
+                }
+                msg!("Reference {}", reference.key);
+            }
+            (signers, memo)
+        }
+        _ => (accounts, input),
+    };
+
     let account_info_iter = &mut accounts.iter();
     let mut missing_required_signature = false;
     for account_info in account_info_iter {
@@ -25,22 +55,91 @@ pub fn process_instruction(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    if input.first() == Some(&MEMO_V2_VERSION) {
+        return process_memo_v2(!accounts.is_empty(), input);
+    }
+
+    // The ciphertext is not UTF-8 and only the recipient can read it, so only its
+    // length is logged
+    if input.first() == Some(&ENCRYPTED_MEMO_PREFIX) {
+        if input.len() < ENCRYPTED_MEMO_MIN_LEN {
+            msg!(
+                "Encrypted memo is shorter than {} bytes",
+                ENCRYPTED_MEMO_MIN_LEN
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        msg!("Encrypted memo (len {})", input.len());
+        return Ok(());
+    }
+
+    let (input, bounded_log) = match input {
+        [MEMO_BOUNDED_LOG_PREFIX, memo @ ..] => (memo, true),
+        _ => (input, false),
+    };
     let memo = from_utf8(input).map_err(|err| {
         msg!("Invalid UTF-8, from byte {}", err.valid_up_to());
         ProgramError::InvalidInstructionData
     })?;
-    msg!("Memo (len {}): {:?}", memo.len(), memo);
+    if bounded_log && memo.len() > MAX_LOGGED_MEMO_LEN {
+        // Formatting the whole memo costs compute in proportion to its length, while
+        // the hash is computed by a syscall
+        let mut prefix_len = MAX_LOGGED_MEMO_LEN;
+        while !memo.is_char_boundary(prefix_len) {
+            prefix_len -= 1;
+        }
+        msg!(
+            "Memo (len {}, sha256 {}): {:?}...",
+            memo.len(),
+            hash(input),
+            &memo[..prefix_len]
+        );
+    } else {
+        msg!("Memo (len {}): {:?}", memo.len(), memo);
+    }
+
+    Ok(())
+}
+
+/// Checks and logs a v2 memo, which must be signed
+fn process_memo_v2(signed: bool, input: &[u8]) -> ProgramResult {
+    if !signed {
+        msg!("Error: v2 memos must be signed");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let memo = MemoV2::unpack(input).map_err(|err| {
+        msg!("Error: {}", err);
+        err
+    })?;
+    if let Some(expiry) = memo.expiry {
+        if Clock::get()?.unix_timestamp > expiry {
+            msg!("Error: memo expired at {}", expiry);
+            return Err(MemoError::Expired.into());
+        }
+    }
 
+    msg!("Memo v2 invoice: {:?}", memo.invoice_id);
+    for reference in memo.references.iter() {
+        msg!("Reference {}", reference);
+    }
+    if let Some(expiry) = memo.expiry {
+        msg!("Expires at {}", expiry);
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::v2::MemoV2Format;
     use huione_program::{
         account_info::IntoAccountInfo, program_error::ProgramError, pubkey::Pubkey,
     };
-    use huione_sdk::account::Account;
+    use huione_sdk::{
+        account::Account,
+        signature::{Keypair, Signer},
+    };
 
     #[test]
     fn test_utf8_memo() {
@@ -62,6 +161,133 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_memo_v2() {
+        let program_id = Pubkey::new(&[0; 32]);
+        let memo = MemoV2 {
+            invoice_id: "INV-1042".to_string(),
+            references: vec![Pubkey::new_unique()],
+            expiry: None,
+        };
+
+        let pubkey = Pubkey::new_unique();
+        let mut account = Account::default();
+        let signed_account_infos = vec![(&pubkey, true, &mut account).into_account_info()];
+        for format in [MemoV2Format::Borsh, MemoV2Format::Json].iter() {
+            assert_eq!(
+                Ok(()),
+                process_instruction(&program_id, &signed_account_infos, &memo.pack(*format))
+            );
+        }
+
+        // v2 memos must be signed
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            process_instruction(&program_id, &[], &memo.pack(MemoV2Format::Borsh))
+        );
+
+        let mut invalid = memo.pack(MemoV2Format::Borsh);
+        invalid.truncate(4);
+        assert_eq!(
+            Err(MemoError::InvalidEnvelope.into()),
+            process_instruction(&program_id, &signed_account_infos, &invalid)
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        let program_id = Pubkey::new(&[0; 32]);
+        let memo = "🐆".as_bytes();
+
+        let signer = Pubkey::new_unique();
+        let reference = Pubkey::new_unique();
+        let mut signer_account = Account::default();
+        let mut reference_account = Account::default();
+        let data = [&[MEMO_REFERENCES_PREFIX, 1][..], memo].concat();
+
+        let account_infos = vec![
+            (&signer, true, &mut signer_account).into_account_info(),
+            (&reference, false, &mut reference_account).into_account_info(),
+        ];
+        assert_eq!(
+            Ok(()),
+            process_instruction(&program_id, &account_infos, &data)
+        );
+        assert_eq!(
+            Ok(()),
+            process_instruction(&program_id, &account_infos[1..], &data)
+        );
+
+        // Without the prefix, the reference is a missing signer
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            process_instruction(&program_id, &account_infos, memo)
+        );
+
+        let missing_references = [&[MEMO_REFERENCES_PREFIX, 3][..], memo].concat();
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            process_instruction(&program_id, &account_infos, &missing_references)
+        );
+
+        let no_references = [&[MEMO_REFERENCES_PREFIX, 0][..], memo].concat();
+        assert_eq!(
+            Err(ProgramError::InvalidInstructionData),
+            process_instruction(&program_id, &account_infos, &no_references)
+        );
+
+        let mut writable_account_infos = account_infos.clone();
+        writable_account_infos[1].is_writable = true;
+        assert_eq!(
+            Err(MemoError::WritableReference.into()),
+            process_instruction(&program_id, &writable_account_infos, &data)
+        );
+
+        // The memo itself is still checked
+        let invalid_utf8 = [MEMO_REFERENCES_PREFIX, 1, 0xF0, 0x9F, 0xFF, 0x86];
+        assert_eq!(
+            Err(ProgramError::InvalidInstructionData),
+            process_instruction(&program_id, &account_infos, &invalid_utf8)
+        );
+    }
+
+    #[test]
+    fn test_encrypted_memo() {
+        let program_id = Pubkey::new(&[0; 32]);
+        let recipient = Keypair::new();
+
+        let data = crate::encryption::encrypt_memo(b"INV-1042", &recipient.pubkey()).unwrap();
+        assert_eq!(Ok(()), process_instruction(&program_id, &[], &data));
+
+        assert_eq!(
+            Err(ProgramError::InvalidInstructionData),
+            process_instruction(&program_id, &[], &data[..ENCRYPTED_MEMO_MIN_LEN - 1])
+        );
+    }
+
+    #[test]
+    fn test_bounded_log() {
+        let program_id = Pubkey::new(&[0; 32]);
+
+        let short = [&[MEMO_BOUNDED_LOG_PREFIX][..], b"letters and such"].concat();
+        assert_eq!(Ok(()), process_instruction(&program_id, &[], &short));
+
+        // The logged prefix of a long memo ends on a character boundary
+        let long = [
+            &[MEMO_BOUNDED_LOG_PREFIX][..],
+            "🐆".repeat(MAX_LOGGED_MEMO_LEN).as_bytes(),
+        ]
+        .concat();
+        assert_eq!(Ok(()), process_instruction(&program_id, &[], &long));
+
+        let mut bad_utf8 = long;
+        bad_utf8[MAX_LOGGED_MEMO_LEN * 2] = 0xFF;
+        assert_eq!(
+            Err(ProgramError::InvalidInstructionData),
+            process_instruction(&program_id, &[], &bad_utf8)
+        );
+    }
+
     #[test]
     fn test_signers() {
         let program_id = Pubkey::new(&[0; 32]);
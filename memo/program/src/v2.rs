@@ -0,0 +1,193 @@
+//! Structured memos, selected by a leading version byte
+
+use crate::error::MemoError;
+use borsh::{BorshDeserialize, BorshSerialize};
+use huione_program::{clock::UnixTimestamp, pubkey::Pubkey};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// First byte of a v2 memo. No UTF-8 string starts with it, so plain memos are never read as
+/// v2 memos.
+pub const MEMO_V2_VERSION: u8 = 0xFE;
+
+/// Longest v2 memo, in bytes, including the version and format bytes
+pub const MAX_MEMO_V2_LEN: usize = 512;
+
+/// Longest invoice id, in bytes
+pub const MAX_INVOICE_ID_LEN: usize = 64;
+
+/// Most reference keys a v2 memo may list
+pub const MAX_REFERENCES: usize = 8;
+
+/// Encoding of the envelope, given by the byte following the version byte
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoV2Format {
+    /// Borsh serialization of `MemoV2`
+    Borsh = 0,
+    /// JSON object with the fields `invoiceId`, `references` (base58 keys, optional) and
+    /// `expiry` (optional)
+    Json = 1,
+}
+
+/// Envelope of a v2 memo
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, PartialEq)]
+pub struct MemoV2 {
+    /// Invoice the payment settles
+    pub invoice_id: String,
+    /// Keys identifying the payment to the merchant
+    pub references: Vec<Pubkey>,
+    /// Unix timestamp after which the memo is rejected
+    pub expiry: Option<UnixTimestamp>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct JsonMemoV2 {
+    invoice_id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    references: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiry: Option<UnixTimestamp>,
+}
+
+impl MemoV2 {
+    /// Serialize the memo, version and format bytes included
+    pub fn pack(&self, format: MemoV2Format) -> Vec<u8> {
+        let mut data = vec![MEMO_V2_VERSION, format as u8];
+        match format {
+            MemoV2Format::Borsh => data.extend(self.try_to_vec().unwrap()),
+            MemoV2Format::Json => {
+                let json = JsonMemoV2 {
+                    invoice_id: self.invoice_id.clone(),
+                    references: self.references.iter().map(|r| r.to_string()).collect(),
+                    expiry: self.expiry,
+                };
+                data.extend(serde_json::to_vec(&json).unwrap())
+            }
+        }
+        data
+    }
+
+    /// Deserialize a v2 memo, version and format bytes included, and check its size limits
+    pub fn unpack(input: &[u8]) -> Result<Self, MemoError> {
+        if input.len() > MAX_MEMO_V2_LEN {
+            return Err(MemoError::MemoTooLong);
+        }
+        let memo = match input {
+            [MEMO_V2_VERSION, 0, envelope @ ..] => {
+                Self::try_from_slice(envelope).map_err(|_| MemoError::InvalidEnvelope)?
+            }
+            [MEMO_V2_VERSION, 1, envelope @ ..] => {
+                let json: JsonMemoV2 =
+                    serde_json::from_slice(envelope).map_err(|_| MemoError::InvalidEnvelope)?;
+                let references = json
+                    .references
+                    .iter()
+                    .map(|reference| Pubkey::from_str(reference))
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| MemoError::InvalidEnvelope)?;
+                Self {
+                    invoice_id: json.invoice_id,
+                    references,
+                    expiry: json.expiry,
+                }
+            }
+            _ => return Err(MemoError::InvalidEnvelope),
+        };
+
+        if memo.invoice_id.is_empty() || memo.invoice_id.len() > MAX_INVOICE_ID_LEN {
+            return Err(MemoError::InvalidInvoiceId);
+        }
+        if memo.references.len() > MAX_REFERENCES {
+            return Err(MemoError::TooManyReferences);
+        }
+        Ok(memo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        let memo = MemoV2 {
+            invoice_id: "INV-1042".to_string(),
+            references: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            expiry: Some(1_700_000_000),
+        };
+        for format in [MemoV2Format::Borsh, MemoV2Format::Json].iter() {
+            let data = memo.pack(*format);
+            assert_eq!(data[..2], [MEMO_V2_VERSION, *format as u8]);
+            assert_eq!(MemoV2::unpack(&data), Ok(memo.clone()));
+        }
+
+        let json = format!(
+            r#"{{"invoiceId":"INV-1042","references":["{}"]}}"#,
+            memo.references[0]
+        );
+        let mut data = vec![MEMO_V2_VERSION, MemoV2Format::Json as u8];
+        data.extend(json.as_bytes());
+        assert_eq!(
+            MemoV2::unpack(&data),
+            Ok(MemoV2 {
+                invoice_id: "INV-1042".to_string(),
+                references: vec![memo.references[0]],
+                expiry: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unpack_errors() {
+        let memo = MemoV2 {
+            invoice_id: "INV-1042".to_string(),
+            ..MemoV2::default()
+        };
+
+        let mut data = memo.pack(MemoV2Format::Borsh);
+        data[1] = 2; // <-- Unknown format
+        assert_eq!(MemoV2::unpack(&data), Err(MemoError::InvalidEnvelope));
+
+        let mut data = memo.pack(MemoV2Format::Borsh);
+        data.push(0); // <-- Trailing byte
+        assert_eq!(MemoV2::unpack(&data), Err(MemoError::InvalidEnvelope));
+
+        let mut data = vec![MEMO_V2_VERSION, MemoV2Format::Json as u8];
+        data.extend(br#"{"invoiceId":"INV-1042","references":["not a key"]}"#);
+        assert_eq!(MemoV2::unpack(&data), Err(MemoError::InvalidEnvelope));
+
+        let mut data = vec![MEMO_V2_VERSION, MemoV2Format::Json as u8];
+        data.extend(br#"{"invoiceId":"INV-1042","amount":5}"#);
+        assert_eq!(MemoV2::unpack(&data), Err(MemoError::InvalidEnvelope));
+
+        let empty = MemoV2::default();
+        assert_eq!(
+            MemoV2::unpack(&empty.pack(MemoV2Format::Borsh)),
+            Err(MemoError::InvalidInvoiceId)
+        );
+
+        let long = MemoV2 {
+            invoice_id: "x".repeat(MAX_INVOICE_ID_LEN + 1),
+            ..MemoV2::default()
+        };
+        assert_eq!(
+            MemoV2::unpack(&long.pack(MemoV2Format::Borsh)),
+            Err(MemoError::InvalidInvoiceId)
+        );
+
+        let crowded = MemoV2 {
+            invoice_id: "INV-1042".to_string(),
+            references: (0..=MAX_REFERENCES).map(|_| Pubkey::new_unique()).collect(),
+            expiry: None,
+        };
+        assert_eq!(
+            MemoV2::unpack(&crowded.pack(MemoV2Format::Borsh)),
+            Err(MemoError::TooManyReferences)
+        );
+
+        let mut data = memo.pack(MemoV2Format::Borsh);
+        data.resize(MAX_MEMO_V2_LEN + 1, 0);
+        assert_eq!(MemoV2::unpack(&data), Err(MemoError::MemoTooLong));
+    }
+}
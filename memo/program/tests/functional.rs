@@ -1,18 +1,20 @@
 #![cfg(feature = "test-bpf")]
 
+use hpl_memo::{error::MemoError, v2::*, *};
 use huione_program::{
+    hash::Hash,
     instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
 };
 use huione_program_test::*;
 use huione_sdk::{
+    packet::PACKET_DATA_SIZE,
     signature::{Keypair, Signer},
     transaction::{Transaction, TransactionError},
 };
-use spl_memo::*;
 
 fn program_test() -> ProgramTest {
-    ProgramTest::new("spl_memo", id(), processor!(processor::process_instruction))
+    ProgramTest::new("hpl_memo", id(), processor!(processor::process_instruction))
 }
 
 #[tokio::test]
@@ -116,6 +118,130 @@ async fn test_memo_signing() {
     );
 }
 
+#[tokio::test]
+async fn test_memo_v2() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let mut memo = MemoV2 {
+        invoice_id: "INV-1042".to_string(),
+        references: vec![Pubkey::new_unique()],
+        expiry: Some(i64::MAX),
+    };
+
+    let mut transaction = Transaction::new_with_payer(
+        &[build_memo_v2(&memo, &[&payer.pubkey()])],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The JSON envelope is read the same way
+    let mut transaction = Transaction::new_with_payer(
+        &[build_memo(
+            &memo.pack(MemoV2Format::Json),
+            &[&payer.pubkey()],
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // Test unsigned v2 memo
+    let mut transaction =
+        Transaction::new_with_payer(&[build_memo_v2(&memo, &[])], Some(&payer.pubkey()));
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+
+    // Test expired memo
+    memo.expiry = Some(1);
+    let mut transaction = Transaction::new_with_payer(
+        &[build_memo_v2(&memo, &[&payer.pubkey()])],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(MemoError::Expired as u32))
+    );
+}
+
+#[tokio::test]
+async fn test_memo_references() {
+    let memo = "INV-1042".as_bytes();
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let reference = Pubkey::new_unique();
+
+    // References need not sign, or exist
+    let mut transaction = Transaction::new_with_payer(
+        &[build_memo_with_references(
+            memo,
+            &[&payer.pubkey()],
+            &[&reference],
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[build_memo_with_references(memo, &[], &[&reference])],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // A v2 memo with references must still be signed
+    let memo_v2 = MemoV2 {
+        invoice_id: "INV-1042".to_string(),
+        references: vec![reference],
+        expiry: None,
+    };
+    let mut transaction = Transaction::new_with_payer(
+        &[build_memo_with_references(
+            &memo_v2.pack(MemoV2Format::Borsh),
+            &[],
+            &[&reference],
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+
+    // Test writable reference
+    let mut instruction = build_memo_with_references(memo, &[], &[&reference]);
+    instruction.accounts[0] = AccountMeta::new(reference, false);
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MemoError::WritableReference as u32)
+        )
+    );
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_memo_compute_limits() {
@@ -204,3 +330,112 @@ async fn test_memo_compute_limits() {
         .unwrap();
     assert!(err == failed_to_complete || err == computational_budget_exceeded);
 }
+
+/// Longest memo that fits in a transaction paid for by `payer` with no other signers
+fn max_memo_len(payer: &Pubkey) -> usize {
+    let fits = |len: usize| {
+        let transaction = Transaction::new_with_payer(
+            &[build_memo_with_bounded_log(&vec![b'a'; len], &[])],
+            Some(payer),
+        );
+        bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE
+    };
+    let mut len = 0;
+    while fits(len + 1) {
+        len += 1;
+    }
+    len
+}
+
+/// Compute units consumed by a transaction made of `instruction`
+async fn units_consumed(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    instruction: Instruction,
+) -> u64 {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[payer], recent_blockhash);
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(simulation.result, Some(Ok(())));
+    simulation.simulation_details.unwrap().units_consumed
+}
+
+/// Measures the compute units of memos of every size that fits in a transaction, logged in
+/// full and in bounded form. Run with `--nocapture` to print the table.
+#[tokio::test]
+async fn bench_memo_compute_units() {
+    let mut program_test = program_test();
+    // Large enough for the longest memo logged in full, so every size is measured
+    program_test.set_bpf_compute_max_units(1_400_000);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let max_len = max_memo_len(&payer.pubkey());
+    let mut lens: Vec<usize> = (0..max_len).step_by(16).collect();
+    lens.push(MAX_LOGGED_MEMO_LEN + 1);
+    lens.push(max_len);
+    lens.sort_unstable();
+    lens.dedup();
+
+    println!("  len   full  bounded");
+    let mut full = vec![];
+    let mut bounded = vec![];
+    for len in lens.iter() {
+        let memo = vec![b'a'; *len];
+        full.push(
+            units_consumed(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                build_memo(&memo, &[]),
+            )
+            .await,
+        );
+        bounded.push(
+            units_consumed(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                build_memo_with_bounded_log(&memo, &[]),
+            )
+            .await,
+        );
+        println!(
+            "{:>5} {:>6} {:>8}",
+            len,
+            full.last().unwrap(),
+            bounded.last().unwrap()
+        );
+    }
+
+    // Logging in full costs more the longer the memo
+    assert!(full.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    // Past `MAX_LOGGED_MEMO_LEN` only the UTF-8 check and the hash syscall still grow
+    // with the memo in bounded form, so its cost stays nearly flat
+    let first_hashed = lens
+        .iter()
+        .position(|len| *len > MAX_LOGGED_MEMO_LEN)
+        .unwrap();
+    let last = lens.len() - 1;
+    let full_growth = full[last] - full[first_hashed];
+    let bounded_growth = bounded[last] - bounded[first_hashed];
+    assert!(
+        bounded_growth * 4 < full_growth,
+        "bounded cost grew by {} units from {} to {} bytes, full cost by {}",
+        bounded_growth,
+        lens[first_hashed],
+        max_len,
+        full_growth
+    );
+    for index in first_hashed..lens.len() {
+        assert!(
+            bounded[index] < full[index],
+            "bounded memo of {} bytes is not cheaper",
+            lens[index]
+        );
+    }
+}
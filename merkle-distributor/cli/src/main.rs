@@ -0,0 +1,652 @@
+use {
+    chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc},
+    clap::{
+        crate_description, crate_name, crate_version, value_t_or_exit, App, AppSettings, Arg,
+        SubCommand,
+    },
+    hpl_merkle_distributor::{claim_leaf, state::MerkleDistributor},
+    hpl_token::merkle::MerkleTree,
+    huione_clap_utils::{
+        input_parsers::{keypair_of, pubkey_of},
+        input_validators::{is_keypair, is_url, is_valid_pubkey},
+    },
+    huione_client::rpc_client::RpcClient,
+    huione_sdk::{
+        clock::UnixTimestamp,
+        commitment_config::CommitmentConfig,
+        hash::Hash,
+        instruction::Instruction,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signer},
+        transaction::Transaction,
+    },
+    serde_json::{json, Value},
+    std::{collections::HashMap, fs, str::FromStr},
+};
+
+struct Config {
+    keypair: Keypair,
+    json_rpc_url: String,
+    verbose: bool,
+}
+
+/// A claim of the distribution, as listed in the tree file
+struct Claim {
+    index: u64,
+    claimant: Pubkey,
+    amount: u128,
+    proof: Vec<Hash>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let app_matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg({
+            let arg = Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .global(true)
+                .help("Configuration file to use");
+            if let Some(ref config_file) = *huione_cli_config::CONFIG_FILE {
+                arg.default_value(config_file)
+            } else {
+                arg
+            }
+        })
+        .arg(
+            Arg::with_name("keypair")
+                .long("keypair")
+                .value_name("KEYPAIR")
+                .validator(is_keypair)
+                .takes_value(true)
+                .global(true)
+                .help("Filepath or URL to a keypair [default: client keypair]"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .long("verbose")
+                .short("v")
+                .takes_value(false)
+                .global(true)
+                .help("Show additional information"),
+        )
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .global(true)
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster [default: value from configuration file]"),
+        )
+        .subcommand(
+            SubCommand::with_name("new")
+                .about("Build the Merkle tree of a CSV file and create its distributor")
+                .arg(
+                    Arg::with_name("distributor")
+                        .value_name("DISTRIBUTOR_KEYPAIR")
+                        .validator(is_keypair)
+                        .index(1)
+                        .required(true)
+                        .help("The keypair of the new distributor"),
+                )
+                .arg(
+                    Arg::with_name("mint")
+                        .long("mint")
+                        .value_name("MINT_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .required(true)
+                        .help("The token to distribute"),
+                )
+                .arg(
+                    Arg::with_name("input_csv")
+                        .long("input-csv")
+                        .value_name("FILENAME")
+                        .required(true)
+                        .help("Allocations CSV file with `recipient,amount` rows, amounts in base units"),
+                )
+                .arg(
+                    Arg::with_name("tree_file")
+                        .long("tree-file")
+                        .value_name("FILENAME")
+                        .required(true)
+                        .default_value("merkle-distributor.json")
+                        .help("Output file listing every claim and its proof"),
+                )
+                .arg(
+                    Arg::with_name("clawback_start")
+                        .long("clawback-start")
+                        .value_name("RFC3339_DATETIME")
+                        .required(true)
+                        .validator(|s| {
+                            DateTime::parse_from_rfc3339(&s)
+                                .map(|_| ())
+                                .map_err(|err| err.to_string())
+                        })
+                        .help("Time after which the unclaimed tokens can be clawed back"),
+                )
+                .arg(
+                    Arg::with_name("clawback_authority")
+                        .long("clawback-authority")
+                        .value_name("ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .help("Authority allowed to claw back the unclaimed tokens [default: client keypair]"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .value_name("TOKEN_ACCOUNT_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .help("Token account funding the distributor, owned by the client keypair \
+                               [default: associated token account of the client keypair]"),
+                )
+                .arg(
+                    Arg::with_name("confirm")
+                        .long("confirm")
+                        .help("Confirm that the distributor should actually be created"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("claim")
+                .about("Claim the tokens of the client keypair")
+                .arg(
+                    Arg::with_name("distributor")
+                        .value_name("DISTRIBUTOR_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the distributor"),
+                )
+                .arg(
+                    Arg::with_name("tree_file")
+                        .long("tree-file")
+                        .value_name("FILENAME")
+                        .required(true)
+                        .default_value("merkle-distributor.json")
+                        .help("Tree file written by the `new` command"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("clawback")
+                .about("Recover the unclaimed tokens once the clawback start has passed")
+                .arg(
+                    Arg::with_name("distributor")
+                        .value_name("DISTRIBUTOR_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the distributor"),
+                )
+                .arg(
+                    Arg::with_name("destination")
+                        .long("destination")
+                        .value_name("TOKEN_ACCOUNT_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .help("Token account receiving the unclaimed tokens \
+                               [default: associated token account of the client keypair]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("info")
+                .about("Display the state of a distributor")
+                .arg(
+                    Arg::with_name("distributor")
+                        .value_name("DISTRIBUTOR_ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the distributor"),
+                ),
+        )
+        .get_matches();
+
+    let (sub_command, sub_matches) = app_matches.subcommand();
+    let matches = sub_matches.unwrap();
+
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            huione_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            huione_cli_config::Config::default()
+        };
+
+        Config {
+            json_rpc_url: matches
+                .value_of("json_rpc_url")
+                .unwrap_or(&cli_config.json_rpc_url)
+                .to_string(),
+            keypair: read_keypair_file(
+                matches
+                    .value_of("keypair")
+                    .unwrap_or(&cli_config.keypair_path),
+            )?,
+            verbose: matches.is_present("verbose"),
+        }
+    };
+    huione_logger::setup_with_default("huione=info");
+    let rpc_client =
+        RpcClient::new_with_commitment(config.json_rpc_url.clone(), CommitmentConfig::confirmed());
+    if config.verbose {
+        println!("JSON RPC URL: {}", config.json_rpc_url);
+    }
+
+    match (sub_command, sub_matches) {
+        ("new", Some(arg_matches)) => {
+            let distributor_keypair = keypair_of(arg_matches, "distributor").unwrap();
+            let mint = pubkey_of(arg_matches, "mint").unwrap();
+            let input_csv = value_t_or_exit!(arg_matches, "input_csv", String);
+            let tree_file = value_t_or_exit!(arg_matches, "tree_file", String);
+            let clawback_start =
+                DateTime::parse_from_rfc3339(arg_matches.value_of("clawback_start").unwrap())
+                    .unwrap()
+                    .timestamp();
+            let clawback_authority = pubkey_of(arg_matches, "clawback_authority")
+                .unwrap_or_else(|| config.keypair.pubkey());
+            let from = pubkey_of(arg_matches, "from").unwrap_or_else(|| {
+                hpl_associated_token_account::get_associated_token_address(
+                    &config.keypair.pubkey(),
+                    &mint,
+                )
+            });
+
+            process_new(
+                &rpc_client,
+                &config,
+                &distributor_keypair,
+                &mint,
+                &input_csv,
+                &tree_file,
+                clawback_start,
+                &clawback_authority,
+                &from,
+                arg_matches.is_present("confirm"),
+            )
+        }
+        ("claim", Some(arg_matches)) => {
+            let distributor_address = pubkey_of(arg_matches, "distributor").unwrap();
+            let tree_file = value_t_or_exit!(arg_matches, "tree_file", String);
+            process_claim(&rpc_client, &config, &distributor_address, &tree_file)
+        }
+        ("clawback", Some(arg_matches)) => {
+            let distributor_address = pubkey_of(arg_matches, "distributor").unwrap();
+            let destination = pubkey_of(arg_matches, "destination");
+            process_clawback(&rpc_client, &config, &distributor_address, destination)
+        }
+        ("info", Some(arg_matches)) => {
+            let distributor_address = pubkey_of(arg_matches, "distributor").unwrap();
+            process_info(&rpc_client, &distributor_address)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn get_distributor(
+    rpc_client: &RpcClient,
+    distributor_address: &Pubkey,
+) -> Result<MerkleDistributor, String> {
+    let account = rpc_client
+        .get_multiple_accounts(&[*distributor_address])
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .next()
+        .unwrap();
+
+    match account {
+        None => Err(format!(
+            "Distributor {} does not exist",
+            distributor_address
+        )),
+        Some(account) => MerkleDistributor::unpack_from_slice(&account.data).map_err(|err| {
+            format!(
+                "Failed to deserialize distributor {}: {}",
+                distributor_address, err
+            )
+        }),
+    }
+}
+
+fn unix_timestamp_to_string(unix_timestamp: UnixTimestamp) -> String {
+    format!(
+        "{} (UnixTimestamp: {})",
+        match NaiveDateTime::from_timestamp_opt(unix_timestamp, 0) {
+            Some(ndt) =>
+                DateTime::<Utc>::from_utc(ndt, Utc).to_rfc3339_opts(SecondsFormat::Secs, true),
+            None => "unknown".to_string(),
+        },
+        unix_timestamp,
+    )
+}
+
+/// Read the `recipient,amount` rows of the allocations CSV file. Allocations of the same
+/// recipient are merged so that each recipient has a single leaf.
+fn read_allocations(input_csv: &str) -> Result<Vec<(Pubkey, u128)>, Box<dyn std::error::Error>> {
+    let mut allocations: Vec<(Pubkey, u128)> = vec![];
+    let mut positions = HashMap::new();
+    for (line_number, line) in fs::read_to_string(input_csv)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (line_number == 0 && line.starts_with("recipient")) {
+            continue;
+        }
+        let mut columns = line.split(',').map(str::trim);
+        let (recipient, amount) = match (columns.next(), columns.next(), columns.next()) {
+            (Some(recipient), Some(amount), None) => (recipient, amount),
+            _ => {
+                return Err(format!(
+                    "{}:{}: expected `recipient,amount`",
+                    input_csv,
+                    line_number + 1
+                )
+                .into())
+            }
+        };
+        let recipient = Pubkey::from_str(recipient)
+            .map_err(|err| format!("{}:{}: {}", input_csv, line_number + 1, err))?;
+        let amount = amount
+            .parse::<u128>()
+            .map_err(|err| format!("{}:{}: {}", input_csv, line_number + 1, err))?;
+        match positions.get(&recipient) {
+            Some(position) => {
+                let total = &mut allocations[*position].1;
+                *total = total
+                    .checked_add(amount)
+                    .ok_or_else(|| format!("{}: allocation overflow", recipient))?;
+            }
+            None => {
+                positions.insert(recipient, allocations.len());
+                allocations.push((recipient, amount));
+            }
+        }
+    }
+    Ok(allocations)
+}
+
+fn write_tree_file(
+    tree_file: &str,
+    distributor_address: &Pubkey,
+    mint: &Pubkey,
+    root: &Hash,
+    claims: &[Claim],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let value = json!({
+        "distributor": distributor_address.to_string(),
+        "mint": mint.to_string(),
+        "root": root.to_string(),
+        "claims": claims
+            .iter()
+            .map(|claim| json!({
+                "index": claim.index,
+                "claimant": claim.claimant.to_string(),
+                "amount": claim.amount.to_string(),
+                "proof": claim.proof.iter().map(|hash| hash.to_string()).collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+    });
+    fs::write(tree_file, serde_json::to_string_pretty(&value)? + "\n")?;
+    Ok(())
+}
+
+fn read_tree_file(
+    tree_file: &str,
+    distributor_address: &Pubkey,
+) -> Result<Vec<Claim>, Box<dyn std::error::Error>> {
+    let value: Value = serde_json::from_str(&fs::read_to_string(tree_file)?)?;
+    if value["distributor"].as_str() != Some(distributor_address.to_string().as_str()) {
+        return Err(format!(
+            "Tree file {} does not belong to distributor {}",
+            tree_file, distributor_address
+        )
+        .into());
+    }
+    let invalid = || format!("Invalid tree file {}", tree_file);
+    let mut claims = vec![];
+    for claim in value["claims"].as_array().ok_or_else(invalid)? {
+        let mut proof = vec![];
+        for hash in claim["proof"].as_array().ok_or_else(invalid)? {
+            proof.push(Hash::from_str(hash.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?);
+        }
+        claims.push(Claim {
+            index: claim["index"].as_u64().ok_or_else(invalid)?,
+            claimant: Pubkey::from_str(claim["claimant"].as_str().ok_or_else(invalid)?)?,
+            amount: claim["amount"].as_str().ok_or_else(invalid)?.parse()?,
+            proof,
+        });
+    }
+    Ok(claims)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_new(
+    rpc_client: &RpcClient,
+    config: &Config,
+    distributor_keypair: &Keypair,
+    mint: &Pubkey,
+    input_csv: &str,
+    tree_file: &str,
+    clawback_start: UnixTimestamp,
+    clawback_authority: &Pubkey,
+    from: &Pubkey,
+    confirm: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let distributor_address = distributor_keypair.pubkey();
+    let vault_address = hpl_merkle_distributor::get_vault_address(&distributor_address);
+
+    let allocations = read_allocations(input_csv)?;
+    if allocations.is_empty() {
+        return Err(format!("{} has no allocations", input_csv).into());
+    }
+    let max_total_claim = allocations
+        .iter()
+        .try_fold(0u128, |total, (_, amount)| total.checked_add(*amount))
+        .ok_or("Total allocation overflow")?;
+
+    let tree = MerkleTree::new(
+        allocations
+            .iter()
+            .enumerate()
+            .map(|(index, (claimant, amount))| claim_leaf(index as u64, claimant, *amount))
+            .collect(),
+    );
+    let root = tree.root();
+    let claims: Vec<Claim> = allocations
+        .iter()
+        .enumerate()
+        .map(|(index, (claimant, amount))| Claim {
+            index: index as u64,
+            claimant: *claimant,
+            amount: *amount,
+            proof: tree.proof(index).unwrap(),
+        })
+        .collect();
+    write_tree_file(tree_file, &distributor_address, mint, &root, &claims)?;
+
+    println!("Distributor: {}", distributor_address);
+    println!("Vault Token Address: {}", vault_address);
+    println!("Merkle Root: {}", root);
+    println!("Number of recipients: {}", claims.len());
+    println!("Tokens to distribute: {}", max_total_claim);
+    println!("Clawback authority: {}", clawback_authority);
+    println!(
+        "Clawback possible after {}",
+        unix_timestamp_to_string(clawback_start)
+    );
+    println!("Tree file: {}", tree_file);
+    println!();
+
+    if !confirm {
+        println!("Add --confirm flag to create the distributor");
+        return Ok(());
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            hpl_merkle_distributor::instruction::initialize(
+                &config.keypair.pubkey(),
+                &distributor_address,
+                mint,
+                &root,
+                claims.len() as u64,
+                max_total_claim,
+                clawback_authority,
+                clawback_start,
+            ),
+            hpl_token::instruction::transfer(
+                &hpl_token::id(),
+                from,
+                &vault_address,
+                &config.keypair.pubkey(),
+                &[],
+                max_total_claim,
+            )?,
+        ],
+        Some(&config.keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    transaction.try_sign(&[&config.keypair, distributor_keypair], blockhash)?;
+    rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
+
+    println!("Distributor created!");
+    println!("Recipients claim their tokens by running:");
+    println!(
+        "    $ hpl-merkle-distributor --keypair <RECIPIENT_KEYPAIR> claim {} --tree-file {}",
+        distributor_address, tree_file
+    );
+    Ok(())
+}
+
+fn process_claim(
+    rpc_client: &RpcClient,
+    config: &Config,
+    distributor_address: &Pubkey,
+    tree_file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let distributor = get_distributor(rpc_client, distributor_address)?;
+    let claimant = config.keypair.pubkey();
+    let claim = read_tree_file(tree_file, distributor_address)?
+        .into_iter()
+        .find(|claim| claim.claimant == claimant)
+        .ok_or_else(|| format!("{} has no allocation in {}", claimant, tree_file))?;
+    if !hpl_token::merkle::verify_proof(
+        &claim.proof,
+        &Hash::new_from_array(distributor.root),
+        claim_leaf(claim.index, &claim.claimant, claim.amount),
+    ) {
+        return Err(format!(
+            "Tree file {} does not match the distributor root",
+            tree_file
+        )
+        .into());
+    }
+
+    let claimant_token =
+        hpl_associated_token_account::get_associated_token_address(&claimant, &distributor.mint);
+    let mut instructions: Vec<Instruction> = vec![];
+    if rpc_client
+        .get_multiple_accounts(&[claimant_token])?
+        .into_iter()
+        .next()
+        .unwrap()
+        .is_none()
+    {
+        println!("Creating associated token account {}", claimant_token);
+        instructions.push(
+            hpl_associated_token_account::create_associated_token_account(
+                &claimant,
+                &claimant,
+                &distributor.mint,
+            ),
+        );
+    }
+    instructions.push(hpl_merkle_distributor::instruction::claim(
+        distributor_address,
+        &distributor.mint,
+        &claimant,
+        claim.index,
+        claim.amount,
+        &claim.proof,
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&claimant));
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    transaction.try_sign(&[&config.keypair], blockhash)?;
+    rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
+
+    println!("Claimed {} tokens into {}", claim.amount, claimant_token);
+    Ok(())
+}
+
+fn process_clawback(
+    rpc_client: &RpcClient,
+    config: &Config,
+    distributor_address: &Pubkey,
+    destination: Option<Pubkey>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let distributor = get_distributor(rpc_client, distributor_address)?;
+    if distributor.clawed_back {
+        return Err(format!(
+            "Distributor {} was already clawed back",
+            distributor_address
+        )
+        .into());
+    }
+    let destination = destination.unwrap_or_else(|| {
+        hpl_associated_token_account::get_associated_token_address(
+            &config.keypair.pubkey(),
+            &distributor.mint,
+        )
+    });
+
+    let mut transaction = Transaction::new_with_payer(
+        &[hpl_merkle_distributor::instruction::clawback(
+            distributor_address,
+            &destination,
+            &config.keypair.pubkey(),
+        )],
+        Some(&config.keypair.pubkey()),
+    );
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    transaction.try_sign(&[&config.keypair], blockhash)?;
+    rpc_client.send_and_confirm_transaction_with_spinner(&transaction)?;
+
+    println!("Unclaimed tokens transferred to {}", destination);
+    Ok(())
+}
+
+fn process_info(
+    rpc_client: &RpcClient,
+    distributor_address: &Pubkey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let distributor = get_distributor(rpc_client, distributor_address)?;
+    let vault_address = hpl_merkle_distributor::get_vault_address(distributor_address);
+    let vault_balance = rpc_client
+        .get_token_account_balance(&vault_address)?
+        .amount
+        .parse::<u128>()
+        .unwrap_or(0);
+
+    println!("Distributor: {}", distributor_address);
+    println!("Mint: {}", distributor.mint);
+    println!("Merkle Root: {}", Hash::new_from_array(distributor.root));
+    println!("Vault Token Address: {}", vault_address);
+    println!("Vault balance: {}", vault_balance);
+    println!(
+        "Claimed: {}/{} recipients, {}/{} tokens",
+        distributor.num_claimed,
+        distributor.num_nodes,
+        distributor.total_claimed,
+        distributor.max_total_claim
+    );
+    println!("Clawback authority: {}", distributor.clawback_authority);
+    println!(
+        "Clawback possible after {}",
+        unix_timestamp_to_string(distributor.clawback_start)
+    );
+    if distributor.clawed_back {
+        println!("Unclaimed tokens were clawed back");
+    }
+    Ok(())
+}
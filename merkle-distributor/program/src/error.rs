@@ -0,0 +1,47 @@
+//! Error types
+
+use huione_program::{decode_error::DecodeError, program_error::ProgramError};
+use num_derive::FromPrimitive;
+use thiserror::Error;
+
+/// Errors that may be returned by the Merkle Distributor program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum DistributorError {
+    // 0
+    /// The number of leaves is zero or too large for the claimed bitmap.
+    #[error("Invalid number of nodes")]
+    InvalidNumNodes,
+    /// The claim index is not a leaf of the tree.
+    #[error("Claim index out of range")]
+    IndexOutOfRange,
+    /// The leaf at this index has already been claimed.
+    #[error("Already claimed")]
+    AlreadyClaimed,
+    /// The proof does not lead to the distributor root.
+    #[error("Invalid proof")]
+    InvalidProof,
+    /// The claim would exceed the maximum total claim of the distributor.
+    #[error("Exceeded maximum total claim")]
+    ExceededMaxClaim,
+
+    // 5
+    /// The clawback authority does not match.
+    #[error("Clawback authority does not match")]
+    ClawbackAuthorityMismatch,
+    /// The clawback deadline has not been reached yet.
+    #[error("Clawback not started")]
+    ClawbackNotStarted,
+    /// The remaining tokens have already been clawed back.
+    #[error("Already clawed back")]
+    AlreadyClawedBack,
+}
+impl From<DistributorError> for ProgramError {
+    fn from(e: DistributorError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+impl<T> DecodeError<T> for DistributorError {
+    fn type_of() -> &'static str {
+        "DistributorError"
+    }
+}
@@ -0,0 +1,233 @@
+//! Program instructions
+
+use crate::*;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use huione_program::{
+    clock::UnixTimestamp,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    sysvar,
+};
+
+/// Instructions supported by the Merkle Distributor program
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+pub enum DistributorInstruction {
+    /// Create a new distributor.
+    ///
+    /// This instruction creates the following accounts, all funded by account 0:
+    /// * The distributor account holding the Merkle root
+    /// * A "vault" token account owned by itself, from which the claims are paid. The vault must
+    ///   be funded with `max_total_claim` tokens by a regular token transfer.
+    /// * A "claimed" bitmap account with one bit per leaf
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writeable,signer]` Funding account (must be a system account)
+    /// 1. `[writeable,signer]` Unallocated distributor account to create
+    /// 2. `[writeable]` Vault token account address from `get_vault_address`
+    /// 3. `[writeable]` Claimed bitmap address from `get_claim_status_address`
+    /// 4. `[]` Mint of the distributed tokens
+    /// 5. `[]` System program
+    /// 6. `[]` HPL Token program
+    /// 7. `[]` Rent sysvar
+    ///
+    Initialize {
+        /// Root of the Merkle tree of `claim_leaf(index, claimant, amount)` leaves
+        root: [u8; 32],
+        /// Number of leaves in the tree
+        num_nodes: u64,
+        /// Total amount of tokens the tree allows to claim
+        max_total_claim: u128,
+        /// Authority allowed to recover the unclaimed tokens
+        clawback_authority: Pubkey,
+        /// Time after which the unclaimed tokens can be clawed back
+        clawback_start: UnixTimestamp,
+    },
+
+    /// Claim the tokens of a leaf into the associated token account of the claimant.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writeable]` Distributor account
+    /// 1. `[writeable]` Vault token account address from `get_vault_address`
+    /// 2. `[writeable]` Claimed bitmap address from `get_claim_status_address`
+    /// 3. `[signer]` Claimant
+    /// 4. `[writeable]` Associated token account of the claimant
+    /// 5. `[]` HPL Token program
+    ///
+    Claim {
+        /// Index of the leaf
+        index: u64,
+        /// Amount of tokens of the leaf
+        amount: u128,
+        /// Sibling hashes from the leaf up to the root
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Transfer the unclaimed tokens out of the vault once the clawback start has passed. No
+    /// claim is accepted afterwards.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writeable]` Distributor account
+    /// 1. `[writeable]` Vault token account address from `get_vault_address`
+    /// 2. `[writeable]` Destination token account
+    /// 3. `[signer]` Clawback authority
+    /// 4. `[]` HPL Token program
+    /// 5. `[]` Clock sysvar
+    ///
+    Clawback,
+}
+
+impl DistributorInstruction {
+    /// Serialize the instruction
+    pub fn pack(&self) -> Vec<u8> {
+        self.try_to_vec().expect("try_to_vec")
+    }
+
+    /// Deserialize an instruction
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(input).map_err(|err| {
+            msg!(
+                "Error: failed to deserialize merkle distributor instruction: {}",
+                err
+            );
+            ProgramError::InvalidInstructionData
+        })
+    }
+}
+
+/// Create a `DistributorInstruction::Initialize` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    funding_address: &Pubkey,
+    distributor_address: &Pubkey,
+    mint_address: &Pubkey,
+    root: &Hash,
+    num_nodes: u64,
+    max_total_claim: u128,
+    clawback_authority: &Pubkey,
+    clawback_start: UnixTimestamp,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*funding_address, true),
+            AccountMeta::new(*distributor_address, true),
+            AccountMeta::new(get_vault_address(distributor_address), false),
+            AccountMeta::new(get_claim_status_address(distributor_address), false),
+            AccountMeta::new_readonly(*mint_address, false),
+            AccountMeta::new_readonly(huione_program::system_program::id(), false),
+            AccountMeta::new_readonly(hpl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: DistributorInstruction::Initialize {
+            root: root.to_bytes(),
+            num_nodes,
+            max_total_claim,
+            clawback_authority: *clawback_authority,
+            clawback_start,
+        }
+        .pack(),
+    }
+}
+
+/// Create a `DistributorInstruction::Claim` instruction
+pub fn claim(
+    distributor_address: &Pubkey,
+    mint_address: &Pubkey,
+    claimant_address: &Pubkey,
+    index: u64,
+    amount: u128,
+    proof: &[Hash],
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*distributor_address, false),
+            AccountMeta::new(get_vault_address(distributor_address), false),
+            AccountMeta::new(get_claim_status_address(distributor_address), false),
+            AccountMeta::new_readonly(*claimant_address, true),
+            AccountMeta::new(
+                hpl_associated_token_account::get_associated_token_address(
+                    claimant_address,
+                    mint_address,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(hpl_token::id(), false),
+        ],
+        data: DistributorInstruction::Claim {
+            index,
+            amount,
+            proof: proof.iter().map(|hash| hash.to_bytes()).collect(),
+        }
+        .pack(),
+    }
+}
+
+/// Create a `DistributorInstruction::Clawback` instruction
+pub fn clawback(
+    distributor_address: &Pubkey,
+    destination_address: &Pubkey,
+    clawback_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*distributor_address, false),
+            AccountMeta::new(get_vault_address(distributor_address), false),
+            AccountMeta::new(*destination_address, false),
+            AccountMeta::new_readonly(*clawback_authority, true),
+            AccountMeta::new_readonly(hpl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: DistributorInstruction::Clawback.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_bytes() {
+        assert_eq!(DistributorInstruction::Clawback.pack(), vec![2]);
+
+        let mut expected = vec![1, 7, 0, 0, 0, 0, 0, 0, 0, 42];
+        expected.extend_from_slice(&[0; 15]);
+        expected.extend_from_slice(&[2, 0, 0, 0]);
+        expected.extend_from_slice(&[1; 32]);
+        expected.extend_from_slice(&[2; 32]);
+        assert_eq!(
+            DistributorInstruction::Claim {
+                index: 7,
+                amount: 42,
+                proof: vec![[1; 32], [2; 32]],
+            }
+            .pack(),
+            expected
+        );
+        assert_eq!(
+            DistributorInstruction::unpack(&expected),
+            Ok(DistributorInstruction::Claim {
+                index: 7,
+                amount: 42,
+                proof: vec![[1; 32], [2; 32]],
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        assert_eq!(
+            DistributorInstruction::unpack(&[3]),
+            Err(ProgramError::InvalidInstructionData),
+        );
+        assert_eq!(
+            DistributorInstruction::unpack(&[2, 0]),
+            Err(ProgramError::InvalidInstructionData),
+        );
+    }
+}
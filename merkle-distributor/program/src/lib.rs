@@ -0,0 +1,41 @@
+//! Merkle Distributor program
+#![deny(missing_docs)]
+#![forbid(unsafe_code)]
+
+mod entrypoint;
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+// Export current SDK types for downstream users building with a different SDK version
+pub use huione_program;
+use huione_program::{hash::Hash, pubkey::Pubkey};
+
+huione_program::declare_id!("HuiMD11111111111111111111111111111111111111");
+
+pub(crate) fn get_vault_address_with_seed(distributor_address: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&distributor_address.to_bytes(), br"vault"], &id())
+}
+
+pub(crate) fn get_claim_status_address_with_seed(distributor_address: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&distributor_address.to_bytes(), br"claimed"], &id())
+}
+
+/// Derive the HPL Token account holding the tokens of a distributor. The vault is its own owner.
+pub fn get_vault_address(distributor_address: &Pubkey) -> Pubkey {
+    get_vault_address_with_seed(distributor_address).0
+}
+
+/// Derive the account holding the claimed bitmap of a distributor
+pub fn get_claim_status_address(distributor_address: &Pubkey) -> Pubkey {
+    get_claim_status_address_with_seed(distributor_address).0
+}
+
+/// Hash the Merkle tree leaf allowing `claimant` to claim `amount` tokens.
+///
+/// This is the balance leaf of HPL Token snapshots, so a snapshot root distributes each
+/// holder's balance to that holder.
+pub fn claim_leaf(index: u64, claimant: &Pubkey, amount: u128) -> Hash {
+    hpl_token::merkle::balance_leaf(index, claimant, amount)
+}
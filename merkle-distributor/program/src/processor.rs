@@ -0,0 +1,354 @@
+//! Program state processor
+
+use crate::{error::DistributorError, instruction::*, state::*, *};
+use hpl_token::merkle::verify_proof;
+use huione_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::{Clock, UnixTimestamp},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+/// Instruction processor
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    match DistributorInstruction::unpack(input)? {
+        DistributorInstruction::Initialize {
+            root,
+            num_nodes,
+            max_total_claim,
+            clawback_authority,
+            clawback_start,
+        } => {
+            msg!("DistributorInstruction::Initialize");
+            process_initialize(
+                program_id,
+                accounts,
+                root,
+                num_nodes,
+                max_total_claim,
+                clawback_authority,
+                clawback_start,
+            )
+        }
+        DistributorInstruction::Claim {
+            index,
+            amount,
+            proof,
+        } => {
+            msg!("DistributorInstruction::Claim");
+            process_claim(program_id, accounts, index, amount, proof)
+        }
+        DistributorInstruction::Clawback => {
+            msg!("DistributorInstruction::Clawback");
+            process_clawback(program_id, accounts)
+        }
+    }
+}
+
+fn process_initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    root: [u8; 32],
+    num_nodes: u64,
+    max_total_claim: u128,
+    clawback_authority: Pubkey,
+    clawback_start: UnixTimestamp,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let funder_info = next_account_info(account_info_iter)?;
+    let distributor_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let claim_status_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let hpl_token_program_info = next_account_info(account_info_iter)?;
+    let rent_sysvar_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_sysvar_info)?;
+
+    if num_nodes == 0 || num_nodes > MAX_NUM_NODES {
+        return Err(DistributorError::InvalidNumNodes.into());
+    }
+    if *mint_info.owner != hpl_token::id() {
+        msg!("Error: mint is not owned by the HPL Token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    hpl_token::state::Mint::unpack(&mint_info.data.borrow())?;
+
+    let (vault_address, vault_bump_seed) = get_vault_address_with_seed(distributor_info.key);
+    if vault_address != *vault_info.key {
+        msg!("Error: vault address derivation mismatch");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (claim_status_address, claim_status_bump_seed) =
+        get_claim_status_address_with_seed(distributor_info.key);
+    if claim_status_address != *claim_status_info.key {
+        msg!("Error: claimed bitmap address derivation mismatch");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let vault_signer_seeds: &[&[_]] = &[
+        &distributor_info.key.to_bytes(),
+        br"vault",
+        &[vault_bump_seed],
+    ];
+
+    let claim_status_signer_seeds: &[&[_]] = &[
+        &distributor_info.key.to_bytes(),
+        br"claimed",
+        &[claim_status_bump_seed],
+    ];
+
+    msg!("Creating distributor account");
+    invoke(
+        &system_instruction::create_account(
+            funder_info.key,
+            distributor_info.key,
+            1.max(rent.minimum_balance(MerkleDistributor::get_packed_len())),
+            MerkleDistributor::get_packed_len() as u64,
+            program_id,
+        ),
+        &[
+            funder_info.clone(),
+            distributor_info.clone(),
+            system_program_info.clone(),
+        ],
+    )?;
+
+    msg!("Creating vault token account");
+    invoke_signed(
+        &system_instruction::create_account(
+            funder_info.key,
+            vault_info.key,
+            1.max(rent.minimum_balance(hpl_token::state::Account::get_packed_len())),
+            hpl_token::state::Account::get_packed_len() as u64,
+            &hpl_token::id(),
+        ),
+        &[
+            funder_info.clone(),
+            vault_info.clone(),
+            system_program_info.clone(),
+        ],
+        &[vault_signer_seeds],
+    )?;
+
+    msg!("Initializing vault token account");
+    invoke(
+        &hpl_token::instruction::initialize_account(
+            &hpl_token::id(),
+            vault_info.key,
+            mint_info.key,
+            vault_info.key,
+        )?,
+        &[
+            vault_info.clone(),
+            mint_info.clone(),
+            hpl_token_program_info.clone(),
+            rent_sysvar_info.clone(),
+        ],
+    )?;
+
+    msg!("Creating claimed bitmap account");
+    let claim_status_len = claim_status_len(num_nodes);
+    invoke_signed(
+        &system_instruction::create_account(
+            funder_info.key,
+            claim_status_info.key,
+            1.max(rent.minimum_balance(claim_status_len)),
+            claim_status_len as u64,
+            program_id,
+        ),
+        &[
+            funder_info.clone(),
+            claim_status_info.clone(),
+            system_program_info.clone(),
+        ],
+        &[claim_status_signer_seeds],
+    )?;
+
+    MerkleDistributor {
+        is_initialized: true,
+        mint: *mint_info.key,
+        root,
+        num_nodes,
+        max_total_claim,
+        total_claimed: 0,
+        num_claimed: 0,
+        clawback_authority,
+        clawback_start,
+        clawed_back: false,
+    }
+    .pack_into_slice(&mut distributor_info.data.borrow_mut());
+    Ok(())
+}
+
+fn process_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u64,
+    amount: u128,
+    proof: Vec<[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let distributor_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let claim_status_info = next_account_info(account_info_iter)?;
+    let claimant_info = next_account_info(account_info_iter)?;
+    let claimant_token_info = next_account_info(account_info_iter)?;
+    let hpl_token_program_info = next_account_info(account_info_iter)?;
+
+    let mut distributor = unpack_distributor(program_id, distributor_info)?;
+    if distributor.clawed_back {
+        return Err(DistributorError::AlreadyClawedBack.into());
+    }
+
+    let (vault_address, vault_bump_seed) = get_vault_address_with_seed(distributor_info.key);
+    if vault_address != *vault_info.key {
+        msg!("Error: vault address derivation mismatch");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if get_claim_status_address(distributor_info.key) != *claim_status_info.key {
+        msg!("Error: claimed bitmap address derivation mismatch");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !claimant_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if hpl_associated_token_account::get_associated_token_address(
+        claimant_info.key,
+        &distributor.mint,
+    ) != *claimant_token_info.key
+    {
+        msg!("Error: claimant token account is not its associated token account");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if index >= distributor.num_nodes {
+        return Err(DistributorError::IndexOutOfRange.into());
+    }
+    let mut claim_status = claim_status_info.data.borrow_mut();
+    if is_claimed(&claim_status, index) {
+        return Err(DistributorError::AlreadyClaimed.into());
+    }
+
+    let proof: Vec<Hash> = proof.into_iter().map(Hash::new_from_array).collect();
+    let leaf = claim_leaf(index, claimant_info.key, amount);
+    if !verify_proof(&proof, &Hash::new_from_array(distributor.root), leaf) {
+        return Err(DistributorError::InvalidProof.into());
+    }
+
+    distributor.total_claimed = distributor
+        .total_claimed
+        .checked_add(amount)
+        .filter(|total_claimed| *total_claimed <= distributor.max_total_claim)
+        .ok_or(DistributorError::ExceededMaxClaim)?;
+    distributor.num_claimed += 1;
+    set_claimed(&mut claim_status, index);
+
+    msg!("Claiming {} tokens", amount);
+    invoke_signed(
+        &hpl_token::instruction::transfer(
+            &hpl_token::id(),
+            vault_info.key,
+            claimant_token_info.key,
+            vault_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            vault_info.clone(),
+            claimant_token_info.clone(),
+            hpl_token_program_info.clone(),
+        ],
+        &[&[
+            &distributor_info.key.to_bytes(),
+            br"vault",
+            &[vault_bump_seed],
+        ]],
+    )?;
+
+    distributor.pack_into_slice(&mut distributor_info.data.borrow_mut());
+    Ok(())
+}
+
+fn process_clawback(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let distributor_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let clawback_authority_info = next_account_info(account_info_iter)?;
+    let hpl_token_program_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    let mut distributor = unpack_distributor(program_id, distributor_info)?;
+    if distributor.clawed_back {
+        return Err(DistributorError::AlreadyClawedBack.into());
+    }
+    if distributor.clawback_authority != *clawback_authority_info.key {
+        return Err(DistributorError::ClawbackAuthorityMismatch.into());
+    }
+    if !clawback_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if clock.unix_timestamp < distributor.clawback_start {
+        return Err(DistributorError::ClawbackNotStarted.into());
+    }
+
+    let (vault_address, vault_bump_seed) = get_vault_address_with_seed(distributor_info.key);
+    if vault_address != *vault_info.key {
+        msg!("Error: vault address derivation mismatch");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let vault = hpl_token::state::Account::unpack(&vault_info.data.borrow())?;
+
+    msg!("Clawing back {} tokens", vault.amount);
+    invoke_signed(
+        &hpl_token::instruction::transfer(
+            &hpl_token::id(),
+            vault_info.key,
+            destination_info.key,
+            vault_info.key,
+            &[],
+            vault.amount,
+        )?,
+        &[
+            vault_info.clone(),
+            destination_info.clone(),
+            hpl_token_program_info.clone(),
+        ],
+        &[&[
+            &distributor_info.key.to_bytes(),
+            br"vault",
+            &[vault_bump_seed],
+        ]],
+    )?;
+
+    distributor.clawed_back = true;
+    distributor.pack_into_slice(&mut distributor_info.data.borrow_mut());
+    Ok(())
+}
+
+fn unpack_distributor(
+    program_id: &Pubkey,
+    distributor_info: &AccountInfo,
+) -> Result<MerkleDistributor, ProgramError> {
+    if distributor_info.owner != program_id {
+        msg!("Error: distributor account is not owned by the program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let distributor = MerkleDistributor::unpack_from_slice(&distributor_info.data.borrow())?;
+    if !distributor.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(distributor)
+}
@@ -0,0 +1,112 @@
+//! Program state
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use huione_program::{
+    clock::UnixTimestamp,
+    msg,
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+/// Largest claimed bitmap the program can allocate in a single instruction
+pub const MAX_CLAIM_STATUS_LEN: usize = 10 * 1024;
+
+/// Largest number of leaves a distributor can hold
+pub const MAX_NUM_NODES: u64 = MAX_CLAIM_STATUS_LEN as u64 * 8;
+
+/// Contents of a Merkle Distributor account
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+pub struct MerkleDistributor {
+    /// Set once the distributor and its vault are created
+    pub is_initialized: bool,
+    /// Mint of the distributed tokens
+    pub mint: Pubkey,
+    /// Root of the Merkle tree of `(index, claimant, amount)` leaves
+    pub root: [u8; 32],
+    /// Number of leaves in the tree
+    pub num_nodes: u64,
+    /// Total amount of tokens the tree allows to claim
+    pub max_total_claim: u128,
+    /// Amount of tokens claimed so far
+    pub total_claimed: u128,
+    /// Number of leaves claimed so far
+    pub num_claimed: u64,
+    /// Authority allowed to recover the unclaimed tokens after `clawback_start`
+    pub clawback_authority: Pubkey,
+    /// Time after which the unclaimed tokens can be clawed back
+    pub clawback_start: UnixTimestamp,
+    /// Whether the unclaimed tokens were clawed back, which ends the distribution
+    pub clawed_back: bool,
+}
+impl Sealed for MerkleDistributor {}
+
+impl Pack for MerkleDistributor {
+    const LEN: usize = 154; // see `test_get_packed_len()`
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let data = self.try_to_vec().unwrap();
+        dst[..data.len()].copy_from_slice(&data);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut mut_src: &[u8] = src;
+        Self::deserialize(&mut mut_src).map_err(|err| {
+            msg!(
+                "Error: failed to deserialize merkle distributor account: {}",
+                err
+            );
+            ProgramError::InvalidAccountData
+        })
+    }
+}
+
+/// Length of the claimed bitmap of a distributor with `num_nodes` leaves
+pub fn claim_status_len(num_nodes: u64) -> usize {
+    ((num_nodes + 7) / 8) as usize
+}
+
+/// Whether the leaf at `index` is marked as claimed
+pub fn is_claimed(claim_status: &[u8], index: u64) -> bool {
+    claim_status[(index / 8) as usize] & (1 << (index % 8)) != 0
+}
+
+/// Mark the leaf at `index` as claimed
+pub fn set_claimed(claim_status: &mut [u8], index: u64) {
+    claim_status[(index / 8) as usize] |= 1 << (index % 8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_packed_len() {
+        assert_eq!(
+            MerkleDistributor::get_packed_len(),
+            huione_program::borsh::get_packed_len::<MerkleDistributor>()
+        );
+    }
+
+    #[test]
+    fn test_claim_status() {
+        assert_eq!(claim_status_len(1), 1);
+        assert_eq!(claim_status_len(8), 1);
+        assert_eq!(claim_status_len(9), 2);
+        assert_eq!(claim_status_len(MAX_NUM_NODES), MAX_CLAIM_STATUS_LEN);
+
+        let mut claim_status = vec![0; claim_status_len(20)];
+        for index in 0..20 {
+            assert!(!is_claimed(&claim_status, index));
+        }
+        set_claimed(&mut claim_status, 0);
+        set_claimed(&mut claim_status, 9);
+        set_claimed(&mut claim_status, 19);
+        assert_eq!(claim_status, vec![0b1, 0b10, 0b1000]);
+        for index in 0..20 {
+            assert_eq!(
+                is_claimed(&claim_status, index),
+                [0, 9, 19].contains(&index)
+            );
+        }
+    }
+}
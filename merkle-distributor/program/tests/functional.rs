@@ -0,0 +1,443 @@
+// Mark this test as BPF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-bpf")]
+
+use {
+    hpl_merkle_distributor::{error::DistributorError, instruction::*, state::*, *},
+    hpl_token::merkle::{balance_leaf, MerkleTree},
+    huione_program::{
+        clock::UnixTimestamp,
+        hash::Hash,
+        instruction::{Instruction, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        system_instruction,
+    },
+    huione_program_test::*,
+    huione_sdk::{
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+fn program_test() -> ProgramTest {
+    let mut pc = ProgramTest::new(
+        "hpl_merkle_distributor",
+        id(),
+        processor!(processor::process_instruction),
+    );
+    pc.add_program(
+        "hpl_token",
+        hpl_token::id(),
+        processor!(hpl_token::processor::Processor::process),
+    );
+    pc.add_program(
+        "hpl_associated_token_account",
+        hpl_associated_token_account::id(),
+        processor!(hpl_associated_token_account::processor::process_instruction),
+    );
+    pc
+}
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+struct Distribution {
+    mint: Pubkey,
+    distributor: Pubkey,
+    claimants: Vec<(Keypair, u128)>,
+    tree: MerkleTree,
+}
+
+impl Distribution {
+    fn claim(&self, index: usize, amount: u128) -> Instruction {
+        let proof = self.tree.proof(index).unwrap();
+        claim(
+            &self.distributor,
+            &self.mint,
+            &self.claimants[index].0.pubkey(),
+            index as u64,
+            amount,
+            &proof,
+        )
+    }
+}
+
+async fn setup(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    amounts: &[u128],
+    clawback_start: UnixTimestamp,
+) -> Distribution {
+    let claimants: Vec<(Keypair, u128)> = amounts
+        .iter()
+        .map(|amount| (Keypair::new(), *amount))
+        .collect();
+    let tree = MerkleTree::new(
+        claimants
+            .iter()
+            .enumerate()
+            .map(|(index, (claimant, amount))| {
+                claim_leaf(index as u64, &claimant.pubkey(), *amount)
+            })
+            .collect(),
+    );
+    setup_with_tree(
+        banks_client,
+        payer,
+        recent_blockhash,
+        claimants,
+        tree,
+        clawback_start,
+    )
+    .await
+}
+
+/// Creates a distributor of `tree`, whose leaves pay `claimants` in order
+async fn setup_with_tree(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    claimants: Vec<(Keypair, u128)>,
+    tree: MerkleTree,
+    clawback_start: UnixTimestamp,
+) -> Distribution {
+    let rent = banks_client.get_rent().await.unwrap();
+    let mint = Keypair::new();
+    let distributor = Keypair::new();
+    let total = claimants.iter().map(|(_, amount)| amount).sum();
+
+    process(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(hpl_token::state::Mint::LEN),
+                hpl_token::state::Mint::LEN as u64,
+                &hpl_token::id(),
+            ),
+            hpl_token::instruction::initialize_mint(
+                &hpl_token::id(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                0,
+            )
+            .unwrap(),
+            initialize(
+                &payer.pubkey(),
+                &distributor.pubkey(),
+                &mint.pubkey(),
+                &tree.root(),
+                claimants.len() as u64,
+                total,
+                &payer.pubkey(),
+                clawback_start,
+            ),
+            hpl_token::instruction::mint_to(
+                &hpl_token::id(),
+                &mint.pubkey(),
+                &get_vault_address(&distributor.pubkey()),
+                &payer.pubkey(),
+                &[],
+                total,
+            )
+            .unwrap(),
+        ],
+        &[&mint, &distributor],
+    )
+    .await
+    .unwrap();
+
+    Distribution {
+        mint: mint.pubkey(),
+        distributor: distributor.pubkey(),
+        claimants,
+        tree,
+    }
+}
+
+async fn token_balance(banks_client: &mut BanksClient, address: Pubkey) -> u128 {
+    banks_client
+        .get_packed_account_data::<hpl_token::state::Account>(address)
+        .await
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+async fn test_claim() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let distribution = setup(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[100, 200, 300],
+        UnixTimestamp::MAX,
+    )
+    .await;
+    let (claimant, amount) = &distribution.claimants[1];
+    let claimant_token = hpl_associated_token_account::get_associated_token_address(
+        &claimant.pubkey(),
+        &distribution.mint,
+    );
+
+    // Wrong amount
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[
+                hpl_associated_token_account::create_associated_token_account(
+                    &payer.pubkey(),
+                    &claimant.pubkey(),
+                    &distribution.mint,
+                ),
+                distribution.claim(1, amount + 1),
+            ],
+            &[claimant],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DistributorError::InvalidProof as u32)
+        )
+    );
+
+    // Success
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            hpl_associated_token_account::create_associated_token_account(
+                &payer.pubkey(),
+                &claimant.pubkey(),
+                &distribution.mint,
+            ),
+            distribution.claim(1, *amount),
+        ],
+        &[claimant],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut banks_client, claimant_token).await, 200);
+    assert_eq!(
+        token_balance(
+            &mut banks_client,
+            get_vault_address(&distribution.distributor)
+        )
+        .await,
+        400
+    );
+    let distributor = banks_client
+        .get_packed_account_data::<MerkleDistributor>(distribution.distributor)
+        .await
+        .unwrap();
+    assert_eq!(distributor.total_claimed, 200);
+    assert_eq!(distributor.num_claimed, 1);
+
+    // Claiming twice fails
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[distribution.claim(1, *amount)],
+            &[claimant],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DistributorError::AlreadyClaimed as u32)
+        )
+    );
+
+    // Clawback before the deadline fails
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[clawback(
+                &distribution.distributor,
+                &claimant_token,
+                &payer.pubkey()
+            )],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DistributorError::ClawbackNotStarted as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_clawback() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let distribution = setup(&mut banks_client, &payer, recent_blockhash, &[100, 200], 0).await;
+    let destination = hpl_associated_token_account::get_associated_token_address(
+        &payer.pubkey(),
+        &distribution.mint,
+    );
+    let wrong_authority = Keypair::new();
+
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[
+                hpl_associated_token_account::create_associated_token_account(
+                    &payer.pubkey(),
+                    &payer.pubkey(),
+                    &distribution.mint,
+                ),
+                clawback(
+                    &distribution.distributor,
+                    &destination,
+                    &wrong_authority.pubkey()
+                ),
+            ],
+            &[&wrong_authority],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DistributorError::ClawbackAuthorityMismatch as u32)
+        )
+    );
+
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[
+            hpl_associated_token_account::create_associated_token_account(
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &distribution.mint,
+            ),
+            clawback(&distribution.distributor, &destination, &payer.pubkey()),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut banks_client, destination).await, 300);
+
+    // No claim is accepted after the clawback
+    let (claimant, amount) = &distribution.claimants[0];
+    assert_eq!(
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[
+                hpl_associated_token_account::create_associated_token_account(
+                    &payer.pubkey(),
+                    &claimant.pubkey(),
+                    &distribution.mint,
+                ),
+                distribution.claim(0, *amount),
+            ],
+            &[claimant],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DistributorError::AlreadyClawedBack as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_claim_snapshot_proof() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+
+    // A token snapshot orders the holders by owner and hashes their balance
+    // leaves, its root can be distributed as is
+    let mut holders: Vec<(Keypair, u128)> = (1..=5u128)
+        .map(|amount| (Keypair::new(), amount * 10))
+        .collect();
+    holders.sort_by_key(|(holder, _)| holder.pubkey());
+    let snapshot_tree = MerkleTree::new(
+        holders
+            .iter()
+            .enumerate()
+            .map(|(index, (holder, amount))| balance_leaf(index as u64, &holder.pubkey(), *amount))
+            .collect(),
+    );
+    let distribution = setup_with_tree(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        holders,
+        snapshot_tree,
+        UnixTimestamp::MAX,
+    )
+    .await;
+
+    for (index, (holder, amount)) in distribution.claimants.iter().enumerate() {
+        process(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[
+                hpl_associated_token_account::create_associated_token_account(
+                    &payer.pubkey(),
+                    &holder.pubkey(),
+                    &distribution.mint,
+                ),
+                distribution.claim(index, *amount),
+            ],
+            &[holder],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            token_balance(
+                &mut banks_client,
+                hpl_associated_token_account::get_associated_token_address(
+                    &holder.pubkey(),
+                    &distribution.mint,
+                )
+            )
+            .await,
+            *amount
+        );
+    }
+    assert_eq!(
+        token_balance(
+            &mut banks_client,
+            get_vault_address(&distribution.distributor)
+        )
+        .await,
+        0
+    );
+}
@@ -63,12 +63,14 @@ mod sort;
 use sort::sort_and_parse_token_accounts;
 
 use hpl_nft::instruction::{
-    create_authorize_instruction, create_burn_instruction, create_freeze_instruction,
-    create_mint_to_inst, create_thaw_instruction, create_transfer_inst, initialize_collection,
-    update_instruction, AuthorityType, MintNftArgs, UpdateType,
+    create_approve_instruction, create_authorize_instruction, create_burn_instruction,
+    create_freeze_instruction, create_mint_to_inst, create_revoke_instruction,
+    create_sign_creator_instruction, create_thaw_instruction, create_transfer_inst,
+    initialize_collection, initialize_collection_with_royalties, update_instruction,
+    AuthorityType, MintNftArgs, UpdateType,
 };
 // use hpl_nft::huione_program::program_pack::Pack;
-use hpl_nft::state::{MetaAccount, NftMint};
+use hpl_nft::state::{Creator, MetaAccount, NftMint, MAX_SELLER_FEE_BASIS_POINTS};
 
 pub const OWNER_ADDRESS_ARG: ArgConstant<'static> = ArgConstant {
     name: "owner",
@@ -145,6 +147,33 @@ fn is_mint_supply(string: String) -> Result<(), String> {
     is_parsable::<u64>(string)
 }
 
+fn is_seller_fee_basis_points(string: String) -> Result<(), String> {
+    let v = u16::from_str(&string).map_err(|e| e.to_string())?;
+    if v > MAX_SELLER_FEE_BASIS_POINTS {
+        Err(format!("must be at most {}", MAX_SELLER_FEE_BASIS_POINTS))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses a creator given as `ADDRESS:SHARE`
+fn parse_creator(string: &str) -> Result<Creator, String> {
+    let mut parts = string.splitn(2, ':');
+    let address = parts.next().unwrap_or_default();
+    let share = parts
+        .next()
+        .ok_or_else(|| "expected ADDRESS:SHARE".to_string())?;
+    Ok(Creator {
+        address: Pubkey::from_str(address).map_err(|e| e.to_string())?,
+        verified: false,
+        share: u8::from_str(share).map_err(|e| e.to_string())?,
+    })
+}
+
+fn is_creator(string: String) -> Result<(), String> {
+    parse_creator(&string).map(|_| ())
+}
+
 // pub fn mint_decimals_arg<'a, 'b>() -> Arg<'a, 'b> {
 //     Arg::with_name(MINT_DECIMALS_ARG.name)
 //         .long(MINT_DECIMALS_ARG.long)
@@ -276,6 +305,8 @@ fn command_create_token(
     name: String,
     symbol: String,
     icon_uri: String,
+    seller_fee_basis_points: u16,
+    creators: Vec<Creator>,
     bulk_signers: Vec<Box<dyn Signer>>,
 ) -> CommandResult {
     println_display(config, format!("Creating nft collection {}", token));
@@ -292,16 +323,32 @@ fn command_create_token(
             0,
             &system_program::id(),
         ),
-        initialize_collection(
-            config.program_id,
-            token,
-            config.fee_payer,
-            freeze_authority_pubkey,
-            total_supply,
-            name,
-            symbol,
-            icon_uri.clone(),
-        )?,
+        // Collections without royalties keep the layout readable by older clients
+        if seller_fee_basis_points == 0 && creators.is_empty() {
+            initialize_collection(
+                config.program_id,
+                token,
+                config.fee_payer,
+                freeze_authority_pubkey,
+                total_supply,
+                name,
+                symbol,
+                icon_uri.clone(),
+            )?
+        } else {
+            initialize_collection_with_royalties(
+                config.program_id,
+                token,
+                config.fee_payer,
+                freeze_authority_pubkey,
+                total_supply,
+                name,
+                symbol,
+                icon_uri.clone(),
+                seller_fee_basis_points,
+                creators,
+            )?
+        },
     ];
     // if let Some(text) = memo {
     //     instructions.push(spl_memo::build_memo(text.as_bytes(), &[&config.fee_payer]));
@@ -647,6 +694,126 @@ fn command_transfer(
     })
 }
 
+fn command_approve(
+    config: &Config,
+    nft: Pubkey,
+    delegate: Pubkey,
+    owner: Pubkey,
+    bulk_signers: Vec<Box<dyn Signer>>,
+) -> CommandResult {
+    println_display(
+        config,
+        format!("approve {} to transfer the nft {}", delegate, nft),
+    );
+
+    let instructions = vec![create_approve_instruction(
+        nft,
+        delegate,
+        owner,
+        config.program_id,
+    )?];
+    let tx_return = handle_tx(
+        &CliSignerInfo {
+            signers: bulk_signers,
+        },
+        config,
+        false,
+        0,
+        instructions,
+    )?;
+
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(cli_signature) => format_output(
+            CliApprove {
+                nft: nft.to_string(),
+                delegate: delegate.to_string(),
+                transaction_data: cli_signature,
+            },
+            "approve",
+            config,
+        ),
+        TransactionReturnData::CliSignOnlyData(cli_sign_only_data) => {
+            format_output(cli_sign_only_data, "approve", config)
+        }
+    })
+}
+
+fn command_revoke(
+    config: &Config,
+    nft: Pubkey,
+    owner: Pubkey,
+    bulk_signers: Vec<Box<dyn Signer>>,
+) -> CommandResult {
+    println_display(config, format!("revoke the delegate of the nft {}", nft));
+
+    let instructions = vec![create_revoke_instruction(nft, owner, config.program_id)?];
+    let tx_return = handle_tx(
+        &CliSignerInfo {
+            signers: bulk_signers,
+        },
+        config,
+        false,
+        0,
+        instructions,
+    )?;
+
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(cli_signature) => format_output(
+            CliRevoke {
+                nft: nft.to_string(),
+                transaction_data: cli_signature,
+            },
+            "revoke",
+            config,
+        ),
+        TransactionReturnData::CliSignOnlyData(cli_sign_only_data) => {
+            format_output(cli_sign_only_data, "revoke", config)
+        }
+    })
+}
+
+fn command_sign_creator(
+    config: &Config,
+    collection: Pubkey,
+    creator: Pubkey,
+    bulk_signers: Vec<Box<dyn Signer>>,
+) -> CommandResult {
+    println_display(
+        config,
+        format!("signing the nft collection {} as creator {}", collection, creator),
+    );
+
+    let instructions = vec![create_sign_creator_instruction(
+        collection,
+        creator,
+        config.program_id,
+    )?];
+    let tx_return = handle_tx(
+        &CliSignerInfo {
+            signers: bulk_signers,
+        },
+        config,
+        false,
+        0,
+        instructions,
+    )?;
+
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(cli_signature) => format_output(
+            CliSignCreator {
+                collection: collection.to_string(),
+                creator: creator.to_string(),
+                transaction_data: cli_signature,
+            },
+            "sign-creator",
+            config,
+        ),
+        TransactionReturnData::CliSignOnlyData(cli_sign_only_data) => {
+            format_output(cli_sign_only_data, "sign-creator", config)
+        }
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 fn command_update(
     config: &Config,
@@ -916,6 +1083,29 @@ fn main() -> Result<(), Error> {
                         .takes_value(true)
                         .help("the icon uri of nft collection. [max 200 bytes]")
                 )
+                .arg(
+                    Arg::with_name("seller_fee_basis_points")
+                        .long("seller-fee-basis-points")
+                        .validator(is_seller_fee_basis_points)
+                        .value_name("BASIS_POINTS")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("the royalty paid to the creators on secondary sales, \
+                              in basis points of the sale price. [max 10000]")
+                )
+                .arg(
+                    Arg::with_name("creator")
+                        .long("creator")
+                        .validator(is_creator)
+                        .value_name("ADDRESS:SHARE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("a creator sharing the royalty and its share in percent. \
+                              May be repeated, up to 5 creators whose shares add up to 100. \
+                              The mint authority, which is the fee payer, is verified at creation; \
+                              other creators verify themselves with `sign-creator`.")
+                )
                 .nonce_args(true)
                 .arg(memo_arg())
                 .offline_args(),
@@ -1047,7 +1237,7 @@ fn main() -> Result<(), Error> {
                         .value_name("from")
                         .takes_value(true)
                         .required(true)
-                        .help("the from account keypair \
+                        .help("the from account keypair, the nft owner or its delegate \
                                 This may be a keypair file or the ASK keyword.
                         "),
                 )
@@ -1066,6 +1256,71 @@ fn main() -> Result<(), Error> {
                 .nonce_args(true)
                 .offline_args(),
         )
+        .subcommand(
+            SubCommand::with_name("approve")
+                .about("Approve a delegate to transfer a nft, until the nft is transferred or the delegate revoked.")
+                .arg(
+                    Arg::with_name("nft")
+                        .validator(is_valid_pubkey)
+                        .value_name("nft")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("the nft to delegate"),
+                )
+                .arg(
+                    Arg::with_name("delegate")
+                        .validator(is_valid_pubkey)
+                        .value_name("delegate")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("the address of the delegate"),
+                )
+                .arg(owner_keypair_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("revoke")
+                .about("Revoke the delegate of a nft.")
+                .arg(
+                    Arg::with_name("nft")
+                        .validator(is_valid_pubkey)
+                        .value_name("nft")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("the nft to revoke the delegate of"),
+                )
+                .arg(owner_keypair_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("sign-creator")
+                .about("Verify a creator of a nft collection.")
+                .arg(
+                    Arg::with_name("address")
+                        .validator(is_valid_pubkey)
+                        .value_name("address")
+                        .takes_value(true)
+                        .required(true)
+                        .help("the address of nft collection."),
+                )
+                .arg(
+                    Arg::with_name("creator")
+                        .long("creator")
+                        .validator(is_valid_signer)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("the creator keypair.default wallet keypair, if not set. \
+                                This may be a keypair file or the ASK keyword.
+                        "),
+                )
+                .nonce_args(true)
+                .offline_args(),
+        )
         .subcommand(
             SubCommand::with_name("collection-info")
                 .about("query info of nft collection.")
@@ -1357,6 +1612,12 @@ fn main() -> Result<(), Error> {
             let name = value_t_or_exit!(arg_matches, "nft_collection_name", String);
             let symbol = value_t_or_exit!(arg_matches, "nft_collection_symbol", String);
             let icon_uri = value_t_or_exit!(arg_matches, "collection_icon_uri", String);
+            let seller_fee_basis_points =
+                value_t_or_exit!(arg_matches, "seller_fee_basis_points", u16);
+            let creators: Vec<Creator> = arg_matches
+                .values_of("creator")
+                .map(|values| values.map(|value| parse_creator(value).unwrap()).collect())
+                .unwrap_or_default();
             let mint_authority =
                 config.pubkey_or_default(arg_matches, "mint_authority", &mut wallet_manager);
             let memo = value_t!(arg_matches, "memo", String).ok();
@@ -1376,6 +1637,8 @@ fn main() -> Result<(), Error> {
                 name,
                 symbol,
                 icon_uri,
+                seller_fee_basis_points,
+                creators,
                 bulk_signers,
             )
         }
@@ -1479,6 +1742,34 @@ fn main() -> Result<(), Error> {
 
             command_transfer(&config, sender, recipient_pubkey, nft_pubkey, bulk_signers)
         }
+        ("approve", Some(arg_matches)) => {
+            let nft = value_t_or_exit!(arg_matches, "nft", Pubkey);
+            let delegate = value_t_or_exit!(arg_matches, "delegate", Pubkey);
+
+            let (owner_signer, owner) =
+                config.signer_or_default(arg_matches, "owner", &mut wallet_manager);
+            bulk_signers.push(owner_signer);
+
+            command_approve(&config, nft, delegate, owner, bulk_signers)
+        }
+        ("revoke", Some(arg_matches)) => {
+            let nft = value_t_or_exit!(arg_matches, "nft", Pubkey);
+
+            let (owner_signer, owner) =
+                config.signer_or_default(arg_matches, "owner", &mut wallet_manager);
+            bulk_signers.push(owner_signer);
+
+            command_revoke(&config, nft, owner, bulk_signers)
+        }
+        ("sign-creator", Some(arg_matches)) => {
+            let collection = value_t_or_exit!(arg_matches, "address", Pubkey);
+
+            let (creator_signer, creator) =
+                config.signer_or_default(arg_matches, "creator", &mut wallet_manager);
+            bulk_signers.push(creator_signer);
+
+            command_sign_creator(&config, collection, creator, bulk_signers)
+        }
         ("collection-info", Some(arg_matches)) => {
             let mint_address = value_t_or_exit!(arg_matches, "address", String);
             let mint_pubkey = Pubkey::from_str(mint_address.as_str()).unwrap();
@@ -1497,6 +1788,8 @@ fn main() -> Result<(), Error> {
                 symbol: mint.symbol,
                 freeze_authority: mint.freeze_authority,
                 icon_uri: mint.icon_uri,
+                seller_fee_basis_points: mint.seller_fee_basis_points,
+                creators: mint.creators.into_iter().map(UiCreator::from).collect(),
             };
             let cli_display_mint = CliDisplayMint {
                 address: mint_address.to_string(),
@@ -1523,11 +1816,18 @@ fn main() -> Result<(), Error> {
                 owner: meta.owner,
                 state: meta.state.to_string(),
                 close_authority: meta.close_authority,
+                delegate: meta.delegate,
                 nft_id: meta.nft_id,
                 nft_uri: meta.nft_uri,
                 name: collection_account_obj.name,
                 symbol: collection_account_obj.symbol,
                 timestamp: meta.mint_timestamp,
+                seller_fee_basis_points: collection_account_obj.seller_fee_basis_points,
+                creators: collection_account_obj
+                    .creators
+                    .into_iter()
+                    .map(UiCreator::from)
+                    .collect(),
             };
             let cli_display_nft_info = CliDisplayNftInfo {
                 address: nft_address.to_string(),
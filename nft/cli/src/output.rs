@@ -5,6 +5,7 @@ use huione_cli_output::{display::writeln_name_value, OutputFormat, QuietDisplay,
 use std::fmt::{self, Display};
 use huione_account_decoder::parse_nft::{UiNFTAccount, UiAccountState};
 use huione_sdk::pubkey::Pubkey;
+use hpl_nft::state::Creator;
 
 pub(crate) trait Output: Serialize + fmt::Display + QuietDisplay + VerboseDisplay {}
 
@@ -312,6 +313,102 @@ impl<T> VerboseDisplay for CliFreeze<T>
 {}
 
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliSignCreator<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    pub(crate) collection: String,
+    pub(crate) creator: String,
+    pub(crate) transaction_data: T,
+}
+
+impl<T> Display for CliSignCreator<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "collection: ", &self.collection)?;
+        writeln_name_value(f, "verified creator: ", &self.creator)?;
+        Display::fmt(&self.transaction_data, f)
+    }
+}
+
+impl<T> QuietDisplay for CliSignCreator<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{}
+impl<T> VerboseDisplay for CliSignCreator<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{}
+
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliApprove<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    pub(crate) nft: String,
+    pub(crate) delegate: String,
+    pub(crate) transaction_data: T,
+}
+
+impl<T> Display for CliApprove<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "nft: ", &self.nft)?;
+        writeln_name_value(f, "delegate: ", &self.delegate)?;
+        Display::fmt(&self.transaction_data, f)
+    }
+}
+
+impl<T> QuietDisplay for CliApprove<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{}
+impl<T> VerboseDisplay for CliApprove<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliRevoke<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    pub(crate) nft: String,
+    pub(crate) transaction_data: T,
+}
+
+impl<T> Display for CliRevoke<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "revoke delegate of nft: ", &self.nft)?;
+        Display::fmt(&self.transaction_data, f)
+    }
+}
+
+impl<T> QuietDisplay for CliRevoke<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{}
+impl<T> VerboseDisplay for CliRevoke<T>
+    where
+        T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{}
+
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CliTransfer<T>
@@ -382,6 +479,61 @@ pub struct UiNftMintInfo {
     pub freeze_authority: Option<Pubkey>, //36
     /// icon uri of nft
     pub icon_uri: String, //36
+    /// royalty of the creators, in basis points of the sale price
+    pub seller_fee_basis_points: u16,
+    /// creators sharing the royalty
+    pub creators: Vec<UiCreator>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct UiCreator {
+    /// address of the creator
+    pub address: Pubkey,
+    /// whether the creator signed the collection
+    pub verified: bool,
+    /// share of the royalty in percent
+    pub share: u8,
+}
+
+impl From<Creator> for UiCreator {
+    fn from(creator: Creator) -> Self {
+        UiCreator {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+/// Writes the royalty and the creators of a collection
+fn write_royalties(
+    f: &mut fmt::Formatter,
+    seller_fee_basis_points: u16,
+    creators: &[UiCreator],
+) -> fmt::Result {
+    writeln_name_value(
+        f,
+        "Seller fee:",
+        &format!(
+            "{}.{:02}%",
+            seller_fee_basis_points / 100,
+            seller_fee_basis_points % 100
+        ),
+    )?;
+    if creators.is_empty() {
+        return writeln_name_value(f, "Creators:", "None");
+    }
+    writeln!(f, "Creators:")?;
+    for creator in creators {
+        writeln!(
+            f,
+            "  {} {}%{}",
+            creator.address,
+            creator.share,
+            if creator.verified { "" } else { " (unverified)" }
+        )?;
+    }
+    Ok(())
 }
 
 #[derive(Serialize, Deserialize)]
@@ -423,6 +575,7 @@ impl fmt::Display for CliDisplayMint {
         writeln_name_value(f, "Mint authority:", &self.account.mint_authority.to_string())?;
         writeln_name_value(f, "Freeze authority:", &freeze_authority)?;
         writeln_name_value(f, "Initialized:", &format!("{:?}", self.account.is_initialized))?;
+        write_royalties(f, self.account.seller_fee_basis_points, &self.account.creators)?;
 
         Ok(())
     }
@@ -443,6 +596,8 @@ pub struct UiNftInfo {
     pub state: String, //1
     /// Optional authority to close the account
     pub close_authority: Option<Pubkey>,// 33
+    /// Optional delegate allowed to transfer the nft
+    pub delegate: Option<Pubkey>,// 33
     /// The nft_id of nft
     pub nft_id: u64, // 8
     /// The nft_uri of the nft
@@ -453,6 +608,10 @@ pub struct UiNftInfo {
     pub symbol: String,
     /// the nft timestamp
     pub timestamp: u64, // 8
+    /// the nft mint royalty, in basis points of the sale price
+    pub seller_fee_basis_points: u16,
+    /// the nft mint creators sharing the royalty
+    pub creators: Vec<UiCreator>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -484,7 +643,13 @@ impl fmt::Display for CliDisplayNftInfo {
         writeln_name_value(f, "NFT Proposal:", &self.account.proposal.to_string())?;
         writeln_name_value(f, "NFT Uri:", &self.account.nft_uri)?;
         writeln_name_value(f, "NFT Close Authority:", &close_authority)?;
+        let delegate = self
+            .account
+            .delegate
+            .map_or_else(|| "None".to_string(), |delegate| delegate.to_string());
+        writeln_name_value(f, "NFT Delegate:", &delegate)?;
         writeln_name_value(f, "NFT Timestamp:", &self.account.timestamp.to_string())?;
+        write_royalties(f, self.account.seller_fee_basis_points, &self.account.creators)?;
         Ok(())
     }
 }
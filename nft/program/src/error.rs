@@ -68,7 +68,28 @@ pub enum TokenError {
 
     /// Thaw a unfrozen nft
     #[error("No required parameters provided")]
-    NoProvided
+    NoProvided,
+
+    // 15
+    /// Seller fee basis points are gt 10000
+    #[error("Seller fee basis points are gt 10000")]
+    InvalidSellerFeeBasisPoints,
+
+    /// Number of creators is gt max_creators
+    #[error("Number of creators is gt max_creators")]
+    TooManyCreators,
+
+    /// Shares of the creators do not add up to 100
+    #[error("Shares of the creators do not add up to 100")]
+    InvalidCreatorShares,
+
+    /// A creator is listed more than once
+    #[error("Creator listed more than once")]
+    DuplicateCreator,
+
+    /// The signer is not a creator of the collection
+    #[error("Signer is not a creator of the collection")]
+    CreatorNotFound,
 }
 
 impl From<TokenError> for ProgramError {
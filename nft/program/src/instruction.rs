@@ -1,6 +1,7 @@
 //! Instruction types
 
 use crate::check_program_account;
+use crate::state::Creator;
 use borsh::{BorshDeserialize, BorshSerialize};
 use huione_program::instruction::{AccountMeta, Instruction};
 use huione_program::program_error::ProgramError;
@@ -55,24 +56,15 @@ pub enum TokenInstruction {
     #[account(6, name = "system_program", desc = "System program")]
     #[account(7, name = "rent", desc = "Rent info")]
     MintTo (MintNftArgs),
-    /// Transfers tokens from one account to another either directly or via a
-    /// delegate.  If this account is associated with the native mint then equal
-    /// amounts of HC and Tokens will be transferred to the destination
-    /// account.
+    /// Transfers a nft to a new owner, signed either by its owner or by its
+    /// delegate. The transfer clears the delegate.
     ///
     /// Accounts expected by this instruction:
     ///
-    ///   * Single owner/delegate
-    ///   0. `[writable]` The source account.
-    ///   1. `[writable]` The destination account.
-    ///   2. `[signer]` The source account's owner/delegate.
-    ///
-    ///   * Multisignature owner/delegate
-    ///   0. `[writable]` The source account.
-    ///   1. `[writable]` The destination account.
-    ///   2. `[]` The source account's multisignature owner/delegate.
-    ///   3. ..3+M `[signer]` M signer accounts.
-    #[account(0, signer, name = "from", desc = "the nft old owner")]
+    ///   0. `[signer]` The nft owner or delegate.
+    ///   1. `[]` The new owner.
+    ///   2. `[writable]` The nft.
+    #[account(0, signer, name = "from", desc = "the nft old owner or its delegate")]
     #[account(1, name = "to", desc = "the nft new owner")]
     #[account(2, writable, name = "nft_pubkey", desc = "the nft key")]
     Transfer,
@@ -132,6 +124,62 @@ pub enum TokenInstruction {
     #[account(0, writable, name = "nft_account", desc = "the nft that will be burn")]
     #[account(1, signer, name = "authority_account", desc = "the nft owner account")]
     Burn,
+
+    /// Marks a creator of a collection as verified. Creators are listed unverified when the
+    /// collection is initialized, unless the creator is the mint authority and signs the
+    /// initialization.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The collection.
+    ///   1. `[signer]` A creator listed in the collection.
+    #[account(0, writable, name = "nft_collection", desc = "the collection listing the creator")]
+    #[account(1, signer, name = "creator", desc = "the creator to verify")]
+    SignCreator,
+
+    /// Approves a delegate to transfer a nft on behalf of its owner, for
+    /// instance a marketplace listing the nft without taking custody of it.
+    /// Replaces any previous delegate. Nfts minted before delegates are
+    /// grown to hold one, the owner paying the extra rent.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The nft.
+    ///   1. `[]` The delegate.
+    ///   2. `[writable, signer]` The nft owner.
+    ///   3. `[]` the system_program
+    #[account(0, writable, name = "nft_account", desc = "the nft to delegate")]
+    #[account(1, name = "delegate", desc = "the delegate allowed to transfer the nft")]
+    #[account(2, writable, signer, name = "owner", desc = "the nft owner")]
+    #[account(3, name = "system_program", desc = "System program")]
+    Approve,
+
+    /// Revokes the delegate of a nft.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The nft.
+    ///   1. `[signer]` The nft owner.
+    #[account(0, writable, name = "nft_account", desc = "the nft to revoke the delegate of")]
+    #[account(1, signer, name = "owner", desc = "the nft owner")]
+    Revoke,
+
+    /// Initializes a new mint like `InitializeMint`, with royalties paid to
+    /// its creators. Creators are listed unverified, except the mint
+    /// authority when it signs as account 1.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    ///   1. `[signer]` the authority of this mint
+    ///   2. `[]` the system_program
+    ///   3. `[]` Rent sysvar
+    ///
+    #[account(0, writable, signer, name = "mint", desc = "mint key")]
+    #[account(1, signer, name = "mint_authority", desc = "Mint authority")]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, name = "rent", desc = "Rent info")]
+    InitializeMintWithRoyalties(InitializeMintWithRoyaltiesArgs),
 }
 
 /// SetAuthorityArgs
@@ -200,6 +248,17 @@ pub struct InitializeMintArgs {
     pub icon_uri: String,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+/// the instruction of InitializeMintWithRoyalties's args
+pub struct InitializeMintWithRoyaltiesArgs {
+    /// The args of the mint, as for InitializeMint
+    pub mint_args: InitializeMintArgs,
+    /// Royalty paid to the creators on secondary sales, in basis points of the sale price
+    pub seller_fee_basis_points: u16,
+    /// The creators sharing the royalties. Their shares add up to 100 unless there are none.
+    pub creators: Vec<Creator>,
+}
+
 /// Creates a `InitializeMint` instruction.
 pub fn initialize_collection(
     token_program_id: Pubkey,
@@ -237,6 +296,49 @@ pub fn initialize_collection(
     })
 }
 
+/// Creates a `InitializeMintWithRoyalties` instruction.
+pub fn initialize_collection_with_royalties(
+    token_program_id: Pubkey,
+    mint_pubkey: Pubkey,
+    mint_authority_pubkey: Pubkey,
+    freeze_authority_pubkey: Option<Pubkey>,
+    total_supply: u64,
+    name: String,
+    symbol: String,
+    icon_uri: String,
+    seller_fee_basis_points: u16,
+    creators: Vec<Creator>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(&token_program_id)?;
+
+    let royalties_args = InitializeMintWithRoyaltiesArgs {
+        mint_args: InitializeMintArgs {
+            total_supply,
+            mint_authority: mint_authority_pubkey,
+            freeze_authority: freeze_authority_pubkey,
+            name,
+            symbol,
+            icon_uri,
+        },
+        seller_fee_basis_points,
+        creators,
+    };
+    let init_ins = TokenInstruction::InitializeMintWithRoyalties(royalties_args);
+    let ins_data = init_ins.serialize();
+
+    let accounts = vec![
+        AccountMeta::new(mint_pubkey, true),
+        AccountMeta::new(mint_authority_pubkey, true),
+        AccountMeta::new_readonly(huione_program::system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    Ok(Instruction {
+        program_id: token_program_id,
+        accounts,
+        data: ins_data,
+    })
+}
+
 /// Creates a `MintTo` instruction.
 pub fn create_mint_to_inst(
     nft_collection_mint_authority: Pubkey,
@@ -297,6 +399,53 @@ pub fn create_transfer_inst(
     })
 }
 
+/// Creates an `Approve` instruction.
+pub fn create_approve_instruction(
+    nft_account: Pubkey,
+    delegate: Pubkey,
+    owner: Pubkey,
+    token_program_id: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(&token_program_id)?;
+
+    let approve_ins = TokenInstruction::Approve;
+    let ins_data = approve_ins.serialize();
+
+    let accounts = vec![
+        AccountMeta::new(nft_account, false),
+        AccountMeta::new_readonly(delegate, false),
+        AccountMeta::new(owner, true),
+        AccountMeta::new_readonly(huione_program::system_program::id(), false),
+    ];
+    Ok(Instruction {
+        program_id: token_program_id,
+        accounts,
+        data: ins_data,
+    })
+}
+
+/// Creates a `Revoke` instruction.
+pub fn create_revoke_instruction(
+    nft_account: Pubkey,
+    owner: Pubkey,
+    token_program_id: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(&token_program_id)?;
+
+    let revoke_ins = TokenInstruction::Revoke;
+    let ins_data = revoke_ins.serialize();
+
+    let accounts = vec![
+        AccountMeta::new(nft_account, false),
+        AccountMeta::new_readonly(owner, true),
+    ];
+    Ok(Instruction {
+        program_id: token_program_id,
+        accounts,
+        data: ins_data,
+    })
+}
+
 /// Creates a `Update` instruction.
 pub fn update_instruction(
     address_account: Pubkey,
@@ -408,9 +557,34 @@ pub fn create_authorize_instruction(
     })
 }
 
+/// Creates a `SignCreator` instruction.
+pub fn create_sign_creator_instruction(
+    nft_collection: Pubkey,
+    creator: Pubkey,
+    token_program_id: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(&token_program_id)?;
+
+    let sign_creator_ins = TokenInstruction::SignCreator;
+    let ins_data = sign_creator_ins.serialize();
+
+    let accounts = vec![
+        AccountMeta::new(nft_collection, false),
+        AccountMeta::new_readonly(creator, true),
+    ];
+    Ok(Instruction {
+        program_id: token_program_id,
+        accounts,
+        data: ins_data,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::instruction::{InitializeMintArgs, TokenInstruction};
+    use crate::instruction::{
+        InitializeMintArgs, InitializeMintWithRoyaltiesArgs, TokenInstruction,
+    };
+    use crate::state::Creator;
 
     #[test]
     fn test_init_mint_instruction_covert() {
@@ -422,13 +596,37 @@ mod tests {
             symbol: "terri sym".to_string(),
             icon_uri: "www.baidu.com".to_string(),
         };
-        let init_ins = TokenInstruction::InitializeMint(mint_args);
+        let init_ins = TokenInstruction::InitializeMint(mint_args.clone());
         let ret = init_ins.serialize();
         let init_ins_data = ret.as_slice();
         println!("the se ret is {:?}", init_ins_data);
 
         let ins = TokenInstruction::deserialize(init_ins_data).unwrap();
         println!("the de ret is {:?}", ins);
+        assert_eq!(ins, init_ins);
+        // the encoding of clients built before royalties
+        assert_eq!(init_ins_data[0], 0);
+        assert_eq!(
+            init_ins_data.len(),
+            1 + 8 + 32 + 1 + (4 + 5) + (4 + 9) + (4 + 13)
+        );
+
+        let royalties_ins = TokenInstruction::InitializeMintWithRoyalties(
+            InitializeMintWithRoyaltiesArgs {
+                mint_args,
+                seller_fee_basis_points: 250,
+                creators: vec![Creator {
+                    address: Default::default(),
+                    verified: false,
+                    share: 100,
+                }],
+            },
+        );
+        let royalties_ins_data = royalties_ins.serialize();
+        assert_eq!(
+            TokenInstruction::deserialize(&royalties_ins_data).unwrap(),
+            royalties_ins
+        );
     }
 }
 
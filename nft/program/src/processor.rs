@@ -2,8 +2,8 @@
 
 use crate::{
     error::TokenError,
-    instruction::{MintNftArgs, TokenInstruction},
-    state::{AccountState, MetaAccount, NftMint},
+    instruction::{InitializeMintWithRoyaltiesArgs, MintNftArgs, TokenInstruction},
+    state::{AccountState, Creator, MetaAccount, NftMint},
 };
 use num_traits::FromPrimitive;
 use huione_program::{account_info::{next_account_info, AccountInfo}, decode_error::DecodeError, entrypoint::ProgramResult, msg, program_error::{PrintProgramError, ProgramError}, program_memory::{huione_memcmp}, pubkey::{Pubkey, PUBKEY_BYTES}, system_instruction, sysvar::{rent::Rent, Sysvar}};
@@ -11,7 +11,7 @@ use huione_program::program::{invoke, invoke_signed};
 use huione_program::program_memory::huione_memset;
 use huione_program::program_pack::Pack;
 use crate::instruction::{AuthorityType, SetAuthorityArgs, UpdateType};
-use crate::state::{MAX_META_DATA_SIZE, MINT_SIZE};
+use crate::state::{MAX_CREATORS, MAX_META_DATA_SIZE, MAX_META_DATA_SIZE_V1, MAX_SELLER_FEE_BASIS_POINTS, MINT_SIZE, MINT_SIZE_V1};
 
 
 /// Program state handler.
@@ -25,7 +25,10 @@ impl Processor {
         freeze_authority: Option<Pubkey>,
         name: String,
         symbol: String,
-        icon_uri: String
+        icon_uri: String,
+        seller_fee_basis_points: u16,
+        mut creators: Vec<Creator>,
+        mint_size: usize,
     ) -> ProgramResult {
 
         // 1、Load accounts
@@ -40,6 +43,8 @@ impl Processor {
         if !mint_account_info.data_is_empty() {
             return Err(TokenError::AlreadyInUse.into());
         }
+        Self::check_royalties(seller_fee_basis_points, &creators)?;
+        Self::verify_mint_authority_creator(&mut creators, &mint_authority, payer_account_info);
 
         // 3、init mint data
         let mut mint = NftMint::default();
@@ -51,12 +56,14 @@ impl Processor {
         mint.name = name.clone();
         mint.symbol = symbol.clone();
         mint.icon_uri = icon_uri;
+        mint.seller_fee_basis_points = seller_fee_basis_points;
+        mint.creators = creators;
 
         // 4、pay for rent
         msg!("staring minus the rent for mint account");
         let rent = &Rent::from_account_info(rent_info)?;
         let required_lamports = rent
-            .minimum_balance(MINT_SIZE)
+            .minimum_balance(mint_size)
             .max(1)
             .saturating_sub(mint_account_info.lamports());
 
@@ -92,7 +99,7 @@ impl Processor {
             &[metadata_bump_seed],
         ];
         invoke_signed(
-            &system_instruction::allocate(mint_account_info.key, MINT_SIZE as u64),
+            &system_instruction::allocate(mint_account_info.key, mint_size as u64),
             allocate_accounts,
             &[metadata_signer_seeds],
         )?;
@@ -123,7 +130,95 @@ impl Processor {
         symbol: String,
         icon_uri: String
     ) -> ProgramResult {
-        Self::_process_initialize_mint(*program_id, accounts, total_supply, mint_authority, freeze_authority, name, symbol, icon_uri)
+        Self::_process_initialize_mint(*program_id, accounts, total_supply, mint_authority, freeze_authority, name, symbol, icon_uri, 0, vec![], MINT_SIZE_V1)
+    }
+
+    /// Processes an [InitializeMintWithRoyalties](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_mint_with_royalties(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        args: InitializeMintWithRoyaltiesArgs,
+    ) -> ProgramResult {
+        let InitializeMintWithRoyaltiesArgs { mint_args, seller_fee_basis_points, creators } = args;
+        Self::_process_initialize_mint(*program_id, accounts, mint_args.total_supply, mint_args.mint_authority, mint_args.freeze_authority, mint_args.name, mint_args.symbol, mint_args.icon_uri, seller_fee_basis_points, creators, MINT_SIZE)
+    }
+
+    /// Checks the royalties of a collection: at most 100% of the sale price, shared between at
+    /// most MAX_CREATORS distinct creators whose shares add up to 100.
+    fn check_royalties(seller_fee_basis_points: u16, creators: &[Creator]) -> ProgramResult {
+        if seller_fee_basis_points > MAX_SELLER_FEE_BASIS_POINTS {
+            return Err(TokenError::InvalidSellerFeeBasisPoints.into());
+        }
+        if creators.len() > MAX_CREATORS {
+            return Err(TokenError::TooManyCreators.into());
+        }
+        if creators.is_empty() {
+            return Ok(());
+        }
+
+        let mut total_share: u8 = 0;
+        for (index, creator) in creators.iter().enumerate() {
+            if creators[..index]
+                .iter()
+                .any(|other| Self::cmp_pubkeys(&other.address, &creator.address))
+            {
+                return Err(TokenError::DuplicateCreator.into());
+            }
+            total_share = total_share
+                .checked_add(creator.share)
+                .ok_or(TokenError::InvalidCreatorShares)?;
+        }
+        if total_share != 100 {
+            return Err(TokenError::InvalidCreatorShares.into());
+        }
+        Ok(())
+    }
+
+    /// Marks only the mint authority as a verified creator, when it signs the initialization as
+    /// `authority_info`. Other creators verify themselves with SignCreator.
+    fn verify_mint_authority_creator(
+        creators: &mut [Creator],
+        mint_authority: &Pubkey,
+        authority_info: &AccountInfo,
+    ) {
+        let authority_signed =
+            authority_info.is_signer && Self::cmp_pubkeys(authority_info.key, mint_authority);
+        for creator in creators.iter_mut() {
+            creator.verified =
+                authority_signed && Self::cmp_pubkeys(&creator.address, mint_authority);
+        }
+    }
+
+    /// Processes a [SignCreator](enum.TokenInstruction.html) instruction.
+    pub fn process_sign_creator(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // 1、load accounts
+        let account_info_iter = &mut accounts.iter();
+        let nft_collection_account_info = next_account_info(account_info_iter)?;
+        let creator_account_info = next_account_info(account_info_iter)?;
+
+        // 2、check the collection and the creator's signature
+        Self::check_account_owner(program_id, nft_collection_account_info)?;
+        if !creator_account_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut mint = NftMint::unpack(&nft_collection_account_info.data.borrow())?;
+
+        // 3、verify the creator and save
+        let creator = mint
+            .creators
+            .iter_mut()
+            .find(|creator| Self::cmp_pubkeys(&creator.address, creator_account_info.key))
+            .ok_or(TokenError::CreatorNotFound)?;
+        if creator.verified {
+            msg!("creator [{}] already verified", creator_account_info.key);
+            return Ok(());
+        }
+        creator.verified = true;
+        msg!("verify creator [{}] of [{}]", creator_account_info.key, nft_collection_account_info.key);
+        NftMint::pack(mint, &mut nft_collection_account_info.data.borrow_mut())
     }
 
     // mint to
@@ -279,15 +374,20 @@ impl Processor {
             msg!("Account Frozen.");
             return Err(TokenError::AccountFrozen.into());
         }
-        // Check NFT owner is correct.
-        if !Self::cmp_pubkeys(&nft_meta.owner, &source_account_info.key) {
+        // Check the signer is the NFT owner or its delegate.
+        let is_delegate = nft_meta
+            .delegate
+            .map_or(false, |delegate| Self::cmp_pubkeys(&delegate, source_account_info.key));
+        if !Self::cmp_pubkeys(&nft_meta.owner, &source_account_info.key) && !is_delegate {
             msg!("Owner mismatch.");
             return Err(TokenError::OwnerMismatch.into());
         }
+        if !source_account_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
 
         // 3、Inspection of NFT rotation
-        let self_transfer =
-            Self::cmp_pubkeys(source_account_info.key, destination_account_info.key);
+        let self_transfer = Self::cmp_pubkeys(&nft_meta.owner, destination_account_info.key);
 
         // This check MUST occur just before the amounts are manipulated
         // to ensure self-transfers are fully validated
@@ -296,9 +396,10 @@ impl Processor {
             return Ok(());
         }
 
-        // 4、To modify nft ownership
-        msg!("transfer_nft [{}] from [{}] to [{}]",nft_account_info.key, source_account_info.key, destination_account_info.key);
+        // 4、To modify nft ownership, the delegate was approved by the previous owner
+        msg!("transfer_nft [{}] from [{}] to [{}]",nft_account_info.key, nft_meta.owner, destination_account_info.key);
         nft_meta.owner = *destination_account_info.key;
+        nft_meta.delegate = None;
         // let mut nft_meta_data = nft_meta.serialize_to().unwrap();
         // let mut empty_tail = vec![0 as u8; MAX_META_DATA_SIZE - nft_meta_data.len()];
         // nft_meta_data.append(&mut empty_tail);
@@ -306,6 +407,80 @@ impl Processor {
         MetaAccount::pack(nft_meta,&mut meta_data)
     }
 
+    /// Processes an [Approve](enum.TokenInstruction.html) instruction.
+    pub fn process_approve(
+        _: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // 1、load accounts
+        let account_info_iter = &mut accounts.iter();
+        let nft_account_info = next_account_info(account_info_iter)?;
+        let delegate_account_info = next_account_info(account_info_iter)?;
+        let owner_account_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        // 2、check the owner
+        let mut nft_meta = MetaAccount::unpack(&nft_account_info.data.borrow())?;
+        if nft_meta.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+        if !Self::cmp_pubkeys(&nft_meta.owner, owner_account_info.key) {
+            return Err(TokenError::OwnerMismatch.into());
+        }
+        if !owner_account_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // 3、grow nfts minted before delegates, the owner pays for rent
+        if nft_account_info.data_len() == MAX_META_DATA_SIZE_V1 {
+            let required_lamports = Rent::get()?
+                .minimum_balance(MAX_META_DATA_SIZE)
+                .max(1)
+                .saturating_sub(nft_account_info.lamports());
+            if required_lamports > 0 {
+                msg!("Transfer {} lamports to the nft account", required_lamports);
+                invoke(
+                    &system_instruction::transfer(owner_account_info.key, nft_account_info.key, required_lamports),
+                    &[
+                        owner_account_info.clone(),
+                        nft_account_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+            nft_account_info.realloc(MAX_META_DATA_SIZE, false)?;
+        }
+
+        // 4、set the delegate and save
+        msg!("approve [{}] to transfer nft [{}]", delegate_account_info.key, nft_account_info.key);
+        nft_meta.delegate = Some(*delegate_account_info.key);
+        MetaAccount::pack(nft_meta, &mut nft_account_info.data.borrow_mut())
+    }
+
+    /// Processes a [Revoke](enum.TokenInstruction.html) instruction.
+    pub fn process_revoke(
+        _: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // 1、load accounts
+        let account_info_iter = &mut accounts.iter();
+        let nft_account_info = next_account_info(account_info_iter)?;
+        let owner_account_info = next_account_info(account_info_iter)?;
+
+        // 2、check the owner
+        let mut nft_meta = MetaAccount::unpack(&nft_account_info.data.borrow())?;
+        if !Self::cmp_pubkeys(&nft_meta.owner, owner_account_info.key) {
+            return Err(TokenError::OwnerMismatch.into());
+        }
+        if !owner_account_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // 3、clear the delegate and save
+        nft_meta.delegate = None;
+        MetaAccount::pack(nft_meta, &mut nft_account_info.data.borrow_mut())
+    }
+
     /// Processes an [Instruction](enum.Instruction.html).
     pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
         let instruction = TokenInstruction::deserialize(input).unwrap();
@@ -316,6 +491,11 @@ impl Processor {
                 Self::process_initialize_mint(program_id, accounts, args.total_supply, args.mint_authority, args.freeze_authority, args.name, args.symbol, args.icon_uri)
             }
 
+            TokenInstruction::InitializeMintWithRoyalties(args) => {
+                msg!("Instruction: InitializeMintWithRoyalties");
+                Self::process_initialize_mint_with_royalties(program_id, accounts, args)
+            }
+
             TokenInstruction::MintTo (args) => {
                 msg!("Instruction: InitializeAccount");
                 Self::process_mint_to(program_id, accounts, args)
@@ -457,7 +637,7 @@ impl Processor {
 
                 // nft_account_info set lamports to 0，it will be clean
                 **nft_account_info.lamports.borrow_mut() = 0;
-                huione_memset(*nft_account_info.data.borrow_mut(), 0, MetaAccount::LEN);
+                huione_memset(*nft_account_info.data.borrow_mut(), 0, nft_account_info.data_len());
                 Ok(())
             }
 
@@ -536,6 +716,21 @@ impl Processor {
                     }
                 }
             }
+
+            TokenInstruction::SignCreator => {
+                msg!("Instruction: SignCreator");
+                Self::process_sign_creator(program_id, accounts)
+            }
+
+            TokenInstruction::Approve => {
+                msg!("Instruction: Approve");
+                Self::process_approve(program_id, accounts)
+            }
+
+            TokenInstruction::Revoke => {
+                msg!("Instruction: Revoke");
+                Self::process_revoke(program_id, accounts)
+            }
         }
     }
 
@@ -595,6 +790,21 @@ impl PrintProgramError for TokenError {
             TokenError::NoProvided => {
                 msg!("Error: No required parameters provided")
             }
+            TokenError::InvalidSellerFeeBasisPoints => {
+                msg!("Error: seller fee basis points are gt 10000")
+            }
+            TokenError::TooManyCreators => {
+                msg!("Error: number of creators is gt max_creators")
+            }
+            TokenError::InvalidCreatorShares => {
+                msg!("Error: shares of the creators do not add up to 100")
+            }
+            TokenError::DuplicateCreator => {
+                msg!("Error: creator listed more than once")
+            }
+            TokenError::CreatorNotFound => {
+                msg!("Error: signer is not a creator of the collection")
+            }
             #[warn(unreachable_patterns)]
             _ => {unreachable!()}
         }
@@ -610,9 +820,9 @@ mod tests {
     use huione_program::program_pack::Pack;
     use huione_program::pubkey::Pubkey;
     use huione_sdk::account::{create_is_signer_account_infos};
-    use crate::instruction::{AuthorityType, create_authorize_instruction, create_burn_instruction, create_freeze_instruction, create_thaw_instruction, create_transfer_inst};
+    use crate::instruction::{AuthorityType, create_approve_instruction, create_authorize_instruction, create_burn_instruction, create_freeze_instruction, create_revoke_instruction, create_sign_creator_instruction, create_thaw_instruction, create_transfer_inst};
     use crate::processor::Processor;
-    use crate::state::{AccountState, MAX_META_DATA_SIZE, MetaAccount, MINT_SIZE, NftMint};
+    use crate::state::{AccountState, Creator, MAX_CREATORS, MAX_META_DATA_SIZE, MetaAccount, MINT_SIZE, NftMint};
     use huione_sdk::account::Account as HuiOneAccount;
     use crate::error::TokenError;
 
@@ -705,7 +915,9 @@ mod tests {
             name: "".to_string(),
             symbol: "".to_string(),
             freeze_authority: None,
-            icon_uri: "".to_string()
+            icon_uri: "".to_string(),
+            seller_fee_basis_points: 0,
+            creators: vec![],
         };
 
         let mut mint_account_data = [0u8; MINT_SIZE];
@@ -807,7 +1019,9 @@ mod tests {
             name: "".to_string(),
             symbol: "".to_string(),
             freeze_authority: None,
-            icon_uri: "".to_string()
+            icon_uri: "".to_string(),
+            seller_fee_basis_points: 0,
+            creators: vec![],
         };
 
         let mut mint_account_data = [0u8; MINT_SIZE];
@@ -983,7 +1197,9 @@ mod tests {
             name: "".to_string(),
             symbol: "".to_string(),
             freeze_authority: None,
-            icon_uri: "".to_string()
+            icon_uri: "".to_string(),
+            seller_fee_basis_points: 0,
+            creators: vec![],
         };
 
         let mut mint_account_data = [0u8; MINT_SIZE];
@@ -1065,4 +1281,283 @@ mod tests {
         assert_eq!(mint_account_data_obj_after_authorize.freeze_authority, Some(new_authority_puk));
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_check_royalties() {
+        let creator = |share| Creator {
+            address: Pubkey::new_unique(),
+            verified: false,
+            share,
+        };
+        assert_eq!(Ok(()), Processor::check_royalties(0, &[]));
+        assert_eq!(Ok(()), Processor::check_royalties(10_000, &[creator(60), creator(40)]));
+        assert_eq!(
+            Err(TokenError::InvalidSellerFeeBasisPoints.into()),
+            Processor::check_royalties(10_001, &[creator(100)])
+        );
+        assert_eq!(
+            Err(TokenError::TooManyCreators.into()),
+            Processor::check_royalties(500, &vec![creator(0); MAX_CREATORS + 1])
+        );
+        assert_eq!(
+            Err(TokenError::InvalidCreatorShares.into()),
+            Processor::check_royalties(500, &[creator(60), creator(30)])
+        );
+        assert_eq!(
+            Err(TokenError::InvalidCreatorShares.into()),
+            Processor::check_royalties(500, &[creator(200), creator(100)])
+        );
+        let duplicate = creator(50);
+        assert_eq!(
+            Err(TokenError::DuplicateCreator.into()),
+            Processor::check_royalties(500, &[duplicate.clone(), duplicate])
+        );
+    }
+
+    #[test]
+    fn test_sign_creator() {
+        let program_id = crate::id();
+        let mint_account_puk = Pubkey::new_unique();
+        let creator_puk = Pubkey::new_unique();
+        let mint_account_data_obj = NftMint {
+            is_initialized: true,
+            seller_fee_basis_points: 500,
+            creators: vec![
+                Creator {
+                    address: Pubkey::new_unique(),
+                    verified: true,
+                    share: 50,
+                },
+                Creator {
+                    address: creator_puk,
+                    verified: false,
+                    share: 50,
+                },
+            ],
+            ..NftMint::default()
+        };
+        let mut mint_account_data = [0u8; MINT_SIZE];
+        NftMint::pack(mint_account_data_obj.clone(), &mut mint_account_data).unwrap();
+        let mut mint_account = HuiOneAccount::new(10, MINT_SIZE, &program_id);
+        mint_account.data = mint_account_data.to_vec();
+        let mut creator_account = HuiOneAccount::default();
+
+        // not a creator
+        assert_eq!(
+            Err(TokenError::CreatorNotFound.into()),
+            do_process_instruction(
+                create_sign_creator_instruction(mint_account_puk, Pubkey::new_unique(), program_id).unwrap(),
+                vec![&mut mint_account, &mut creator_account]
+            )
+        );
+
+        // creator did not sign
+        let mut instruction = create_sign_creator_instruction(mint_account_puk, creator_puk, program_id).unwrap();
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(instruction, vec![&mut mint_account, &mut creator_account])
+        );
+
+        // collection owned by another program
+        let mut other_mint_account = HuiOneAccount::new(10, MINT_SIZE, &Pubkey::new_unique());
+        other_mint_account.data = mint_account_data.to_vec();
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process_instruction(
+                create_sign_creator_instruction(mint_account_puk, creator_puk, program_id).unwrap(),
+                vec![&mut other_mint_account, &mut creator_account]
+            )
+        );
+
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_sign_creator_instruction(mint_account_puk, creator_puk, program_id).unwrap(),
+                vec![&mut mint_account, &mut creator_account]
+            )
+        );
+        let mint_account_data_obj_after_sign = NftMint::unpack(mint_account.data.as_slice()).unwrap();
+        assert!(mint_account_data_obj_after_sign.creators.iter().all(|creator| creator.verified));
+        assert_eq!(mint_account_data_obj_after_sign.seller_fee_basis_points, 500);
+
+        // signing again changes nothing
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_sign_creator_instruction(mint_account_puk, creator_puk, program_id).unwrap(),
+                vec![&mut mint_account, &mut creator_account]
+            )
+        );
+        assert_eq!(NftMint::unpack(mint_account.data.as_slice()).unwrap(), mint_account_data_obj_after_sign);
+    }
+
+    #[test]
+    fn test_delegate_transfer() {
+        let program_id = crate::id();
+        let owner_puk = Pubkey::new_unique();
+        let delegate_puk = Pubkey::new_unique();
+        let to_account_puk = Pubkey::new_unique();
+        let nft_account_puk = Pubkey::new_unique();
+
+        let nft_account_data_obj = MetaAccount {
+            owner: owner_puk,
+            state: AccountState::Initialized,
+            ..MetaAccount::default()
+        };
+        let mut nft_account_data = [0u8; MAX_META_DATA_SIZE];
+        MetaAccount::pack(nft_account_data_obj.clone(), &mut nft_account_data).unwrap();
+        let mut nft_account = HuiOneAccount::new(1000, MAX_META_DATA_SIZE, &program_id);
+        nft_account.data = nft_account_data.to_vec();
+        let mut owner_account = HuiOneAccount::default();
+        let mut delegate_account = HuiOneAccount::default();
+        let mut to_account = HuiOneAccount::default();
+        let mut system_account = HuiOneAccount::default();
+
+        // not approved yet
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                create_transfer_inst(delegate_puk, to_account_puk, nft_account_puk, program_id).unwrap(),
+                vec![&mut delegate_account, &mut to_account, &mut nft_account]
+            )
+        );
+
+        // only the owner approves
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                create_approve_instruction(nft_account_puk, delegate_puk, delegate_puk, program_id).unwrap(),
+                vec![&mut nft_account, &mut delegate_account, &mut to_account, &mut system_account]
+            )
+        );
+        let mut instruction = create_approve_instruction(nft_account_puk, delegate_puk, owner_puk, program_id).unwrap();
+        instruction.accounts[2].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![&mut nft_account, &mut delegate_account, &mut owner_account, &mut system_account]
+            )
+        );
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_approve_instruction(nft_account_puk, delegate_puk, owner_puk, program_id).unwrap(),
+                vec![&mut nft_account, &mut delegate_account, &mut owner_account, &mut system_account]
+            )
+        );
+        assert_eq!(MetaAccount::unpack(&nft_account.data).unwrap().delegate, Some(delegate_puk));
+
+        // revoke, then approve again
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_revoke_instruction(nft_account_puk, owner_puk, program_id).unwrap(),
+                vec![&mut nft_account, &mut owner_account]
+            )
+        );
+        assert_eq!(MetaAccount::unpack(&nft_account.data).unwrap().delegate, None);
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_approve_instruction(nft_account_puk, delegate_puk, owner_puk, program_id).unwrap(),
+                vec![&mut nft_account, &mut delegate_account, &mut owner_account, &mut system_account]
+            )
+        );
+
+        // the delegate must sign
+        let mut instruction = create_transfer_inst(delegate_puk, to_account_puk, nft_account_puk, program_id).unwrap();
+        instruction.accounts[0].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(instruction, vec![&mut delegate_account, &mut to_account, &mut nft_account])
+        );
+
+        // the delegate transfers, which clears the delegate
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_transfer_inst(delegate_puk, to_account_puk, nft_account_puk, program_id).unwrap(),
+                vec![&mut delegate_account, &mut to_account, &mut nft_account]
+            )
+        );
+        let transfer_after_data_obj = MetaAccount::unpack(&nft_account.data).unwrap();
+        assert_eq!(transfer_after_data_obj.owner, to_account_puk);
+        assert_eq!(transfer_after_data_obj.delegate, None);
+
+        // the former delegate cannot transfer again
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                create_transfer_inst(delegate_puk, owner_puk, nft_account_puk, program_id).unwrap(),
+                vec![&mut delegate_account, &mut owner_account, &mut nft_account]
+            )
+        );
+
+        // a delegate transferring to itself becomes the owner
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_approve_instruction(nft_account_puk, delegate_puk, to_account_puk, program_id).unwrap(),
+                vec![&mut nft_account, &mut delegate_account, &mut to_account, &mut system_account]
+            )
+        );
+        let mut delegate_destination_account = HuiOneAccount::default();
+        assert_eq!(
+            Ok(()),
+            do_process_instruction(
+                create_transfer_inst(delegate_puk, delegate_puk, nft_account_puk, program_id).unwrap(),
+                vec![&mut delegate_account, &mut delegate_destination_account, &mut nft_account]
+            )
+        );
+        let transfer_after_data_obj = MetaAccount::unpack(&nft_account.data).unwrap();
+        assert_eq!(transfer_after_data_obj.owner, delegate_puk);
+        assert_eq!(transfer_after_data_obj.delegate, None);
+    }
+
+    #[test]
+    fn test_verify_mint_authority_creator() {
+        let mint_authority_puk = Pubkey::new_unique();
+        let other_puk = Pubkey::new_unique();
+        let creators = vec![
+            Creator {
+                address: mint_authority_puk,
+                verified: false,
+                share: 50,
+            },
+            Creator {
+                address: other_puk,
+                // clients cannot verify creators themselves
+                verified: true,
+                share: 50,
+            },
+        ];
+        let verified = |creators: &[Creator]| creators.iter().map(|creator| creator.verified).collect::<Vec<_>>();
+        let mut authority_account = HuiOneAccount::default();
+        let mut other_account = HuiOneAccount::default();
+        let mut accounts = vec![
+            (&mint_authority_puk, true, &mut authority_account),
+            (&other_puk, true, &mut other_account),
+        ];
+        let account_infos = create_is_signer_account_infos(&mut accounts);
+
+        // the signing mint authority is verified
+        let mut signed_creators = creators.clone();
+        Processor::verify_mint_authority_creator(&mut signed_creators, &mint_authority_puk, &account_infos[0]);
+        assert_eq!(verified(&signed_creators), vec![true, false]);
+
+        // a signer other than the mint authority verifies no one
+        let mut other_creators = creators.clone();
+        Processor::verify_mint_authority_creator(&mut other_creators, &mint_authority_puk, &account_infos[1]);
+        assert_eq!(verified(&other_creators), vec![false, false]);
+
+        // the mint authority must sign
+        let mut authority_account = HuiOneAccount::default();
+        let mut accounts = vec![(&mint_authority_puk, false, &mut authority_account)];
+        let account_infos = create_is_signer_account_infos(&mut accounts);
+        let mut unsigned_creators = creators;
+        Processor::verify_mint_authority_creator(&mut unsigned_creators, &mint_authority_puk, &account_infos[0]);
+        assert_eq!(verified(&unsigned_creators), vec![false, false]);
+    }
+}
@@ -16,9 +16,31 @@ pub const MAX_ICON_URI_SIZE: usize = 200;
 pub const MAX_MINT_NAME_SIZE: usize = 32;
 /// MAX_MINT_SYMBOL_SIZE
 pub const MAX_MINT_SYMBOL_SIZE: usize = 8;
-/// MINT_SIZE
-pub const MINT_SIZE: usize =
+/// MAX_CREATORS
+pub const MAX_CREATORS: usize = 5;
+/// CREATOR_SIZE
+pub const CREATOR_SIZE: usize = 32 + 1 + 1;
+/// CREATORS_SIZE, the number of creators followed by MAX_CREATORS creator slots
+pub const CREATORS_SIZE: usize = 1 + MAX_CREATORS * CREATOR_SIZE;
+/// MAX_SELLER_FEE_BASIS_POINTS, 100% of the sale price
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10_000;
+/// MINT_SIZE_V1, the size of collections without royalties
+pub const MINT_SIZE_V1: usize =
     32 + 8 + 8 + 1 + MAX_MINT_NAME_SIZE + MAX_MINT_SYMBOL_SIZE + 33 + MAX_ICON_URI_SIZE;
+/// MINT_SIZE, the size of collections with royalties
+pub const MINT_SIZE: usize = MINT_SIZE_V1 + 2 + CREATORS_SIZE;
+
+/// A creator of the nfts of a collection, paid a share of the royalties.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct Creator {
+    /// The creator's address
+    pub address: Pubkey, //32
+    /// Is `true` once the creator has signed the collection with `SignCreator`
+    pub verified: bool, //1
+    /// The creator's share of the royalties, in percent
+    pub share: u8, //1
+}
 
 /// Mint data.
 #[repr(C)]
@@ -42,6 +64,10 @@ pub struct NftMint {
     pub freeze_authority: Option<Pubkey>, // 33
     /// the uri of icon
     pub icon_uri: String, // 200
+    /// Royalty paid to the creators on secondary sales, in basis points of the sale price
+    pub seller_fee_basis_points: u16, // 2
+    /// The creators sharing the royalties, at most MAX_CREATORS
+    pub creators: Vec<Creator>, // 171
 }
 // impl Sealed for NftMint {}
 impl IsInitialized for NftMint {
@@ -68,9 +94,41 @@ impl NftMint {
 
 impl Sealed for NftMint {}
 
+/// Collections come in two layout versions, told apart by the account length: MINT_SIZE_V1
+/// collections were created before royalties and read as having none, MINT_SIZE collections
+/// end with the royalties.
 impl Pack for NftMint {
     const LEN: usize = MINT_SIZE;
 
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() == MINT_SIZE {
+            Self::unpack_from_slice(input)
+        } else if input.len() == MINT_SIZE_V1 {
+            let mut src = [0; MINT_SIZE];
+            src[..MINT_SIZE_V1].copy_from_slice(input);
+            Self::unpack_from_slice(&src)
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() == MINT_SIZE {
+            src.pack_into_slice(dst);
+            Ok(())
+        } else if dst.len() == MINT_SIZE_V1
+            && src.seller_fee_basis_points == 0
+            && src.creators.is_empty()
+        {
+            let mut data = [0; MINT_SIZE];
+            src.pack_into_slice(&mut data);
+            dst.copy_from_slice(&data[..MINT_SIZE_V1]);
+            Ok(())
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, MINT_SIZE];
         let (
@@ -82,7 +140,21 @@ impl Pack for NftMint {
             symbol_dst,
             freeze_authority_dst,
             icon_uri_dst,
-        ) = mut_array_refs![dst, 32, 8, 8, 1, 32, 8, 33, MAX_ICON_URI_SIZE];
+            seller_fee_basis_points_dst,
+            creators_dst,
+        ) = mut_array_refs![
+            dst,
+            32,
+            8,
+            8,
+            1,
+            32,
+            8,
+            33,
+            MAX_ICON_URI_SIZE,
+            2,
+            CREATORS_SIZE
+        ];
         let NftMint {
             mint_authority,
             supply,
@@ -92,6 +164,8 @@ impl Pack for NftMint {
             symbol,
             freeze_authority,
             icon_uri,
+            seller_fee_basis_points,
+            creators,
         } = self;
 
         mint_authority_dst.copy_from_slice(mint_authority.as_ref());
@@ -102,6 +176,8 @@ impl Pack for NftMint {
         pack_string_into(symbol, symbol_dst).expect("invalid length of name");
         pack_option_key_into(freeze_authority, freeze_authority_dst);
         pack_string_into(icon_uri, icon_uri_dst).expect("invalid length of name");
+        *seller_fee_basis_points_dst = seller_fee_basis_points.to_le_bytes();
+        pack_creators_into(creators, creators_dst).expect("invalid number of creators");
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -115,6 +191,8 @@ impl Pack for NftMint {
             symbol,
             freeze_authority,
             icon_uri,
+            seller_fee_basis_points,
+            creators,
         ) = array_refs![
             src,
             32,
@@ -124,7 +202,9 @@ impl Pack for NftMint {
             MAX_MINT_NAME_SIZE,
             MAX_MINT_SYMBOL_SIZE,
             33,
-            MAX_ICON_URI_SIZE
+            MAX_ICON_URI_SIZE,
+            2,
+            CREATORS_SIZE
         ];
         Ok(NftMint {
             mint_authority: Pubkey::new_from_array(*mint_authority),
@@ -135,10 +215,60 @@ impl Pack for NftMint {
             symbol: unpack_string(symbol).unwrap(),
             freeze_authority: unpack_option_key(freeze_authority),
             icon_uri: unpack_string(icon_uri).unwrap(),
+            seller_fee_basis_points: u16::from_le_bytes(*seller_fee_basis_points),
+            creators: unpack_creators(creators)?,
         })
     }
 }
 
+/// pack creators, their number followed by the creators, less replace with zero
+fn pack_creators_into(src: &[Creator], target_dst: &mut [u8; CREATORS_SIZE]) -> Result<(), String> {
+    if src.len() > MAX_CREATORS {
+        return Err("too many creators".to_string());
+    }
+    let (count_dst, creators_dst) = mut_array_refs![target_dst, 1, MAX_CREATORS * CREATOR_SIZE];
+    count_dst[0] = src.len() as u8;
+    for (index, creator_dst) in creators_dst.chunks_exact_mut(CREATOR_SIZE).enumerate() {
+        let creator_dst = array_mut_ref![creator_dst, 0, CREATOR_SIZE];
+        let (address_dst, verified_dst, share_dst) = mut_array_refs![creator_dst, 32, 1, 1];
+        match src.get(index) {
+            Some(creator) => {
+                address_dst.copy_from_slice(creator.address.as_ref());
+                verified_dst[0] = creator.verified as u8;
+                share_dst[0] = creator.share;
+            }
+            None => {
+                *address_dst = [0; 32];
+                verified_dst[0] = 0;
+                share_dst[0] = 0;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// unpack creators.
+fn unpack_creators(src_data: &[u8; CREATORS_SIZE]) -> Result<Vec<Creator>, ProgramError> {
+    let (count, creators) = array_refs![src_data, 1, MAX_CREATORS * CREATOR_SIZE];
+    let count = count[0] as usize;
+    if count > MAX_CREATORS {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(creators
+        .chunks_exact(CREATOR_SIZE)
+        .take(count)
+        .map(|creator| {
+            let creator = array_ref![creator, 0, CREATOR_SIZE];
+            let (address, verified, share) = array_refs![creator, 32, 1, 1];
+            Creator {
+                address: Pubkey::new_from_array(*address),
+                verified: verified[0] != 0,
+                share: share[0],
+            }
+        })
+        .collect())
+}
+
 /// pack option key.
 fn pack_option_key_into(src: &Option<Pubkey>, target_dst: &mut [u8; 33]) {
     let (tag, body) = mut_array_refs![target_dst, 1, 32];
@@ -210,12 +340,16 @@ pub struct MetaAccount {
     pub mint_timestamp: u64, // 8
     /// The suffix of the nft
     pub nft_uri: String, // 200
+    /// Optional delegate allowed to transfer the nft, cleared by the transfer
+    pub delegate: Option<Pubkey>, // 33
 }
 
 /// MAX_TOKEN_URI
 const MAX_TOKEN_URI_SIZE: usize = 200;
+/// meta data size of nfts minted before delegates
+pub const MAX_META_DATA_SIZE_V1: usize = 32 + 32 + 32 + 32 + 1 + 33 + 8 + 8 + MAX_TOKEN_URI_SIZE;
 /// max meta data size
-pub const MAX_META_DATA_SIZE: usize = 32 + 32 + 32 + 32 + 1 + 33 + 8 + 8 + MAX_TOKEN_URI_SIZE;
+pub const MAX_META_DATA_SIZE: usize = MAX_META_DATA_SIZE_V1 + 33;
 
 impl Sealed for MetaAccount {}
 impl IsInitialized for MetaAccount {
@@ -224,9 +358,38 @@ impl IsInitialized for MetaAccount {
     }
 }
 
+/// Nfts come in two layout versions, told apart by the account length: MAX_META_DATA_SIZE_V1
+/// nfts were minted before delegates and read as having none, MAX_META_DATA_SIZE nfts end with
+/// the delegate.
 impl Pack for MetaAccount {
     const LEN: usize = MAX_META_DATA_SIZE;
 
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() == MAX_META_DATA_SIZE {
+            Self::unpack_from_slice(input)
+        } else if input.len() == MAX_META_DATA_SIZE_V1 {
+            let mut src = [0; MAX_META_DATA_SIZE];
+            src[..MAX_META_DATA_SIZE_V1].copy_from_slice(input);
+            Self::unpack_from_slice(&src)
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() == MAX_META_DATA_SIZE {
+            src.pack_into_slice(dst);
+            Ok(())
+        } else if dst.len() == MAX_META_DATA_SIZE_V1 && src.delegate.is_none() {
+            let mut data = [0; MAX_META_DATA_SIZE];
+            src.pack_into_slice(&mut data);
+            dst.copy_from_slice(&data[..MAX_META_DATA_SIZE_V1]);
+            Ok(())
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, MAX_META_DATA_SIZE];
         let (
@@ -239,7 +402,8 @@ impl Pack for MetaAccount {
             nft_id_dst,
             timestamp_dst,
             nft_uri_dst,
-        ) = mut_array_refs![dst, 32, 32, 32, 32, 1, 33, 8, 8, MAX_TOKEN_URI_SIZE];
+            delegate_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 32, 1, 33, 8, 8, MAX_TOKEN_URI_SIZE, 33];
         let MetaAccount {
             collection,
             author,
@@ -250,6 +414,7 @@ impl Pack for MetaAccount {
             nft_id,
             mint_timestamp,
             nft_uri,
+            delegate,
         } = self;
 
         collection_dst.copy_from_slice(collection.as_ref());
@@ -261,6 +426,7 @@ impl Pack for MetaAccount {
         *nft_id_dst = nft_id.to_le_bytes();
         *timestamp_dst = mint_timestamp.to_le_bytes();
         pack_string_into(nft_uri, nft_uri_dst).expect("invalid length of token_uri");
+        pack_option_key_into(delegate, delegate_dst);
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -275,7 +441,8 @@ impl Pack for MetaAccount {
             nft_id,
             timestamp,
             nft_uri,
-        ) = array_refs![src, 32, 32, 32, 32, 1, 33, 8, 8, MAX_TOKEN_URI_SIZE];
+            delegate,
+        ) = array_refs![src, 32, 32, 32, 32, 1, 33, 8, 8, MAX_TOKEN_URI_SIZE, 33];
         Ok(MetaAccount {
             collection: Pubkey::new_from_array(*collection),
             author: Pubkey::new_from_array(*author),
@@ -287,6 +454,7 @@ impl Pack for MetaAccount {
             nft_id: u64::from_le_bytes(*nft_id),
             mint_timestamp: u64::from_le_bytes(*timestamp),
             nft_uri: unpack_string(nft_uri).unwrap(),
+            delegate: unpack_option_key(delegate),
         })
     }
 }
@@ -327,6 +495,7 @@ impl MetaAccount {
         let nft_id: u64 = BorshDeserialize::deserialize(buf)?;
         let mint_timestamp: u64 = BorshDeserialize::deserialize(buf)?;
         let nft_uri: String = BorshDeserialize::deserialize(buf)?;
+        let delegate: Option<Pubkey> = BorshDeserialize::deserialize(buf)?;
 
         /* We can have accidentally valid, but corrupted data, particularly on the Collection struct,
         so to increase probability of catching errors If any of these deserializations fail, set all values to None.
@@ -342,6 +511,7 @@ impl MetaAccount {
             nft_uri,
             nft_id,
             mint_timestamp,
+            delegate,
         };
 
         Ok(metadata)
@@ -410,6 +580,7 @@ mod tests {
             nft_id: 0,
             nft_uri: "".to_string(),
             mint_timestamp: 1,
+            delegate: None,
         };
         let mut dst = [0 as u8; MAX_META_DATA_SIZE];
         meta.pack_into_slice(&mut dst);
@@ -418,6 +589,18 @@ mod tests {
         let unpack_ret = MetaAccount::unpack_from_slice(&dst);
         println!("unpack empty ret {:?}", unpack_ret);
 
+        // delegated nft
+        let meta = MetaAccount {
+            state: AccountState::Initialized,
+            owner: Pubkey::new_unique(),
+            delegate: Some(Pubkey::new_unique()),
+            nft_uri: "www.baidu.com".to_string(),
+            ..MetaAccount::default()
+        };
+        let mut dst = [0 as u8; MAX_META_DATA_SIZE];
+        meta.pack_into_slice(&mut dst);
+        assert_eq!(MetaAccount::unpack_from_slice(&dst), Ok(meta));
+
         // nomal mint
         let meta = MetaAccount {
             collection: Pubkey::from_str("3u8SXMVLiceaDFdSR3iaig2WLdmRQPYNs6Xb2KkmMvXF").unwrap(),
@@ -435,6 +618,52 @@ mod tests {
         println!("unpack normal ret {:?}", unpack_ret);
     }
 
+    #[test]
+    fn test_nft_meta_v1_pack() {
+        let meta = MetaAccount {
+            collection: Pubkey::new_unique(),
+            author: Pubkey::new_unique(),
+            proposal: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            state: AccountState::Initialized,
+            close_authority: Some(Pubkey::new_unique()),
+            nft_id: 7,
+            mint_timestamp: 1,
+            nft_uri: "www.baidu.com".to_string(),
+            delegate: None,
+        };
+        // an nft minted before delegates, 378 bytes long
+        let mut dst = [0 as u8; MAX_META_DATA_SIZE_V1];
+        assert_eq!(dst.len(), 378);
+        let mut full_dst = [0 as u8; MAX_META_DATA_SIZE];
+        meta.pack_into_slice(&mut full_dst);
+        dst.copy_from_slice(&full_dst[..MAX_META_DATA_SIZE_V1]);
+        assert_eq!(MetaAccount::unpack(&dst), Ok(meta.clone()));
+
+        // it is written back in place while it has no delegate
+        let transferred = MetaAccount {
+            owner: Pubkey::new_unique(),
+            ..meta.clone()
+        };
+        MetaAccount::pack(transferred.clone(), &mut dst).unwrap();
+        assert_eq!(MetaAccount::unpack(&dst), Ok(transferred.clone()));
+
+        // a delegate needs the larger account
+        let delegated = MetaAccount {
+            delegate: Some(Pubkey::new_unique()),
+            ..transferred
+        };
+        assert_eq!(
+            MetaAccount::pack(delegated, &mut dst),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        assert_eq!(
+            MetaAccount::unpack(&full_dst[..MAX_META_DATA_SIZE_V1 - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
     #[test]
     fn test_mint_pack() {
         // empty mint
@@ -447,6 +676,8 @@ mod tests {
             symbol: "".to_string(),
             freeze_authority: None,
             icon_uri: "".to_string(),
+            seller_fee_basis_points: 0,
+            creators: vec![],
         };
         let mut dst = [0 as u8; MINT_SIZE];
         mint.pack_into_slice(&mut dst);
@@ -466,6 +697,19 @@ mod tests {
             symbol: "usdt".to_string(),
             freeze_authority: None,
             icon_uri: "www.baidu.com".to_string(),
+            seller_fee_basis_points: 500,
+            creators: vec![
+                Creator {
+                    address: Pubkey::new_unique(),
+                    verified: true,
+                    share: 70,
+                },
+                Creator {
+                    address: Pubkey::new_unique(),
+                    verified: false,
+                    share: 30,
+                },
+            ],
         };
         let mut dst = [0 as u8; MINT_SIZE];
         mint.pack_into_slice(&mut dst);
@@ -473,6 +717,7 @@ mod tests {
         // unpack test
         let unpack_ret = NftMint::unpack_from_slice(&dst);
         println!("unpack normal ret {:?}", unpack_ret);
+        assert_eq!(unpack_ret, Ok(mint));
 
         // name full mint
         let mint = NftMint {
@@ -486,10 +731,80 @@ mod tests {
             symbol: "usdt".to_string(),
             freeze_authority: None,
             icon_uri: "www.baidu.com".to_string(),
+            seller_fee_basis_points: 0,
+            creators: vec![],
         };
         let mut dst = [0 as u8; MINT_SIZE];
         let result = panic::catch_unwind(move || mint.pack_into_slice(&mut dst));
-        assert_eq!(true, result.is_err())
+        assert_eq!(true, result.is_err());
+
+        // too many creators
+        let mint = NftMint {
+            is_initialized: true,
+            creators: vec![Creator::default(); MAX_CREATORS + 1],
+            ..NftMint::default()
+        };
+        let mut dst = [0 as u8; MINT_SIZE];
+        let result = panic::catch_unwind(move || mint.pack_into_slice(&mut dst));
+        assert_eq!(true, result.is_err());
+
+        // invalid number of creators
+        let mut dst = [0 as u8; MINT_SIZE];
+        dst[MINT_SIZE - CREATORS_SIZE] = MAX_CREATORS as u8 + 1;
+        assert_eq!(
+            NftMint::unpack_from_slice(&dst),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_mint_v1_pack() {
+        let mint = NftMint {
+            mint_authority: Pubkey::new_unique(),
+            supply: 2,
+            total_supply: 10,
+            is_initialized: true,
+            name: "nftt mint 12345".to_string(),
+            symbol: "usdt".to_string(),
+            freeze_authority: Some(Pubkey::new_unique()),
+            icon_uri: "www.baidu.com".to_string(),
+            seller_fee_basis_points: 0,
+            creators: vec![],
+        };
+        let mut dst = [0 as u8; MINT_SIZE_V1];
+        NftMint::pack(mint.clone(), &mut dst).unwrap();
+        // a collection created before royalties reads as having none
+        assert_eq!(NftMint::unpack(&dst), Ok(mint.clone()));
+        let mut full_dst = [0 as u8; MINT_SIZE];
+        NftMint::pack(mint.clone(), &mut full_dst).unwrap();
+        assert_eq!(&full_dst[..MINT_SIZE_V1], &dst[..]);
+
+        // royalties do not fit in a collection created before them
+        let royalty_mint = NftMint {
+            seller_fee_basis_points: 500,
+            ..mint.clone()
+        };
+        assert_eq!(
+            NftMint::pack(royalty_mint, &mut dst),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let creator_mint = NftMint {
+            creators: vec![Creator {
+                address: Pubkey::new_unique(),
+                verified: false,
+                share: 100,
+            }],
+            ..mint.clone()
+        };
+        assert_eq!(
+            NftMint::pack(creator_mint, &mut dst),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        assert_eq!(
+            NftMint::unpack(&full_dst[..MINT_SIZE_V1 + 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
     }
 
     #[test]
@@ -0,0 +1,111 @@
+// Mark this test as BPF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-bpf")]
+
+use hpl_nft::{
+    id,
+    instruction::create_approve_instruction,
+    processor::Processor,
+    state::{AccountState, MetaAccount, MAX_META_DATA_SIZE, MAX_META_DATA_SIZE_V1},
+};
+use huione_program::{program_pack::Pack, pubkey::Pubkey, rent::Rent, system_program};
+use huione_program_test::*;
+use huione_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+#[tokio::test]
+async fn test_approve_grows_v1_nft() {
+    let nft = Pubkey::new_unique();
+    let owner = Keypair::new();
+    let owner_lamports = 1_000_000_000;
+    let meta = MetaAccount {
+        collection: Pubkey::new_unique(),
+        author: Pubkey::new_unique(),
+        proposal: Pubkey::new_unique(),
+        owner: owner.pubkey(),
+        state: AccountState::Initialized,
+        close_authority: None,
+        nft_id: 7,
+        mint_timestamp: 1_650_000_000,
+        nft_uri: "https://example.com/7.json".to_string(),
+        delegate: None,
+    };
+
+    // An nft minted before delegates, with the shorter layout
+    let mut program_test = ProgramTest::new("hpl_nft", id(), processor!(Processor::process));
+    let mut data = vec![0; MAX_META_DATA_SIZE_V1];
+    MetaAccount::pack(meta.clone(), &mut data).unwrap();
+    program_test.add_account(
+        nft,
+        Account {
+            lamports: Rent::default().minimum_balance(MAX_META_DATA_SIZE_V1),
+            data,
+            owner: id(),
+            ..Account::default()
+        },
+    );
+    program_test.add_account(
+        owner.pubkey(),
+        Account {
+            lamports: owner_lamports,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+
+    // Approving a delegate makes room for it, the owner paying the extra rent
+    let delegate = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(
+        &[create_approve_instruction(nft, delegate, owner.pubkey(), id()).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &owner], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let nft_account = banks_client.get_account(nft).await.unwrap().unwrap();
+    assert_eq!(nft_account.data.len(), MAX_META_DATA_SIZE);
+    assert_eq!(MAX_META_DATA_SIZE, 411);
+    assert_eq!(
+        nft_account.lamports,
+        rent.minimum_balance(MAX_META_DATA_SIZE)
+    );
+    assert_eq!(
+        banks_client.get_balance(owner.pubkey()).await.unwrap(),
+        owner_lamports
+            - (rent.minimum_balance(MAX_META_DATA_SIZE)
+                - rent.minimum_balance(MAX_META_DATA_SIZE_V1))
+    );
+    assert_eq!(
+        MetaAccount::unpack(&nft_account.data).unwrap(),
+        MetaAccount {
+            delegate: Some(delegate),
+            ..meta.clone()
+        }
+    );
+
+    // The grown nft takes another delegate without paying again
+    let other_delegate = Pubkey::new_unique();
+    let recent_blockhash = banks_client
+        .get_new_blockhash(&recent_blockhash)
+        .await
+        .unwrap()
+        .0;
+    let mut transaction = Transaction::new_with_payer(
+        &[create_approve_instruction(nft, other_delegate, owner.pubkey(), id()).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &owner], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let regrown_account = banks_client.get_account(nft).await.unwrap().unwrap();
+    assert_eq!(regrown_account.data.len(), MAX_META_DATA_SIZE);
+    assert_eq!(regrown_account.lamports, nft_account.lamports);
+    assert_eq!(
+        MetaAccount::unpack(&regrown_account.data).unwrap().delegate,
+        Some(other_delegate)
+    );
+}
@@ -1,8 +1,10 @@
+use crate::nonce_pool::NoncePool;
 use clap::ArgMatches;
 use huione_clap_utils::{
     input_parsers::pubkey_of_signer,
     keypair::{pubkey_from_path, signer_from_path_with_config, SignerFromPathConfig},
 };
+use huione_cli_output::OutputFormat;
 use huione_client::{blockhash_query::BlockhashQuery, rpc_client::RpcClient};
 use huione_remote_wallet::remote_wallet::RemoteWalletManager;
 use huione_sdk::{pubkey::Pubkey, signature::Signer};
@@ -11,11 +13,15 @@ use std::{process::exit, sync::Arc};
 
 pub(crate) struct Config<'a> {
     pub(crate) rpc_client: RpcClient,
+    pub(crate) websocket_url: String,
     pub(crate) verbose: bool,
+    pub(crate) output_format: OutputFormat,
     pub(crate) fee_payer: Pubkey,
     pub(crate) default_keypair_path: String,
     pub(crate) nonce_account: Option<Pubkey>,
     pub(crate) nonce_authority: Option<Pubkey>,
+    /// Nonce accounts used in turn by the transactions of bulk commands
+    pub(crate) nonce_pool: Option<NoncePool>,
     pub(crate) blockhash_query: BlockhashQuery,
     pub(crate) sign_only: bool,
     pub(crate) multisigner_pubkeys: Vec<&'a Pubkey>,
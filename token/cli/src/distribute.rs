@@ -0,0 +1,553 @@
+use huione_clap_utils::input_validators::is_amount;
+use huione_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// A row of the distribution file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Payment {
+    /// Line number of the row in the distribution file, which identifies the
+    /// payment in the ledger
+    pub line: usize,
+    pub recipient: Pubkey,
+    /// Raw token amount
+    pub amount: u128,
+}
+
+/// Reads the `recipient,amount` rows of a distribution file. Amounts are in
+/// tokens and converted using the mint's `decimals`.
+///
+/// Rows are kept as is: paying the same recipient twice takes two rows.
+pub fn read_payments<P: AsRef<Path>>(path: P, decimals: u8) -> Result<Vec<Payment>, Error> {
+    let path = path.as_ref();
+    let mut payments = vec![];
+    for (index, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || (line_number == 1 && line.starts_with("recipient")) {
+            continue;
+        }
+        let row_error = |err: String| format!("{}:{}: {}", path.display(), line_number, err);
+        let mut columns = line.split(',').map(str::trim);
+        let (recipient, amount) = match (columns.next(), columns.next(), columns.next()) {
+            (Some(recipient), Some(amount), None) => (recipient, amount),
+            _ => return Err(row_error("expected `recipient,amount`".to_string()).into()),
+        };
+        let recipient = Pubkey::from_str(recipient).map_err(|err| row_error(err.to_string()))?;
+        is_amount(amount.to_string()).map_err(row_error)?;
+        let (inte, frac) = crate::parse_amount(amount);
+        let amount = hpl_token::ui_amount_to_amount(inte, frac, decimals);
+        if amount == 0 {
+            return Err(row_error("amount must be greater than zero".to_string()).into());
+        }
+        payments.push(Payment {
+            line: line_number,
+            recipient,
+            amount,
+        });
+    }
+    Ok(payments)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BatchStatus {
+    /// Signed and about to be sent; whether it landed is unknown
+    Pending,
+    Confirmed,
+    /// Failed or expired without landing, so its payments are still due
+    Failed,
+}
+
+impl BatchStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BatchStatus::Pending => "pending",
+            BatchStatus::Confirmed => "confirmed",
+            BatchStatus::Failed => "failed",
+        }
+    }
+
+    fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(BatchStatus::Pending),
+            "confirmed" => Some(BatchStatus::Confirmed),
+            "failed" => Some(BatchStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A transaction of the distribution, as recorded in the ledger
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub signature: Signature,
+    /// Recent blockhash of the transaction, or the nonce it was signed with
+    pub blockhash: Hash,
+    /// Nonce account of a transaction using a durable nonce
+    pub nonce_account: Option<Pubkey>,
+    pub payments: Vec<Payment>,
+    pub status: BatchStatus,
+}
+
+/// Append-only record of the transactions sent by a distribution.
+///
+/// A batch is written as pending before its transaction is sent, and as
+/// confirmed or failed once its outcome is known. Each line is a JSON object
+/// flushed to disk before moving on, so a crashed run can always tell which
+/// payments may have landed.
+pub struct Ledger {
+    path: PathBuf,
+    batches: Vec<Batch>,
+}
+
+impl Ledger {
+    /// Default ledger path of a distribution file
+    pub fn default_path<P: AsRef<Path>>(input: P) -> PathBuf {
+        let mut path = input.as_ref().as_os_str().to_owned();
+        path.push(".ledger");
+        path.into()
+    }
+
+    /// Opens the ledger at `path`, replaying its records if it exists
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let mut batches: Vec<Batch> = vec![];
+        let mut positions = HashMap::new();
+        if path.exists() {
+            for (index, line) in Self::read_records(&path)?.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record_error = |err: &str| format!("{}:{}: {}", path.display(), index + 1, err);
+                let value: Value =
+                    serde_json::from_str(line).map_err(|err| record_error(&err.to_string()))?;
+                let string = |name: &str| {
+                    value[name]
+                        .as_str()
+                        .ok_or_else(|| record_error(&format!("missing field `{}`", name)))
+                };
+                let signature = Signature::from_str(string("signature")?)
+                    .map_err(|err| record_error(&err.to_string()))?;
+                let status = BatchStatus::parse(string("status")?)
+                    .ok_or_else(|| record_error("invalid status"))?;
+
+                if status == BatchStatus::Pending {
+                    let blockhash = Hash::from_str(string("blockhash")?)
+                        .map_err(|err| record_error(&format!("{:?}", err)))?;
+                    let nonce_account = match value.get("nonceAccount") {
+                        None => None,
+                        Some(nonce_account) => Some(
+                            nonce_account
+                                .as_str()
+                                .and_then(|nonce_account| Pubkey::from_str(nonce_account).ok())
+                                .ok_or_else(|| record_error("invalid nonce account"))?,
+                        ),
+                    };
+                    let mut payments = vec![];
+                    for payment in value["payments"]
+                        .as_array()
+                        .ok_or_else(|| record_error("missing field `payments`"))?
+                    {
+                        payments.push(Payment {
+                            line: payment["line"]
+                                .as_u64()
+                                .ok_or_else(|| record_error("invalid payment line"))?
+                                as usize,
+                            recipient: payment["recipient"]
+                                .as_str()
+                                .and_then(|recipient| Pubkey::from_str(recipient).ok())
+                                .ok_or_else(|| record_error("invalid payment recipient"))?,
+                            amount: payment["amount"]
+                                .as_str()
+                                .and_then(|amount| amount.parse().ok())
+                                .ok_or_else(|| record_error("invalid payment amount"))?,
+                        });
+                    }
+                    positions.insert(signature, batches.len());
+                    batches.push(Batch {
+                        signature,
+                        blockhash,
+                        nonce_account,
+                        payments,
+                        status,
+                    });
+                } else {
+                    let position = positions
+                        .get(&signature)
+                        .ok_or_else(|| record_error("outcome of an unknown transaction"))?;
+                    batches[*position].status = status;
+                }
+            }
+        }
+        Ok(Self { path, batches })
+    }
+
+    /// Reads the records of the ledger at `path`.
+    ///
+    /// A run that crashed while appending can leave the last record cut short.
+    /// Its transaction was never sent, or its outcome is still unknown, so the
+    /// partial record is dropped from the file before appending anything else.
+    fn read_records(path: &Path) -> Result<String, Error> {
+        let mut records = fs::read_to_string(path)?;
+        if records.is_empty() || records.ends_with('\n') {
+            return Ok(records);
+        }
+        let last_start = records.rfind('\n').map_or(0, |position| position + 1);
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        if serde_json::from_str::<Value>(&records[last_start..]).is_ok() {
+            records.push('\n');
+            file.seek(SeekFrom::End(0))?;
+            file.write_all(b"\n")?;
+        } else {
+            records.truncate(last_start);
+            file.set_len(last_start as u64)?;
+        }
+        file.sync_all()?;
+        Ok(records)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Batches whose outcome is unknown
+    pub fn pending(&self) -> Vec<Batch> {
+        self.batches
+            .iter()
+            .filter(|batch| batch.status == BatchStatus::Pending)
+            .cloned()
+            .collect()
+    }
+
+    /// Lines of the distribution file paid by a confirmed transaction
+    pub fn paid(&self) -> HashSet<usize> {
+        self.batches
+            .iter()
+            .filter(|batch| batch.status == BatchStatus::Confirmed)
+            .flat_map(|batch| batch.payments.iter().map(|payment| payment.line))
+            .collect()
+    }
+
+    /// Checks that every payment recorded in the ledger is still the same row
+    /// of the distribution file, so that an edited file can't be resumed
+    pub fn check(&self, payments: &[Payment]) -> Result<(), Error> {
+        let rows: HashMap<usize, &Payment> = payments
+            .iter()
+            .map(|payment| (payment.line, payment))
+            .collect();
+        for payment in self.batches.iter().flat_map(|batch| &batch.payments) {
+            if rows.get(&payment.line) != Some(&payment) {
+                return Err(format!(
+                    "Line {} of the distribution file no longer matches {}. \
+                     Use a new ledger to start a different distribution",
+                    payment.line,
+                    self.path.display()
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Records a signed transaction before it is sent
+    pub fn record_pending(
+        &mut self,
+        signature: Signature,
+        blockhash: Hash,
+        nonce_account: Option<Pubkey>,
+        payments: Vec<Payment>,
+    ) -> Result<(), Error> {
+        let mut record = json!({
+            "status": BatchStatus::Pending.as_str(),
+            "signature": signature.to_string(),
+            "blockhash": blockhash.to_string(),
+            "payments": payments
+                .iter()
+                .map(|payment| json!({
+                    "line": payment.line,
+                    "recipient": payment.recipient.to_string(),
+                    "amount": payment.amount.to_string(),
+                }))
+                .collect::<Vec<_>>(),
+        });
+        if let Some(nonce_account) = nonce_account {
+            record["nonceAccount"] = json!(nonce_account.to_string());
+        }
+        self.append(record)?;
+        self.batches.push(Batch {
+            signature,
+            blockhash,
+            nonce_account,
+            payments,
+            status: BatchStatus::Pending,
+        });
+        Ok(())
+    }
+
+    /// Records the outcome of a pending transaction
+    pub fn record_outcome(
+        &mut self,
+        signature: &Signature,
+        status: BatchStatus,
+    ) -> Result<(), Error> {
+        self.append(json!({
+            "status": status.as_str(),
+            "signature": signature.to_string(),
+        }))?;
+        for batch in self.batches.iter_mut() {
+            if batch.signature == *signature {
+                batch.status = status;
+            }
+        }
+        Ok(())
+    }
+
+    fn append(&self, record: Value) -> Result<(), Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", record)?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payments(count: usize) -> Vec<Payment> {
+        (1..=count)
+            .map(|line| Payment {
+                line,
+                recipient: Pubkey::new_unique(),
+                amount: line as u128 * 100,
+            })
+            .collect()
+    }
+
+    fn signature(seed: u8) -> Signature {
+        Signature::new(&[seed; 64])
+    }
+
+    #[test]
+    fn test_ledger_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger");
+        let payments = payments(5);
+        let nonce_account = Pubkey::new_unique();
+        let blockhash = Hash::new(&[7; 32]);
+
+        let mut ledger = Ledger::open(&path).unwrap();
+        assert!(ledger.pending().is_empty());
+        assert!(ledger.paid().is_empty());
+        ledger
+            .record_pending(signature(1), blockhash, None, payments[0..2].to_vec())
+            .unwrap();
+        ledger
+            .record_pending(
+                signature(2),
+                blockhash,
+                Some(nonce_account),
+                payments[2..4].to_vec(),
+            )
+            .unwrap();
+        ledger
+            .record_pending(signature(3), blockhash, None, payments[4..].to_vec())
+            .unwrap();
+        ledger
+            .record_outcome(&signature(1), BatchStatus::Confirmed)
+            .unwrap();
+        ledger
+            .record_outcome(&signature(3), BatchStatus::Failed)
+            .unwrap();
+
+        let ledger = Ledger::open(&path).unwrap();
+        let pending = ledger.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].signature, signature(2));
+        assert_eq!(pending[0].blockhash, blockhash);
+        assert_eq!(pending[0].nonce_account, Some(nonce_account));
+        assert_eq!(pending[0].payments, payments[2..4].to_vec());
+        assert_eq!(
+            ledger.paid(),
+            [1, 2].iter().cloned().collect::<HashSet<_>>()
+        );
+        assert!(ledger.check(&payments).is_ok());
+    }
+
+    #[test]
+    fn test_ledger_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger");
+        let payments = payments(3);
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_pending(signature(1), Hash::default(), None, payments[0..2].to_vec())
+            .unwrap();
+        assert!(ledger.check(&payments).is_ok());
+
+        // rows the ledger does not mention may change
+        let mut edited = payments.clone();
+        edited[2].amount += 1;
+        assert!(ledger.check(&edited).is_ok());
+
+        let mut edited = payments.clone();
+        edited[1].amount += 1;
+        assert!(ledger.check(&edited).is_err());
+        let mut edited = payments.clone();
+        edited[0].recipient = Pubkey::new_unique();
+        assert!(ledger.check(&edited).is_err());
+        assert!(ledger.check(&payments[1..]).is_err());
+    }
+
+    #[test]
+    fn test_ledger_partial_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger");
+        let payments = payments(4);
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_pending(signature(1), Hash::default(), None, payments[0..2].to_vec())
+            .unwrap();
+        let complete = fs::read_to_string(&path).unwrap();
+
+        // a pending record cut short was never sent
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"status\":\"pending\",\"signa").unwrap();
+        let mut ledger = Ledger::open(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), complete);
+        assert_eq!(ledger.pending().len(), 1);
+
+        // an outcome cut short leaves the transaction pending
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"status\":\"confirmed\"").unwrap();
+        let ledger_reopened = Ledger::open(&path).unwrap();
+        assert_eq!(ledger_reopened.pending().len(), 1);
+        assert!(ledger_reopened.paid().is_empty());
+
+        // a complete record missing its line break is kept
+        ledger
+            .record_outcome(&signature(1), BatchStatus::Confirmed)
+            .unwrap();
+        let records = fs::read_to_string(&path).unwrap();
+        fs::write(&path, records.trim_end()).unwrap();
+        let mut ledger = Ledger::open(&path).unwrap();
+        assert_eq!(
+            ledger.paid(),
+            [1, 2].iter().cloned().collect::<HashSet<_>>()
+        );
+        ledger
+            .record_pending(signature(2), Hash::default(), None, payments[2..].to_vec())
+            .unwrap();
+        let ledger = Ledger::open(&path).unwrap();
+        assert_eq!(
+            ledger.paid(),
+            [1, 2].iter().cloned().collect::<HashSet<_>>()
+        );
+        assert_eq!(ledger.pending().len(), 1);
+    }
+
+    #[test]
+    fn test_ledger_corrupt_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger");
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_pending(signature(1), Hash::default(), None, payments(1))
+            .unwrap();
+        let records = fs::read_to_string(&path).unwrap();
+
+        // complete records are never dropped
+        fs::write(&path, format!("not json\n{}", records)).unwrap();
+        assert!(Ledger::open(&path).is_err());
+        fs::write(&path, format!("{}{{\"status\":\"sent\"}}\n", records)).unwrap();
+        assert!(Ledger::open(&path).is_err());
+
+        // outcome of a transaction the ledger never recorded
+        fs::write(
+            &path,
+            format!(
+                "{}{}\n",
+                records,
+                json!({
+                    "status": "confirmed",
+                    "signature": signature(2).to_string(),
+                })
+            ),
+        )
+        .unwrap();
+        assert!(Ledger::open(&path).is_err());
+    }
+
+    #[test]
+    fn test_ledger_resume_after_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger");
+        let payments = payments(4);
+
+        // the first batch lands, then the run crashes after sending the second
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_pending(signature(1), Hash::default(), None, payments[0..2].to_vec())
+            .unwrap();
+        ledger
+            .record_outcome(&signature(1), BatchStatus::Confirmed)
+            .unwrap();
+        ledger
+            .record_pending(signature(2), Hash::default(), None, payments[2..].to_vec())
+            .unwrap();
+        drop(ledger);
+
+        // the resumed run settles the pending batch, which had landed
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger.check(&payments).unwrap();
+        let pending = ledger.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].signature, signature(2));
+        ledger
+            .record_outcome(&pending[0].signature, BatchStatus::Confirmed)
+            .unwrap();
+        let paid = ledger.paid();
+        assert!(payments.iter().all(|payment| paid.contains(&payment.line)));
+
+        // nothing is due on the next run
+        let ledger = Ledger::open(&path).unwrap();
+        assert!(ledger.pending().is_empty());
+        let paid = ledger.paid();
+        assert!(payments.iter().all(|payment| paid.contains(&payment.line)));
+
+        // had it failed instead, only its payments would be due again
+        let path = dir.path().join("failed-ledger");
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_pending(signature(1), Hash::default(), None, payments[0..2].to_vec())
+            .unwrap();
+        ledger
+            .record_outcome(&signature(1), BatchStatus::Confirmed)
+            .unwrap();
+        ledger
+            .record_pending(signature(2), Hash::default(), None, payments[2..].to_vec())
+            .unwrap();
+        let mut ledger = Ledger::open(&path).unwrap();
+        ledger
+            .record_outcome(&signature(2), BatchStatus::Failed)
+            .unwrap();
+        let paid = ledger.paid();
+        let due: Vec<_> = payments
+            .iter()
+            .filter(|payment| !paid.contains(&payment.line))
+            .cloned()
+            .collect();
+        assert_eq!(due, payments[2..].to_vec());
+    }
+}
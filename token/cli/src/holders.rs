@@ -0,0 +1,59 @@
+use huione_sdk::pubkey::Pubkey;
+
+/// Shares of the holders compared in the percentile buckets, in percent
+pub const PERCENTILES: [u8; 5] = [1, 5, 10, 25, 50];
+
+/// Balances of the holders of a mint, largest first
+pub struct Distribution {
+    pub holders: Vec<(Pubkey, u128)>,
+    pub total: u128,
+}
+
+impl Distribution {
+    /// Ranks holders by balance, ties ordered by owner address
+    pub fn new(mut holders: Vec<(Pubkey, u128)>) -> Self {
+        holders.sort_by(|(a_owner, a_amount), (b_owner, b_amount)| {
+            b_amount.cmp(a_amount).then(a_owner.cmp(b_owner))
+        });
+        let total = holders.iter().map(|(_, amount)| amount).sum();
+        Self { holders, total }
+    }
+
+    /// Share of the total held by `amount`, in percent
+    pub fn share(&self, amount: u128) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            amount as f64 * 100.0 / self.total as f64
+        }
+    }
+
+    /// Gini coefficient of the balances: 0 when every holder holds the same
+    /// amount, approaching 1 as a single holder holds everything
+    pub fn gini(&self) -> f64 {
+        let n = self.holders.len() as f64;
+        if self.total == 0 {
+            return 0.0;
+        }
+        // With balances in ascending order x_1..x_n,
+        // G = 2 * sum(i * x_i) / (n * sum(x_i)) - (n + 1) / n
+        let weighted: f64 = self
+            .holders
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, (_, amount))| (i + 1) as f64 * *amount as f64)
+            .sum();
+        2.0 * weighted / (n * self.total as f64) - (n + 1.0) / n
+    }
+
+    /// Number of holders in the top `percentile` percent and their combined
+    /// balance. A bucket always counts at least one holder.
+    pub fn bucket(&self, percentile: u8) -> (usize, u128) {
+        let holders = self.holders.len();
+        let count = (holders * percentile as usize + 99) / 100;
+        let count = count.max(1).min(holders);
+        let amount = self.holders[..count].iter().map(|(_, amount)| amount).sum();
+        (count, amount)
+    }
+}
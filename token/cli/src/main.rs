@@ -1,6 +1,6 @@
 use clap::{
-    crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, AppSettings, Arg,
-    ArgMatches, SubCommand,
+    crate_description, crate_name, crate_version, value_t, value_t_or_exit, values_t, App,
+    AppSettings, Arg, ArgMatches, SubCommand,
 };
 use console::Emoji;
 use hpl_associated_token_account::*;
@@ -8,11 +8,14 @@ use hpl_token::{
     self,
     instruction::*,
     native_mint,
-    state::{Account, Mint, MintMeta, Multisig},
+    state::{
+        Account, Mint, MintMeta, Multisig, MultisigV2, PendingAuthority, TokenGroup,
+        TokenGroupMember,
+    },
 };
 use huione_account_decoder::{
     parse_token::{TokenAccountType, UiAccountState},
-    UiAccountData,
+    UiAccountData, UiAccountEncoding,
 };
 use huione_clap_utils::{
     fee_payer::fee_payer_arg,
@@ -26,33 +29,88 @@ use huione_clap_utils::{
     offline::{self, *},
     ArgConstant,
 };
-use huione_cli_output::{display::println_name_value, return_signers, OutputFormat};
+use huione_cli_output::{
+    return_signers_data, CliSignature, OutputFormat, QuietDisplay, ReturnSignersConfig,
+    VerboseDisplay,
+};
 use huione_client::{
-    blockhash_query::BlockhashQuery, rpc_client::RpcClient, rpc_request::TokenAccountsFilter,
+    blockhash_query::{self, BlockhashQuery},
+    nonce_utils,
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::{RpcRequest, TokenAccountsFilter},
+    rpc_response::{Response, RpcConfirmedTransactionStatusWithSignature, RpcKeyedAccount},
 };
 use huione_remote_wallet::remote_wallet::RemoteWalletManager;
 use huione_sdk::{
+    account::Account as HuiOneAccount,
+    clock::Slot,
     commitment_config::CommitmentConfig,
+    fee_calculator::FeeCalculator,
+    hash::Hash,
     instruction::Instruction,
     message::Message,
     native_token::*,
+    nonce::State as NonceState,
+    packet::PACKET_DATA_SIZE,
     program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::{Keypair, Signature, Signer},
     system_instruction, system_program,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
+};
+use huione_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::exit,
+    str::FromStr,
+    sync::Arc,
 };
-use std::{collections::HashMap, process::exit, str::FromStr, sync::Arc};
 
 mod config;
 use config::Config;
 
+mod distribute;
+use distribute::{BatchStatus, Ledger};
+
+mod nonce_pool;
+use nonce_pool::NoncePool;
+
+mod output;
+use output::*;
+
+mod holders;
+use holders::Distribution;
+
+mod snapshot;
+use snapshot::Snapshot;
+
+mod token_spec;
+use token_spec::TokenSpec;
+
+mod transaction_file;
+use transaction_file::TransactionFile;
+
+mod watch;
+use watch::BalanceAlert;
+
 mod sort;
 use sort::sort_and_parse_token_accounts;
 
 static WARNING: Emoji = Emoji("⚠️", "!");
 
+/// Recipients of the initial supply minted by each transaction of `create-token`
+const MINT_RECIPIENTS_PER_TRANSACTION: usize = 5;
+
+/// Times a mint and its token accounts are read before giving up on a
+/// consistent view
+const CONSISTENT_READ_ATTEMPTS: usize = 5;
+
 pub const OWNER_ADDRESS_ARG: ArgConstant<'static> = ArgConstant {
     name: "owner",
     long: "owner",
@@ -167,16 +225,103 @@ pub fn multisig_signer_arg<'a, 'b>() -> Arg<'a, 'b> {
         .takes_value(true)
         .multiple(true)
         .min_values(0u64)
-        .max_values(MAX_SIGNERS as u64)
+        .max_values(MAX_SIGNERS_V2 as u64)
         .help(MULTISIG_SIGNER_ARG.help)
 }
 
+pub fn output_format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("output_format")
+        .long("output")
+        .value_name("FORMAT")
+        .takes_value(true)
+        .possible_values(&["json", "json-compact"])
+        .help(
+            "Return information in specified output format. A command prints one \
+             document for its result and one for each transaction it sends or signs; \
+             json-compact prints each document on a line of its own",
+        )
+}
+
+pub trait WatchArgs {
+    fn watch_args(self) -> Self;
+}
+
+impl WatchArgs for App<'_, '_> {
+    fn watch_args(self) -> Self {
+        self.arg(
+            Arg::with_name("watch")
+                .long("watch")
+                .takes_value(false)
+                .help("Keep running and show the balances again whenever they change"),
+        )
+        .arg(
+            Arg::with_name("alert_below")
+                .long("alert-below")
+                .validator(is_amount)
+                .value_name("TOKEN_AMOUNT")
+                .takes_value(true)
+                .requires("alert_command")
+                .help("Run the alert command when a balance falls below this amount"),
+        )
+        .arg(
+            Arg::with_name("alert_command")
+                .long("alert-command")
+                .value_name("COMMAND")
+                .takes_value(true)
+                .requires("alert_below")
+                .help(
+                    "Shell command to run when a balance falls below the alert threshold. \
+                     It gets HPL_TOKEN_ACCOUNT, HPL_TOKEN_MINT, HPL_TOKEN_BALANCE and \
+                     HPL_TOKEN_THRESHOLD in its environment",
+                ),
+        )
+    }
+}
+
+pub trait NoncePoolArgs {
+    fn nonce_pool_args(self) -> Self;
+}
+
+impl NoncePoolArgs for App<'_, '_> {
+    fn nonce_pool_args(self) -> Self {
+        self.arg(
+            Arg::with_name("nonce_pool")
+                .long("nonce-pool")
+                .value_name("PATH")
+                .takes_value(true)
+                .conflicts_with_all(&[NONCE_ARG.name, BLOCKHASH_ARG.name])
+                .help(
+                    "Use a durable nonce from this pool for each transaction. \
+                     Create a pool with `hpl-token nonce-pool create`",
+                ),
+        )
+        .arg(
+            Arg::with_name("nonce_pool_authority")
+                .long("nonce-pool-authority")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .validator(is_valid_signer)
+                .requires("nonce_pool")
+                .help("Nonce authority of the pool. Defaults to the client keypair."),
+        )
+    }
+}
+
+fn balance_alert_of(matches: &ArgMatches<'_>) -> Option<BalanceAlert> {
+    matches.value_of("alert_below").map(|threshold| {
+        BalanceAlert::new(
+            threshold.to_string(),
+            matches.value_of("alert_command").unwrap().to_string(),
+        )
+    })
+}
+
 fn is_multisig_minimum_signers(string: String) -> Result<(), String> {
     let v = u8::from_str(&string).map_err(|e| e.to_string())? as usize;
     if v < MIN_SIGNERS {
         Err(format!("must be at least {}", MIN_SIGNERS))
-    } else if v > MAX_SIGNERS {
-        Err(format!("must be at most {}", MAX_SIGNERS))
+    } else if v > MAX_SIGNERS_V2 {
+        Err(format!("must be at most {}", MAX_SIGNERS_V2))
     } else {
         Ok(())
     }
@@ -207,6 +352,77 @@ fn get_signer(
     })
 }
 
+/// Builds the message of the `index`th transaction of a command, advancing
+/// the nonce account it uses, if any
+fn new_message(config: &Config, instructions: &[Instruction], index: usize) -> Message {
+    let nonce_account = match config.nonce_pool.as_ref() {
+        Some(nonce_pool) => Some(nonce_pool.account(index)),
+        None => config.nonce_account,
+    };
+    if let Some(nonce_account) = nonce_account {
+        Message::new_with_nonce(
+            instructions.to_vec(),
+            Some(&config.fee_payer),
+            &nonce_account,
+            config.nonce_authority.as_ref().unwrap(),
+        )
+    } else {
+        Message::new(instructions, Some(&config.fee_payer))
+    }
+}
+
+/// Returns the blockhash of the `index`th transaction of a command, which is
+/// the current nonce of its account when it uses the nonce pool
+fn get_blockhash_and_fee_calculator(
+    config: &Config,
+    index: usize,
+) -> Result<(Hash, FeeCalculator), Error> {
+    let commitment = config.rpc_client.commitment();
+    match config.nonce_pool.as_ref() {
+        Some(nonce_pool) => blockhash_query::Source::NonceAccount(nonce_pool.account(index))
+            .get_blockhash_and_fee_calculator(&config.rpc_client, commitment),
+        None => config
+            .blockhash_query
+            .get_blockhash_and_fee_calculator(&config.rpc_client, commitment),
+    }
+}
+
+/// Moves the nonce pool, if any, past the account of the `index`th transaction
+/// of a command once that transaction is signed
+fn record_nonce_signed(config: &Config, index: usize) -> Result<(), Error> {
+    match config.nonce_pool.as_ref() {
+        Some(nonce_pool) => nonce_pool.record_signed(index),
+        None => Ok(()),
+    }
+}
+
+/// Packs groups of instructions into as few transactions as fit in a packet,
+/// keeping the groups in order and each group within a single transaction
+fn pack_instruction_groups(
+    config: &Config,
+    instruction_groups: Vec<Vec<Instruction>>,
+) -> Vec<Vec<Instruction>> {
+    let fits = |instructions: &[Instruction]| {
+        let message = new_message(config, instructions, 0);
+        bincode::serialized_size(&Transaction::new_unsigned(message))
+            .map_or(false, |size| size <= PACKET_DATA_SIZE as u64)
+    };
+
+    let mut instruction_batches: Vec<Vec<Instruction>> = vec![];
+    for instructions in instruction_groups {
+        if let Some(batch) = instruction_batches.last_mut() {
+            let mut packed = batch.clone();
+            packed.extend(instructions.iter().cloned());
+            if fits(&packed) {
+                *batch = packed;
+                continue;
+            }
+        }
+        instruction_batches.push(instructions);
+    }
+    instruction_batches
+}
+
 fn check_fee_payer_balance(config: &Config, required_balance: u128) -> Result<(), Error> {
     let balance = config.rpc_client.get_balance(&config.fee_payer)?;
     if balance < required_balance {
@@ -277,14 +493,11 @@ pub fn parse_amount(amount:&str) -> (u128, f64){
 
 fn command_create_token(
     config: &Config,
-    decimals: u8,
     token: Pubkey,
-    authority: Pubkey,
-    enable_freeze: bool,
-    symbol: Option<String>,
-    name: Option<String>,
-    icon: Option<String>,
+    spec: TokenSpec,
+    setup_authority: Pubkey,
     memo: Option<String>,
+    dry_run: bool,
 ) -> CommandResult {
     let minimum_balance_for_rent_exemption = if !config.sign_only {
         config
@@ -302,32 +515,18 @@ fn command_create_token(
         0
     };
 
-    let freeze_authority_pubkey = if enable_freeze { Some(authority) } else { None };
-
-    let symbol = if let Some(symbol) = symbol {
-        symbol
-    } else {
-        "".to_string()
-    };
-
-    let name = if let Some(name) = name {
-        name
-    } else {
-        "".to_string()
-    };
-
-    let icon = if let Some(icon) = icon {
-        icon
-    } else {
-        "".to_string()
-    };
+    let minimum_balance_for_rent_exemption_account =
+        if !config.sign_only && !spec.recipients.is_empty() {
+            config
+                .rpc_client
+                .get_minimum_balance_for_rent_exemption(Account::LEN)?
+        } else {
+            0
+        };
 
     let (mint_info, _) =
         Pubkey::find_program_address(&[b"MintMeta", &token.to_bytes()], &hpl_token::id());
 
-    println!("Creating token {}", token);
-    println!("Creating token meta {}", mint_info);
-
     let mut instructions = vec![
         system_instruction::create_account(
             &config.fee_payer,
@@ -340,26 +539,155 @@ fn command_create_token(
         initialize_mint(
             &hpl_token::id(),
             &token,
-            &authority,
-            freeze_authority_pubkey.as_ref(),
-            decimals,
+            &setup_authority,
+            spec.freeze_authority.as_ref(),
+            spec.decimals,
         )?,
         initialize_mint_meta(
             &hpl_token::id(),
             &token,
             &mint_info,
-            symbol,
-            name,
-            icon,
+            spec.symbol.clone(),
+            spec.name.clone(),
+            spec.icon.clone(),
         )?,
     ];
     if let Some(text) = memo {
         instructions.push(hpl_memo::build_memo(text.as_bytes(), &[&config.fee_payer]));
     }
-    Ok(Some((
-        minimum_balance_for_rent_exemption + minimum_balance_for_rent_exemption_mint_info,
-        vec![instructions],
-    )))
+    let mut instruction_batches = vec![instructions];
+
+    // The initial supply, a few recipients per transaction
+    for recipients in spec.recipients.chunks(MINT_RECIPIENTS_PER_TRANSACTION) {
+        let mut instructions = vec![];
+        for recipient in recipients {
+            let address = get_associated_token_address(&recipient.owner, &token);
+            instructions.push(create_associated_token_account(
+                &config.fee_payer,
+                &recipient.owner,
+                &token,
+            ));
+            instructions.push(mint_to(
+                &hpl_token::id(),
+                &token,
+                &address,
+                &setup_authority,
+                &[],
+                recipient.amount,
+            )?);
+        }
+        instruction_batches.push(instructions);
+    }
+
+    // Hand the authorities over once the setup is done
+    let mut instructions = vec![];
+    if spec.meta_authority != setup_authority {
+        instructions.push(set_authority(
+            &hpl_token::id(),
+            &token,
+            Some(&spec.meta_authority),
+            AuthorityType::UpdateMeta,
+            &setup_authority,
+            &[],
+        )?);
+    }
+    if spec.mint_authority != Some(setup_authority) {
+        instructions.push(set_authority(
+            &hpl_token::id(),
+            &token,
+            spec.mint_authority.as_ref(),
+            AuthorityType::MintTokens,
+            &setup_authority,
+            &[],
+        )?);
+    }
+    if !instructions.is_empty() {
+        instruction_batches.push(instructions);
+    }
+
+    let rent = minimum_balance_for_rent_exemption
+        + minimum_balance_for_rent_exemption_mint_info
+        + minimum_balance_for_rent_exemption_account * spec.recipients.len() as u128;
+
+    if dry_run {
+        let (_, fee_calculator) = config
+            .blockhash_query
+            .get_blockhash_and_fee_calculator(&config.rpc_client, config.rpc_client.commitment())?;
+        let fees = instruction_batches
+            .iter()
+            .map(|instructions| {
+                fee_calculator.calculate_fee(&Message::new(instructions, Some(&config.fee_payer)))
+            })
+            .sum();
+
+        let mut accounts = vec![
+            CliPlannedAccount {
+                address: token.to_string(),
+                kind: "mint".to_string(),
+                owner: None,
+                amount: None,
+                rent: minimum_balance_for_rent_exemption,
+            },
+            CliPlannedAccount {
+                address: mint_info.to_string(),
+                kind: "mint meta".to_string(),
+                owner: None,
+                amount: None,
+                rent: minimum_balance_for_rent_exemption_mint_info,
+            },
+        ];
+        for recipient in &spec.recipients {
+            accounts.push(CliPlannedAccount {
+                address: get_associated_token_address(&recipient.owner, &token).to_string(),
+                kind: "token account".to_string(),
+                owner: Some(recipient.owner.to_string()),
+                amount: Some(hpl_token::amount_to_ui_amount(
+                    recipient.amount,
+                    spec.decimals,
+                )),
+                rent: minimum_balance_for_rent_exemption_account,
+            });
+        }
+
+        let plan = CliTokenPlan {
+            token: token.to_string(),
+            decimals: spec.decimals,
+            mint_authority: spec.mint_authority.map(|authority| authority.to_string()),
+            freeze_authority: spec.freeze_authority.map(|authority| authority.to_string()),
+            meta_authority: spec.meta_authority.to_string(),
+            symbol: spec.symbol,
+            name: spec.name,
+            icon: spec.icon,
+            supply: hpl_token::amount_to_ui_amount(
+                spec.recipients
+                    .iter()
+                    .map(|recipient| recipient.amount)
+                    .sum(),
+                spec.decimals,
+            ),
+            accounts,
+            transactions: instruction_batches.len(),
+            rent,
+            fees,
+        };
+        println!("{}", format_output(plan, "create-token", config));
+        return Ok(None);
+    }
+
+    println_display(config, format!("Creating token {}", token));
+    println_display(config, format!("Creating token meta {}", mint_info));
+    for recipient in &spec.recipients {
+        println_display(
+            config,
+            format!(
+                "Minting {} tokens to {}",
+                hpl_token::amount_to_ui_amount(recipient.amount, spec.decimals),
+                recipient.owner
+            ),
+        );
+    }
+
+    Ok(Some((rent, instruction_batches)))
 }
 
 fn command_create_account(
@@ -377,7 +705,7 @@ fn command_create_account(
     };
 
     let (account, system_account_ok, instructions) = if let Some(account) = maybe_account {
-        println!("Creating account {}", account);
+        println_display(config, format!("Creating account {}", account));
         (
             account,
             false,
@@ -394,7 +722,7 @@ fn command_create_account(
         )
     } else {
         let account = get_associated_token_address(&owner, &token);
-        println!("Creating account {}", account);
+        println_display(config, format!("Creating account {}", account));
         (
             account,
             true,
@@ -429,36 +757,57 @@ fn command_create_multisig(
     multisig: Pubkey,
     minimum_signers: u8,
     multisig_members: Vec<Pubkey>,
+    v2: bool,
 ) -> CommandResult {
-    println!(
-        "Creating {}/{} multisig {}",
-        minimum_signers,
-        multisig_members.len(),
-        multisig
+    let v2 = v2 || multisig_members.len() > MAX_SIGNERS;
+    println_display(
+        config,
+        format!(
+            "Creating {}/{} multisig{} {}",
+            minimum_signers,
+            multisig_members.len(),
+            if v2 { " v2" } else { "" },
+            multisig
+        ),
     );
 
+    let space = if v2 {
+        MultisigV2::get_packed_len(multisig_members.len())
+    } else {
+        Multisig::LEN
+    };
     let minimum_balance_for_rent_exemption = if !config.sign_only {
         config
             .rpc_client
-            .get_minimum_balance_for_rent_exemption(Multisig::LEN)?
+            .get_minimum_balance_for_rent_exemption(space)?
     } else {
         0
     };
 
+    let multisig_members = multisig_members.iter().collect::<Vec<_>>();
     let instructions = vec![
         system_instruction::create_account(
             &config.fee_payer,
             &multisig,
             minimum_balance_for_rent_exemption,
-            Multisig::LEN as u64,
+            space as u64,
             &hpl_token::id(),
         ),
-        initialize_multisig(
-            &hpl_token::id(),
-            &multisig,
-            multisig_members.iter().collect::<Vec<_>>().as_slice(),
-            minimum_signers,
-        )?,
+        if v2 {
+            initialize_multisig_v2(
+                &hpl_token::id(),
+                &multisig,
+                &multisig_members,
+                minimum_signers,
+            )?
+        } else {
+            initialize_multisig(
+                &hpl_token::id(),
+                &multisig,
+                &multisig_members,
+                minimum_signers,
+            )?
+        },
     ];
     Ok(Some((
         minimum_balance_for_rent_exemption,
@@ -473,6 +822,7 @@ fn command_authorize(
     authority: Pubkey,
     new_authority: Option<Pubkey>,
     force_authorize: bool,
+    two_step: bool,
 ) -> CommandResult {
     let auth_str = match authority_type {
         AuthorityType::MintTokens => "mint authority",
@@ -544,19 +894,55 @@ fn command_authorize(
     } else {
         COption::None
     };
-    println!(
-        "Updating {}\n  Current {}: {}\n  New {}: {}",
-        account,
-        auth_str,
-        previous_authority
-            .map(|pubkey| pubkey.to_string())
-            .unwrap_or_else(|| "disabled".to_string()),
-        auth_str,
-        new_authority
-            .map(|pubkey| pubkey.to_string())
-            .unwrap_or_else(|| "disabled".to_string())
+    println_display(
+        config,
+        format!(
+            "{} {}\n  Current {}: {}\n  New {}: {}",
+            if two_step { "Proposing" } else { "Updating" },
+            account,
+            auth_str,
+            previous_authority
+                .map(|pubkey| pubkey.to_string())
+                .unwrap_or_else(|| "disabled".to_string()),
+            auth_str,
+            new_authority
+                .map(|pubkey| pubkey.to_string())
+                .unwrap_or_else(|| "disabled".to_string())
+        ),
     );
 
+    if two_step {
+        let new_authority = new_authority
+            .ok_or("Error: a two-step authority transfer cannot disable the authority")?;
+        let minimum_balance_for_rent_exemption = if !config.sign_only {
+            config
+                .rpc_client
+                .get_minimum_balance_for_rent_exemption(PendingAuthority::LEN)?
+        } else {
+            0
+        };
+        println_display(
+            config,
+            format!(
+                "  The new {} must run `hpl-token accept-authority` to complete the transfer",
+                auth_str
+            ),
+        );
+        let instructions = vec![propose_authority(
+            &hpl_token::id(),
+            &config.fee_payer,
+            &account,
+            &new_authority,
+            authority_type,
+            &authority,
+            &config.multisigner_pubkeys,
+        )?];
+        return Ok(Some((
+            minimum_balance_for_rent_exemption,
+            vec![instructions],
+        )));
+    }
+
     let instructions = vec![set_authority(
         &hpl_token::id(),
         &account,
@@ -568,6 +954,66 @@ fn command_authorize(
     Ok(Some((0, vec![instructions])))
 }
 
+fn command_accept_authority(
+    config: &Config,
+    account: Pubkey,
+    authority_type: AuthorityType,
+    new_authority: Pubkey,
+) -> CommandResult {
+    // The rent of the pending authority goes back to whoever paid for the proposal
+    let mut payer = config.fee_payer;
+    if !config.sign_only {
+        let target = if authority_type == AuthorityType::UpdateMeta {
+            Pubkey::find_program_address(&[b"MintMeta", &account.to_bytes()], &hpl_token::id()).0
+        } else {
+            account
+        };
+        let pending_address = hpl_token::get_pending_authority_address(&target, &authority_type);
+        let pending = config
+            .rpc_client
+            .get_account(&pending_address)
+            .ok()
+            .and_then(|pending| PendingAuthority::unpack(&pending.data).ok())
+            .ok_or_else(|| format!("Error: no pending authority transfer for {}", account))?;
+        if pending.new_authority != new_authority {
+            return Err(format!(
+                "Error: pending authority of {} is {}, not {}",
+                account, pending.new_authority, new_authority
+            )
+            .into());
+        }
+        println_display(
+            config,
+            format!(
+                "Accepting {}\n  Current authority: {}\n  New authority: {}",
+                account, pending.current_authority, pending.new_authority
+            ),
+        );
+        payer = pending.payer;
+    }
+
+    let instructions = vec![accept_authority(
+        &hpl_token::id(),
+        &account,
+        &payer,
+        authority_type,
+        &new_authority,
+        &config.multisigner_pubkeys,
+    )?];
+    Ok(Some((0, vec![instructions])))
+}
+
+fn parse_authority_type(authority_type: &str) -> AuthorityType {
+    match authority_type {
+        "mint" => AuthorityType::MintTokens,
+        "freeze" => AuthorityType::FreezeAccount,
+        "owner" => AuthorityType::AccountOwner,
+        "close" => AuthorityType::CloseAccount,
+        "meta" => AuthorityType::UpdateMeta,
+        _ => unreachable!(),
+    }
+}
+
 fn resolve_mint_info(
     config: &Config,
     token_account: &Pubkey,
@@ -669,11 +1115,14 @@ fn command_transfer(
         })?;
 
         let transfer_balance = maybe_transfer_balance.unwrap_or(sender_balance);
-        println!(
-            "Transfer {} tokens\n  Sender: {}\n  Recipient: {}",
-            hpl_token::amount_to_ui_amount(transfer_balance, decimals),
-            sender,
-            recipient
+        println_display(
+            config,
+            format!(
+                "Transfer {} tokens\n  Sender: {}\n  Recipient: {}",
+                hpl_token::amount_to_ui_amount(transfer_balance, decimals),
+                sender,
+                recipient
+            ),
         );
 
         if transfer_balance > sender_balance {
@@ -714,9 +1163,12 @@ fn command_transfer(
 
     if !recipient_is_token_account {
         recipient_token_account = get_associated_token_address(&recipient, &mint_pubkey);
-        println!(
-            "  Recipient associated token account: {}",
-            recipient_token_account
+        println_display(
+            config,
+            format!(
+                "  Recipient associated token account: {}",
+                recipient_token_account
+            ),
         );
 
         let needs_funding = if !config.sign_only {
@@ -750,10 +1202,13 @@ fn command_transfer(
                     minimum_balance_for_rent_exemption += config
                         .rpc_client
                         .get_minimum_balance_for_rent_exemption(Account::LEN)?;
-                    println!(
-                        "  Funding recipient: {} ({} HC)",
-                        recipient_token_account,
-                        lamports_to_hc(minimum_balance_for_rent_exemption)
+                    println_display(
+                        config,
+                        format!(
+                            "  Funding recipient: {} ({} HC)",
+                            recipient_token_account,
+                            lamports_to_hc(minimum_balance_for_rent_exemption)
+                        ),
                     );
                 }
                 instructions.push(create_associated_token_account(
@@ -787,46 +1242,294 @@ fn command_transfer(
     )))
 }
 
-fn command_burn(
+#[allow(clippy::too_many_arguments)]
+fn command_distribute(
     config: &Config,
-    source: Pubkey,
-    source_owner: Pubkey,
-    ui_amount: String,
-    mint_address: Option<Pubkey>,
-    mint_decimals: Option<u8>,
+    signer_info: &CliSignerInfo,
+    token: Pubkey,
+    input: &str,
+    ledger_path: Option<&str>,
+    sender: Option<Pubkey>,
+    sender_owner: Pubkey,
+    fund_recipient: bool,
+    batch_size: usize,
 ) -> CommandResult {
-    println!("Burn {} tokens\n  Source: {}", ui_amount, source);
-
-    let (mint_pubkey, decimals) = resolve_mint_info(config, &source, mint_address, mint_decimals)?;
-    let (inte,frac) = parse_amount(ui_amount.as_str());
-    let amount = hpl_token::ui_amount_to_amount(inte,frac, decimals);
+    if batch_size == 0 {
+        return Err("Error: The batch size must be greater than zero".into());
+    }
+    let sender = sender.unwrap_or_else(|| get_associated_token_address(&sender_owner, &token));
+    let (mint_pubkey, decimals) = resolve_mint_info(config, &sender, Some(token), None)?;
+    let payments = distribute::read_payments(input, decimals)?;
+    let mut ledger = Ledger::open(
+        ledger_path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Ledger::default_path(input)),
+    )?;
+    ledger.check(&payments)?;
+
+    // Settle the transactions a previous run sent without seeing their outcome. A
+    // transaction whose blockhash expired, or whose nonce advanced, can no longer
+    // land, so if it isn't found after that, its payments are still due.
+    for batch in ledger.pending() {
+        let expired = match batch.nonce_account {
+            Some(nonce_account) => {
+                let (nonce, _) = blockhash_query::Source::NonceAccount(nonce_account)
+                    .get_blockhash_and_fee_calculator(
+                        &config.rpc_client,
+                        config.rpc_client.commitment(),
+                    )?;
+                nonce != batch.blockhash
+            }
+            None => !config
+                .rpc_client
+                .is_blockhash_valid(&batch.blockhash, config.rpc_client.commitment())?,
+        };
+        let status = config
+            .rpc_client
+            .get_signature_status_with_commitment_and_history(
+                &batch.signature,
+                config.rpc_client.commitment(),
+                true,
+            )?;
+        let outcome = match status {
+            Some(Ok(())) => BatchStatus::Confirmed,
+            Some(Err(_)) => BatchStatus::Failed,
+            None if expired => BatchStatus::Failed,
+            None => {
+                return Err(format!(
+                    "Transaction {} of a previous run is still in flight. \
+                     Wait for its blockhash to expire, or advance its nonce, and try again",
+                    batch.signature
+                )
+                .into())
+            }
+        };
+        println_display(
+            config,
+            format!("Previous transaction {}: {:?}", batch.signature, outcome),
+        );
+        ledger.record_outcome(&batch.signature, outcome)?;
+    }
 
-    let instructions = vec![burn_checked(
-        &hpl_token::id(),
-        &source,
-        &mint_pubkey,
-        &source_owner,
-        &config.multisigner_pubkeys,
-        amount,
-        decimals,
-    )?];
-    Ok(Some((0, vec![instructions])))
-}
+    let paid = ledger.paid();
+    let due: Vec<_> = payments
+        .into_iter()
+        .filter(|payment| !paid.contains(&payment.line))
+        .collect();
+    let mut cli_distribution = CliDistribution {
+        ledger: ledger.path().display().to_string(),
+        already_paid: paid.len(),
+        paid: 0,
+        signatures: vec![],
+    };
+    println_display(config, format!("Ledger: {}", cli_distribution.ledger));
+    println_display(config, format!("Already paid: {}", paid.len()));
+    println_display(config, format!("Payments due: {}", due.len()));
+    if due.is_empty() {
+        println!("{}", format_output(cli_distribution, "distribute", config));
+        return Ok(None);
+    }
 
-fn command_mint(
-    config: &Config,
-    token: Pubkey,
-    ui_amount: String,
-    recipient: Pubkey,
-    mint_decimals: Option<u8>,
-    mint_authority: Pubkey,
-) -> CommandResult {
-    println!(
-        "Minting {} tokens\n  Token: {}\n  Recipient: {}",
-        ui_amount, token, recipient
+    let total = due.iter().map(|payment| payment.amount).sum::<u128>();
+    let sender_balance = config
+        .rpc_client
+        .get_token_account_balance(&sender)?
+        .amount
+        .parse::<u128>()?;
+    println_display(
+        config,
+        format!("Total: {}", hpl_token::amount_to_ui_amount(total, decimals)),
     );
+    if total > sender_balance {
+        return Err(format!(
+            "Error: Sender has insufficient funds, current balance is {}",
+            hpl_token::amount_to_ui_amount(sender_balance, decimals)
+        )
+        .into());
+    }
 
-    let (_, decimals) = resolve_mint_info(config, &recipient, None, mint_decimals)?;
+    // Pay recipients that are token accounts of the mint directly, and the
+    // associated token account of the others
+    let mut recipients: Vec<Pubkey> = due.iter().map(|payment| payment.recipient).collect();
+    recipients.sort();
+    recipients.dedup();
+    let mut destinations = HashMap::new();
+    for chunk in recipients.chunks(100) {
+        let accounts = config.rpc_client.get_multiple_accounts(chunk)?;
+        for (recipient, account) in chunk.iter().zip(accounts) {
+            let is_token_account = account.map_or(false, |account| {
+                account.owner == hpl_token::id()
+                    && Account::unpack(&account.data)
+                        .map_or(false, |token_account| token_account.mint == mint_pubkey)
+            });
+            let destination = if is_token_account {
+                *recipient
+            } else {
+                get_associated_token_address(recipient, &mint_pubkey)
+            };
+            destinations.insert(*recipient, destination);
+        }
+    }
+
+    let associated_accounts: Vec<(Pubkey, Pubkey)> = destinations
+        .iter()
+        .filter(|(recipient, destination)| recipient != destination)
+        .map(|(recipient, destination)| (*recipient, *destination))
+        .collect();
+    let mut unfunded = HashSet::new();
+    for chunk in associated_accounts.chunks(100) {
+        let addresses: Vec<Pubkey> = chunk.iter().map(|(_, address)| *address).collect();
+        let accounts = config.rpc_client.get_multiple_accounts(&addresses)?;
+        for ((recipient, _), account) in chunk.iter().zip(accounts) {
+            if account.map_or(true, |account| account.owner == system_program::id()) {
+                unfunded.insert(*recipient);
+            }
+        }
+    }
+    let minimum_balance_for_rent_exemption = if unfunded.is_empty() {
+        0
+    } else if fund_recipient {
+        let minimum_balance = config
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(Account::LEN)?;
+        println_display(
+            config,
+            format!(
+                "Funding recipients: {} ({} HC)",
+                unfunded.len(),
+                lamports_to_hc(minimum_balance * unfunded.len() as u128)
+            ),
+        );
+        minimum_balance
+    } else {
+        return Err(format!(
+            "Error: {} recipients have no associated token account. \
+             Add `--fund-recipient` to fund their accounts",
+            unfunded.len()
+        )
+        .into());
+    };
+
+    let batches: Vec<_> = due.chunks(batch_size).collect();
+    for (index, payments) in batches.iter().enumerate() {
+        let mut instructions = vec![];
+        let mut funding = 0;
+        for payment in payments.iter() {
+            if unfunded.remove(&payment.recipient) {
+                instructions.push(create_associated_token_account(
+                    &config.fee_payer,
+                    &payment.recipient,
+                    &mint_pubkey,
+                ));
+                funding += minimum_balance_for_rent_exemption;
+            }
+            instructions.push(transfer_checked(
+                &hpl_token::id(),
+                &sender,
+                &mint_pubkey,
+                &destinations[&payment.recipient],
+                &sender_owner,
+                &config.multisigner_pubkeys,
+                payment.amount,
+                decimals,
+            )?);
+        }
+
+        let message = new_message(config, &instructions, index);
+        let (recent_blockhash, fee_calculator) = get_blockhash_and_fee_calculator(config, index)?;
+        check_fee_payer_balance(config, funding + fee_calculator.calculate_fee(&message))?;
+        let signers = signer_info.signers_for_message(&message);
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction.try_sign(&signers, recent_blockhash)?;
+        record_nonce_signed(config, index)?;
+        let signature = transaction.signatures[0];
+
+        // The ledger must know about the transaction before it can land
+        let nonce_account = config
+            .nonce_pool
+            .as_ref()
+            .map(|nonce_pool| nonce_pool.account(index));
+        ledger.record_pending(
+            signature,
+            recent_blockhash,
+            nonce_account,
+            payments.to_vec(),
+        )?;
+        println_display(
+            config,
+            format!("Batch {}/{}: {}", index + 1, batches.len(), signature),
+        );
+        match config
+            .rpc_client
+            .send_and_confirm_transaction_with_spinner(&transaction)
+        {
+            Ok(_) => {
+                ledger.record_outcome(&signature, BatchStatus::Confirmed)?;
+                cli_distribution.paid += payments.len();
+                cli_distribution.signatures.push(signature.to_string());
+            }
+            Err(err) => {
+                if let Some(TransactionError::InstructionError(..)) = err.get_transaction_error() {
+                    ledger.record_outcome(&signature, BatchStatus::Failed)?;
+                }
+                return Err(format!(
+                    "{}\nRun the same command again to resume the distribution",
+                    err
+                )
+                .into());
+            }
+        }
+    }
+    println!("{}", format_output(cli_distribution, "distribute", config));
+    Ok(None)
+}
+
+fn command_burn(
+    config: &Config,
+    source: Pubkey,
+    source_owner: Pubkey,
+    ui_amount: String,
+    mint_address: Option<Pubkey>,
+    mint_decimals: Option<u8>,
+) -> CommandResult {
+    println_display(
+        config,
+        format!("Burn {} tokens\n  Source: {}", ui_amount, source),
+    );
+
+    let (mint_pubkey, decimals) = resolve_mint_info(config, &source, mint_address, mint_decimals)?;
+    let (inte,frac) = parse_amount(ui_amount.as_str());
+    let amount = hpl_token::ui_amount_to_amount(inte,frac, decimals);
+
+    let instructions = vec![burn_checked(
+        &hpl_token::id(),
+        &source,
+        &mint_pubkey,
+        &source_owner,
+        &config.multisigner_pubkeys,
+        amount,
+        decimals,
+    )?];
+    Ok(Some((0, vec![instructions])))
+}
+
+fn command_mint(
+    config: &Config,
+    token: Pubkey,
+    ui_amount: String,
+    recipient: Pubkey,
+    mint_decimals: Option<u8>,
+    mint_authority: Pubkey,
+) -> CommandResult {
+    println_display(
+        config,
+        format!(
+            "Minting {} tokens\n  Token: {}\n  Recipient: {}",
+            ui_amount, token, recipient
+        ),
+    );
+
+    let (_, decimals) = resolve_mint_info(config, &recipient, None, mint_decimals)?;
 
     let (inte,frac) = parse_amount(ui_amount.as_str());
 
@@ -856,7 +1559,10 @@ fn command_freeze(
 ) -> CommandResult {
     let (token, _) = resolve_mint_info(config, &account, mint_address, None)?;
 
-    println!("Freezing account: {}\n  Token: {}", account, token);
+    println_display(
+        config,
+        format!("Freezing account: {}\n  Token: {}", account, token),
+    );
 
     let instructions = vec![freeze_account(
         &hpl_token::id(),
@@ -876,7 +1582,10 @@ fn command_thaw(
 ) -> CommandResult {
     let (token, _) = resolve_mint_info(config, &account, mint_address, None)?;
 
-    println!("Freezing account: {}\n  Token: {}", account, token);
+    println_display(
+        config,
+        format!("Freezing account: {}\n  Token: {}", account, token),
+    );
 
     let instructions = vec![thaw_account(
         &hpl_token::id(),
@@ -901,7 +1610,10 @@ fn command_wrap(
     }
 
     let instructions = if let Some(wrapped_huione_account) = wrapped_huione_account {
-        println!("Wrapping {} HC into {}", hc, wrapped_huione_account);
+        println_display(
+            config,
+            format!("Wrapping {} HC into {}", hc, wrapped_huione_account),
+        );
         vec![
             system_instruction::create_account(
                 &wallet_address,
@@ -932,7 +1644,7 @@ fn command_wrap(
             }
         }
 
-        println!("Wrapping {} HC into {}", hc, account);
+        println_display(config, format!("Wrapping {} HC into {}", hc, account));
         vec![
             system_instruction::transfer(&wallet_address, &account, lamports),
             create_associated_token_account(&config.fee_payer, &wallet_address, &native_mint::id()),
@@ -952,7 +1664,7 @@ fn command_unwrap(
     let use_associated_account = address.is_none();
     let address = address
         .unwrap_or_else(|| get_associated_token_address(&wallet_address, &native_mint::id()));
-    println!("Unwrapping {}", address);
+    println_display(config, format!("Unwrapping {}", address));
     if !config.sign_only {
         let lamports = config.rpc_client.get_balance(&address)?;
         if lamports == 0 {
@@ -962,9 +1674,9 @@ fn command_unwrap(
                 return Err(format!("No wrapped HC in {}", address).into());
             }
         }
-        println!("  Amount: {} HC", lamports_to_hc(lamports),);
+        println_display(config, format!("  Amount: {} HC", lamports_to_hc(lamports)));
     }
-    println!("  Recipient: {}", &wallet_address);
+    println_display(config, format!("  Recipient: {}", &wallet_address));
 
     let instructions = vec![close_account(
         &hpl_token::id(),
@@ -985,9 +1697,12 @@ fn command_approve(
     mint_address: Option<Pubkey>,
     mint_decimals: Option<u8>,
 ) -> CommandResult {
-    println!(
-        "Approve {} tokens\n  Account: {}\n  Delegate: {}",
-        ui_amount, account, delegate
+    println_display(
+        config,
+        format!(
+            "Approve {} tokens\n  Account: {}\n  Delegate: {}",
+            ui_amount, account, delegate
+        ),
     );
 
     let (mint_pubkey, decimals) = resolve_mint_info(config, &account, mint_address, mint_decimals)?;
@@ -1029,9 +1744,12 @@ fn command_revoke(
     };
 
     if let Some(delegate) = delegate {
-        println!(
-            "Revoking approval\n  Account: {}\n  Delegate: {}",
-            account, delegate
+        println_display(
+            config,
+            format!(
+                "Revoking approval\n  Account: {}\n  Delegate: {}",
+                account, delegate
+            ),
         );
     } else {
         return Err(format!("No delegate on account {}", account).into());
@@ -1088,30 +1806,42 @@ fn command_close(
     Ok(Some((0, vec![instructions])))
 }
 
-fn command_balance(config: &Config, address: Pubkey) -> CommandResult {
+fn command_balance(
+    config: &Config,
+    address: Pubkey,
+    alert: Option<&mut BalanceAlert>,
+) -> CommandResult {
     let balance = config
         .rpc_client
         .get_token_account_balance(&address)
         .map_err(|_| format!("Could not find token account {}", address))?;
-
-    if config.verbose {
-        println!("ui amount: {}", balance.real_number_string_trimmed());
-        println!("decimals: {}", balance.decimals);
-        println!("amount: {}", balance.amount);
-    } else {
-        println!("{}", balance.real_number_string_trimmed());
+    if let Some(alert) = alert {
+        alert.check(&address.to_string(), None, &balance);
     }
+    let cli_token_amount = CliTokenAmount {
+        address: address.to_string(),
+        amount: balance,
+    };
+    println!("{}", format_output(cli_token_amount, "balance", config));
     Ok(None)
 }
 
 fn command_supply(config: &Config, address: Pubkey) -> CommandResult {
     let supply = config.rpc_client.get_token_supply(&address)?;
-
-    println!("{}", supply.real_number_string_trimmed());
+    let cli_token_amount = CliTokenAmount {
+        address: address.to_string(),
+        amount: supply,
+    };
+    println!("{}", format_output(cli_token_amount, "supply", config));
     Ok(None)
 }
 
-fn command_accounts(config: &Config, token: Option<Pubkey>, owner: Pubkey) -> CommandResult {
+fn command_accounts(
+    config: &Config,
+    token: Option<Pubkey>,
+    owner: Pubkey,
+    alert: Option<&mut BalanceAlert>,
+) -> CommandResult {
     if let Some(token) = token {
         validate_mint(config, token)?;
     }
@@ -1122,13 +1852,49 @@ fn command_accounts(config: &Config, token: Option<Pubkey>, owner: Pubkey) -> Co
             None => TokenAccountsFilter::ProgramId(hpl_token::id()),
         },
     )?;
-    if accounts.is_empty() {
+    let is_empty = accounts.is_empty();
+    let (mint_accounts, unsupported_accounts, max_len_balance, includes_aux) =
+        sort_and_parse_token_accounts(&owner, accounts);
+    if let Some(alert) = alert {
+        for (mint, accounts) in mint_accounts.iter() {
+            for account in accounts {
+                alert.check(
+                    &account.address,
+                    Some(mint),
+                    &account.ui_token_account.token_amount,
+                );
+            }
+        }
+    }
+
+    if let OutputFormat::Json | OutputFormat::JsonCompact = config.output_format {
+        let cli_token_accounts = CliTokenAccounts {
+            accounts: mint_accounts
+                .into_iter()
+                .flat_map(|(_, accounts)| accounts)
+                .map(|account| {
+                    CliTokenAccount::new(
+                        account.address,
+                        account.is_associated,
+                        account.ui_token_account,
+                    )
+                })
+                .collect(),
+            unsupported_accounts: unsupported_accounts
+                .into_iter()
+                .map(|account| CliUnsupportedAccount {
+                    address: account.address,
+                    error: account.err,
+                })
+                .collect(),
+        };
+        println!("{}", format_output(cli_token_accounts, "accounts", config));
+        return Ok(None);
+    }
+    if is_empty {
         println!("None");
         return Ok(None);
     }
-
-    let (mint_accounts, unsupported_accounts, max_len_balance, includes_aux) =
-        sort_and_parse_token_accounts(&owner, accounts);
     let aux_len = if includes_aux { 10 } else { 0 };
     let mut gc_alert = false;
 
@@ -1236,15 +2002,61 @@ fn command_accounts(config: &Config, token: Option<Pubkey>, owner: Pubkey) -> Co
     Ok(None)
 }
 
+// Clears the terminal before a watch shows the balances again. JSON documents
+// are streamed one after the other instead.
+fn clear_display(config: &Config) -> Result<(), Error> {
+    if let OutputFormat::Display | OutputFormat::DisplayVerbose = config.output_format {
+        console::Term::stdout().clear_screen()?;
+    }
+    Ok(())
+}
+
+fn command_watch_balance(
+    config: &Config,
+    address: Pubkey,
+    mut alert: Option<BalanceAlert>,
+) -> CommandResult {
+    watch::watch_account(
+        &config.websocket_url,
+        config.rpc_client.commitment(),
+        &address,
+        || {
+            clear_display(config)?;
+            command_balance(config, address, alert.as_mut()).map(|_| ())
+        },
+    )?;
+    Ok(None)
+}
+
+fn command_watch_accounts(
+    config: &Config,
+    token: Option<Pubkey>,
+    owner: Pubkey,
+    mut alert: Option<BalanceAlert>,
+) -> CommandResult {
+    watch::watch_owner(
+        &config.websocket_url,
+        config.rpc_client.commitment(),
+        &owner,
+        || {
+            clear_display(config)?;
+            command_accounts(config, token, owner, alert.as_mut()).map(|_| ())
+        },
+    )?;
+    Ok(None)
+}
+
 fn command_address(config: &Config, token: Option<Pubkey>, owner: Pubkey) -> CommandResult {
+    let mut cli_address = CliWalletAddress {
+        wallet_address: owner.to_string(),
+        ..CliWalletAddress::default()
+    };
     if let Some(token) = token {
         validate_mint(config, token)?;
         let associated_token_address = get_associated_token_address(&owner, &token);
-        println!("Wallet address: {:?}", owner);
-        println!("Associated token address: {:?}", associated_token_address);
-    } else {
-        println!("Wallet address: {:?}", owner);
+        cli_address.associated_token_address = Some(associated_token_address.to_string());
     }
+    println!("{}", format_output(cli_address, "address", config));
     Ok(None)
 }
 
@@ -1257,73 +2069,256 @@ fn command_account_info(config: &Config, address: Pubkey) -> CommandResult {
     let mint = Pubkey::from_str(&account.mint).unwrap();
     let owner = Pubkey::from_str(&account.owner).unwrap();
     let is_associated = get_associated_token_address(&owner, &mint) == address;
-    let address_message = if is_associated {
-        address.to_string()
-    } else {
-        format!("{}  (Aux*)", address)
-    };
-    println!();
-    println_name_value("Address:", &address_message);
-    println_name_value(
-        "Balance:",
-        &account.token_amount.real_number_string_trimmed(),
-    );
-    let mint = format!(
-        "{}{}",
-        account.mint,
-        if account.is_native { " (native)" } else { "" }
+    let cli_token_account = CliTokenAccount::new(address.to_string(), is_associated, account);
+    println!(
+        "{}",
+        format_output(cli_token_account, "account-info", config)
     );
-    println_name_value("Mint:", &mint);
-    println_name_value("Owner:", &account.owner);
-    println_name_value("State:", &format!("{:?}", account.state));
-    if let Some(delegate) = &account.delegate {
-        println!("Delegation:");
-        println_name_value("  Delegate:", delegate);
-        let allowance = account.delegated_amount.as_ref().unwrap();
-        println_name_value("  Allowance:", &allowance.real_number_string_trimmed());
+    Ok(None)
+}
+
+fn format_output<T>(command_output: T, command_name: &str, config: &Config) -> String
+where
+    T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    config.output_format.formatted_string(&CommandOutput {
+        command_name: String::from(command_name),
+        command_output,
+    })
+}
+
+// Name of an instruction, without its fields
+fn token_instruction_name(instruction: &TokenInstruction) -> String {
+    format!("{:?}", instruction)
+        .chars()
+        .take_while(|c| c.is_alphanumeric())
+        .collect()
+}
+
+fn get_history_transaction(
+    config: &Config,
+    address: &Pubkey,
+    decimals: u8,
+    status: RpcConfirmedTransactionStatusWithSignature,
+) -> Result<CliHistoryTransaction, Error> {
+    let signature = Signature::from_str(&status.signature)?;
+    let confirmed_transaction = config.rpc_client.get_transaction_with_config(
+        &signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(config.rpc_client.commitment()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+
+    let mut instructions = vec![];
+    let mut change = None;
+    let mut post_balance = None;
+    let transaction = confirmed_transaction.transaction.transaction.decode();
+    let account_keys = transaction
+        .as_ref()
+        .map(|transaction| transaction.message.static_account_keys())
+        .unwrap_or_default();
+    if let Some(index) = account_keys.iter().position(|key| key == address) {
+        for instruction in transaction.as_ref().unwrap().message.instructions() {
+            if account_keys.get(instruction.program_id_index as usize) == Some(&hpl_token::id())
+                && instruction.accounts.contains(&(index as u8))
+            {
+                instructions.push(
+                    TokenInstruction::unpack(&instruction.data)
+                        .map(|instruction| token_instruction_name(&instruction))
+                        .unwrap_or_else(|_| "Unknown".to_string()),
+                );
+            }
+        }
+
+        // The account is missing from the token balances of the transactions that
+        // create or close it
+        if let Some(meta) = confirmed_transaction.transaction.meta {
+            let balance = |balances: Option<Vec<UiTransactionTokenBalance>>| {
+                balances.map(|balances| {
+                    balances
+                        .iter()
+                        .find(|balance| balance.account_index as usize == index)
+                        .and_then(|balance| balance.ui_token_amount.amount.parse::<u128>().ok())
+                        .unwrap_or_default()
+                })
+            };
+            let pre = balance(meta.pre_token_balances.into());
+            let post = balance(meta.post_token_balances.into());
+            if let (Some(pre), Some(post)) = (pre, post) {
+                change = Some(if post >= pre {
+                    format!("+{}", hpl_token::amount_to_ui_amount(post - pre, decimals))
+                } else {
+                    format!("-{}", hpl_token::amount_to_ui_amount(pre - post, decimals))
+                });
+                post_balance = Some(hpl_token::amount_to_ui_amount(post, decimals));
+            }
+        }
+    }
+
+    Ok(CliHistoryTransaction {
+        signature: status.signature,
+        slot: status.slot,
+        block_time: status.block_time,
+        error: status.err.map(|err| err.to_string()),
+        instructions,
+        change,
+        post_balance,
+        memo: status.memo,
+    })
+}
+
+fn command_history(
+    config: &Config,
+    address: Pubkey,
+    limit: usize,
+    before: Option<Signature>,
+    csv: bool,
+) -> CommandResult {
+    let account = config
+        .rpc_client
+        .get_token_account(&address)?
+        .ok_or_else(|| format!("Could not find token account {}", address))?;
+    let decimals = account.token_amount.decimals;
+
+    let mut statuses = vec![];
+    let mut before = before;
+    while statuses.len() < limit {
+        let page = config.rpc_client.get_signatures_for_address_with_config(
+            &address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some((limit - statuses.len()).min(1000)),
+                commitment: Some(config.rpc_client.commitment()),
+            },
+        )?;
+        match page.last() {
+            Some(last) => before = Some(Signature::from_str(&last.signature)?),
+            None => break,
+        }
+        statuses.extend(page);
+    }
+    let next_page_before = if statuses.len() == limit {
+        before.map(|signature| signature.to_string())
     } else {
-        println_name_value("Delegation:", "");
+        None
+    };
+
+    let mut transactions = vec![];
+    for status in statuses {
+        transactions.push(get_history_transaction(config, &address, decimals, status)?);
     }
-    println_name_value(
-        "Close authority:",
-        account.close_authority.as_ref().unwrap_or(&String::new()),
-    );
-    if !is_associated {
-        println!();
-        println!("* Please run `hpl-token gc` to clean up Aux accounts");
+    let history = CliHistory {
+        address: address.to_string(),
+        mint: account.mint,
+        decimals,
+        transactions,
+        next_page_before,
+    };
+    if csv {
+        print!("{}", history.to_csv());
+    } else {
+        println!("{}", format_output(history, "history", config));
     }
     Ok(None)
 }
 
-fn get_multisig(config: &Config, address: &Pubkey) -> Result<Multisig, Error> {
+/// Returns the version, the number of required signers and the signers of a multisig
+fn get_multisig(config: &Config, address: &Pubkey) -> Result<(u8, u8, Vec<Pubkey>), Error> {
     let account = config.rpc_client.get_account(address)?;
-    Multisig::unpack(&account.data).map_err(|e| e.into())
+    if account.data.len() == Multisig::LEN {
+        let multisig = Multisig::unpack(&account.data)?;
+        let n = multisig.n as usize;
+        assert!(n <= multisig.signers.len());
+        Ok((1, multisig.m, multisig.signers[..n].to_vec()))
+    } else {
+        let multisig = MultisigV2::unpack(&account.data)?;
+        Ok((MultisigV2::VERSION, multisig.m, multisig.signers))
+    }
 }
 
 fn command_multisig(config: &Config, address: Pubkey) -> CommandResult {
-    let multisig = get_multisig(config, &address)?;
-    let n = multisig.n as usize;
-    assert!(n <= multisig.signers.len());
-    println!();
-    println_name_value("Address:", &address.to_string());
-    println_name_value("M/N:", &format!("{}/{}", multisig.m, n));
-    println_name_value("Signers:", " ");
-    let width = if n >= 9 { 4 } else { 3 };
-    for i in 0..n {
-        let title = format!("{1:>0$}:", width, i + 1);
-        let pubkey = &multisig.signers[i];
-        println_name_value(&title, &pubkey.to_string())
-    }
+    let (version, m, signers) = get_multisig(config, &address)?;
+    let cli_multisig = CliMultisig {
+        address: address.to_string(),
+        version,
+        m,
+        n: signers.len() as u8,
+        signers: signers.iter().map(|pubkey| pubkey.to_string()).collect(),
+    };
+    println!("{}", format_output(cli_multisig, "multisig-info", config));
     Ok(None)
 }
 
-fn command_gc(config: &Config, owner: Pubkey) -> CommandResult {
-    println!("Fetching token accounts");
+fn command_update_multisig(
+    config: &Config,
+    multisig: Pubkey,
+    minimum_signers: u8,
+    multisig_members: Vec<Pubkey>,
+) -> CommandResult {
+    let space = MultisigV2::get_packed_len(multisig_members.len());
+    let minimum_balance_for_rent_exemption = if !config.sign_only {
+        let (version, m, signers) = get_multisig(config, &multisig)?;
+        println_display(
+            config,
+            format!(
+                "Updating multisig {}\n  Current: {}/{} (v{})\n  New: {}/{} (v{})",
+                multisig,
+                m,
+                signers.len(),
+                version,
+                minimum_signers,
+                multisig_members.len(),
+                MultisigV2::VERSION
+            ),
+        );
+        let balance = config.rpc_client.get_balance(&multisig)?;
+        config
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(space)?
+            .saturating_sub(balance)
+    } else {
+        0
+    };
+
+    let instructions = vec![update_multisig(
+        &hpl_token::id(),
+        &multisig,
+        &config.fee_payer,
+        &multisig_members.iter().collect::<Vec<_>>(),
+        minimum_signers,
+        &config.multisigner_pubkeys,
+    )?];
+    Ok(Some((
+        minimum_balance_for_rent_exemption,
+        vec![instructions],
+    )))
+}
+
+/// A token account of the owner, as `gc` sees it
+struct GcAccount {
+    amount: u128,
+    decimals: u8,
+    frozen: bool,
+    close_authority: Pubkey,
+    lamports: u128,
+    is_native: bool,
+}
+
+fn command_gc(
+    config: &Config,
+    owner: Pubkey,
+    burn_dust_below: Option<String>,
+    dry_run: bool,
+) -> CommandResult {
+    println_display(config, "Fetching token accounts".to_string());
     let accounts = config
         .rpc_client
         .get_token_accounts_by_owner(&owner, TokenAccountsFilter::ProgramId(hpl_token::id()))?;
     if accounts.is_empty() {
-        println!("Nothing to do");
+        println_display(config, "Nothing to do".to_string());
         return Ok(None);
     }
 
@@ -1335,7 +2330,7 @@ fn command_gc(config: &Config, owner: Pubkey) -> CommandResult {
         0
     };
 
-    let mut accounts_by_token = HashMap::new();
+    let mut accounts_by_token = BTreeMap::new();
 
     for keyed_account in accounts {
         if let UiAccountData::Json(parsed_account) = keyed_account.account.data {
@@ -1364,87 +2359,319 @@ fn command_gc(config: &Config, owner: Pubkey) -> CommandResult {
                             .unwrap_or_else(|err| panic!("Invalid close authority: {}", err))
                     });
 
-                    let entry = accounts_by_token.entry(token).or_insert_with(HashMap::new);
+                    let entry = accounts_by_token.entry(token).or_insert_with(BTreeMap::new);
                     entry.insert(
                         token_account,
-                        (
-                            token_amount,
-                            ui_token_account.token_amount.decimals,
+                        GcAccount {
+                            amount: token_amount,
+                            decimals: ui_token_account.token_amount.decimals,
                             frozen,
                             close_authority,
-                        ),
+                            lamports: keyed_account.account.lamports,
+                            is_native: ui_token_account.is_native,
+                        },
                     );
                 }
             }
         }
     }
 
-    let mut instructions = vec![];
+    let mut instruction_groups = vec![];
     let mut lamports_needed = 0;
+    let mut planned_accounts = vec![];
+    let mut associated_accounts_created = 0;
+    let mut rent_recovered = 0;
 
     for (token, accounts) in accounts_by_token.into_iter() {
-        println!("Processing token: {}", token);
         let associated_token_account = get_associated_token_address(&owner, &token);
-        let total_balance: u128 = accounts.values().map(|account| account.0).sum();
-
-        if total_balance > 0 && !accounts.contains_key(&associated_token_account) {
-            // Create the associated token account
-            instructions.push(vec![create_associated_token_account(
-                &config.fee_payer,
-                &owner,
-                &token,
-            )]);
-            lamports_needed += minimum_balance_for_rent_exemption;
-        }
+        let associated_account = accounts.get(&associated_token_account);
+        let associated_account_frozen = associated_account.map_or(false, |account| account.frozen);
+        let mut create_associated_account = false;
+        let mut token_instruction_groups = vec![];
 
-        for (address, (amount, decimals, frozen, close_authority)) in accounts {
-            if address == associated_token_account {
+        for (address, account) in &accounts {
+            if *address == associated_token_account {
                 // leave the associated token account alone
                 continue;
             }
 
-            if frozen {
-                // leave frozen accounts alone
+            let mut planned_account = CliGcAccount {
+                address: address.to_string(),
+                mint: token.to_string(),
+                action: "skip".to_string(),
+                balance: hpl_token::amount_to_ui_amount(account.amount, account.decimals),
+                rent: 0,
+                reason: None,
+            };
+            if account.frozen {
+                // neither the balance nor the account can be touched until thawed
+                planned_account.reason = Some("account is frozen".to_string());
+                planned_accounts.push(planned_account);
                 continue;
             }
 
+            let is_dust = burn_dust_below.as_ref().map_or(false, |threshold| {
+                let (inte, frac) = parse_amount(threshold);
+                account.amount < hpl_token::ui_amount_to_amount(inte, frac, account.decimals)
+            });
+            let mut actions = vec![];
             let mut account_instructions = vec![];
 
-            // Transfer the account balance into the associated token account
-            if amount > 0 {
-                account_instructions.push(transfer_checked(
-                    &hpl_token::id(),
-                    &address,
-                    &token,
-                    &associated_token_account,
-                    &owner,
-                    &config.multisigner_pubkeys,
-                    amount,
-                    decimals,
-                )?);
+            if account.amount > 0 {
+                if is_dust && !account.is_native {
+                    // Burn the dust rather than move it
+                    actions.push("burn");
+                    account_instructions.push(burn_checked(
+                        &hpl_token::id(),
+                        address,
+                        &token,
+                        &owner,
+                        &config.multisigner_pubkeys,
+                        account.amount,
+                        account.decimals,
+                    )?);
+                } else if associated_account_frozen {
+                    planned_account.reason = Some("associated account is frozen".to_string());
+                    planned_accounts.push(planned_account);
+                    continue;
+                } else {
+                    // Transfer the account balance into the associated token account
+                    create_associated_account |= associated_account.is_none();
+                    actions.push("transfer");
+                    account_instructions.push(transfer_checked(
+                        &hpl_token::id(),
+                        address,
+                        &token,
+                        &associated_token_account,
+                        &owner,
+                        &config.multisigner_pubkeys,
+                        account.amount,
+                        account.decimals,
+                    )?);
+                }
             }
             // Close the account if config.owner is able to
-            if close_authority == owner {
+            if account.close_authority == owner {
+                actions.push("close");
                 account_instructions.push(close_account(
                     &hpl_token::id(),
-                    &address,
+                    address,
                     &owner,
                     &owner,
                     &config.multisigner_pubkeys,
                 )?);
+                // The wrapped balance of a native account leaves with the transfer
+                planned_account.rent = if account.is_native {
+                    account.lamports.saturating_sub(account.amount)
+                } else {
+                    account.lamports
+                };
+                rent_recovered += planned_account.rent;
+            } else {
+                planned_account.reason =
+                    Some(format!("close authority is {}", account.close_authority));
             }
 
-            if !account_instructions.is_empty() {
-                instructions.push(account_instructions);
+            if !actions.is_empty() {
+                planned_account.action = actions.join(" and ");
+                token_instruction_groups.push(account_instructions);
             }
+            planned_accounts.push(planned_account);
+        }
+
+        if create_associated_account {
+            // Create the associated token account
+            instruction_groups.push(vec![create_associated_token_account(
+                &config.fee_payer,
+                &owner,
+                &token,
+            )]);
+            lamports_needed += minimum_balance_for_rent_exemption;
+            associated_accounts_created += 1;
         }
+        instruction_groups.extend(token_instruction_groups);
+    }
+
+    if planned_accounts.is_empty() {
+        println_display(config, "Nothing to do".to_string());
+        return Ok(None);
+    }
+
+    let instruction_batches = pack_instruction_groups(config, instruction_groups);
+    let (_, fee_calculator) = config
+        .blockhash_query
+        .get_blockhash_and_fee_calculator(&config.rpc_client, config.rpc_client.commitment())?;
+    let fees = instruction_batches
+        .iter()
+        .map(|instructions| {
+            fee_calculator.calculate_fee(&Message::new(instructions, Some(&config.fee_payer)))
+        })
+        .sum();
+
+    let cli_gc = CliGc {
+        dry_run,
+        accounts: planned_accounts,
+        associated_accounts_created,
+        transactions: instruction_batches.len(),
+        rent_recovered,
+        rent_spent: lamports_needed,
+        fees,
+    };
+    println!("{}", format_output(cli_gc, "gc", config));
+    if dry_run || instruction_batches.is_empty() {
+        return Ok(None);
     }
 
-    Ok(Some((lamports_needed, instructions)))
+    Ok(Some((lamports_needed, instruction_batches)))
 }
 
-fn command_sync_native(native_account_address: Pubkey) -> CommandResult {
-    let instructions = vec![sync_native(&hpl_token::id(), &native_account_address)?];
+fn command_nonce_pool_create(
+    config: &Config,
+    pool_file: &Path,
+    count: usize,
+    seed_prefix: String,
+    authority: Pubkey,
+) -> CommandResult {
+    let nonce_pool = NoncePool::new(config.fee_payer, seed_prefix, authority, count)?;
+    // Running the command again creates the accounts an earlier run failed
+    // to, as long as it describes the same pool
+    if pool_file.exists() {
+        let existing = NoncePool::read(pool_file)?;
+        if existing.base != nonce_pool.base
+            || existing.seed_prefix != nonce_pool.seed_prefix
+            || existing.authority != nonce_pool.authority
+            || existing.accounts.len() != count
+        {
+            return Err(format!(
+                "{} already exists and describes another pool",
+                pool_file.display()
+            )
+            .into());
+        }
+    } else {
+        nonce_pool.write(pool_file)?;
+    }
+    let minimum_balance_for_rent_exemption = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(NonceState::size())?;
+
+    let mut instruction_groups = vec![];
+    for (chunk_index, chunk) in nonce_pool.accounts.chunks(100).enumerate() {
+        for (offset, (nonce_account, account)) in chunk
+            .iter()
+            .zip(config.rpc_client.get_multiple_accounts(chunk)?)
+            .enumerate()
+        {
+            if account.is_none() {
+                let index = chunk_index * 100 + offset;
+                instruction_groups.push(system_instruction::create_nonce_account_with_seed(
+                    &config.fee_payer,
+                    nonce_account,
+                    &nonce_pool.base,
+                    &nonce_pool.seed(index),
+                    &authority,
+                    minimum_balance_for_rent_exemption,
+                ));
+            }
+        }
+    }
+    if instruction_groups.is_empty() {
+        println_display(
+            config,
+            format!(
+                "All {} nonce accounts of {} exist",
+                count,
+                pool_file.display()
+            ),
+        );
+        return Ok(None);
+    }
+    println_display(
+        config,
+        format!(
+            "Creating {} nonce accounts\n  Pool file: {}",
+            instruction_groups.len(),
+            pool_file.display()
+        ),
+    );
+    Ok(Some((
+        minimum_balance_for_rent_exemption * instruction_groups.len() as u128,
+        pack_instruction_groups(config, instruction_groups),
+    )))
+}
+
+fn command_nonce_pool_list(config: &Config, pool_file: &Path) -> CommandResult {
+    let nonce_pool = NoncePool::read(pool_file)?;
+    let mut accounts = vec![];
+    for chunk in nonce_pool.accounts.chunks(100) {
+        for (address, account) in chunk
+            .iter()
+            .zip(config.rpc_client.get_multiple_accounts(chunk)?)
+        {
+            let mut cli_account = CliNoncePoolAccount {
+                address: address.to_string(),
+                status: "missing".to_string(),
+                blockhash: None,
+            };
+            if let Some(account) = account {
+                cli_account.status = match nonce_utils::data_from_account(&account) {
+                    Ok(data) if data.authority == nonce_pool.authority => {
+                        let (blockhash, _) = blockhash_query::Source::NonceAccount(*address)
+                            .get_blockhash_and_fee_calculator(
+                                &config.rpc_client,
+                                config.rpc_client.commitment(),
+                            )?;
+                        cli_account.blockhash = Some(blockhash.to_string());
+                        "ready".to_string()
+                    }
+                    Ok(data) => format!("authority changed to {}", data.authority),
+                    Err(_) => "not a nonce account".to_string(),
+                };
+            }
+            accounts.push(cli_account);
+        }
+    }
+
+    let cli_nonce_pool = CliNoncePool {
+        file: pool_file.display().to_string(),
+        authority: nonce_pool.authority.to_string(),
+        accounts,
+    };
+    println!("{}", format_output(cli_nonce_pool, "nonce-pool", config));
+    Ok(None)
+}
+
+fn command_nonce_pool_advance(
+    config: &Config,
+    pool_file: &Path,
+    authority: Pubkey,
+) -> CommandResult {
+    let nonce_pool = NoncePool::read(pool_file)?;
+    if authority != nonce_pool.authority {
+        return Err(format!(
+            "The nonce authority of the pool is {}, not {}",
+            nonce_pool.authority, authority
+        )
+        .into());
+    }
+    let instruction_groups = nonce_pool
+        .accounts
+        .iter()
+        .map(|nonce_account| {
+            vec![system_instruction::advance_nonce_account(
+                nonce_account,
+                &authority,
+            )]
+        })
+        .collect();
+    Ok(Some((
+        0,
+        pack_instruction_groups(config, instruction_groups),
+    )))
+}
+
+fn command_sync_native(native_account_address: Pubkey) -> CommandResult {
+    let instructions = vec![sync_native(&hpl_token::id(), &native_account_address)?];
     Ok(Some((0, vec![instructions])))
 }
 
@@ -1460,17 +2687,18 @@ fn command_token_info(config: &Config, address: Pubkey) -> CommandResult {
 
     let token_data = MintMeta::unpack_from_slice(account_data.as_slice())?;
 
-    println_name_value("Token:", address.to_string().as_str());
-    let auth = match token_data.authority {
-        COption::Some(key) => key.to_string(),
-        COption::None => "None".to_string(),
+    let cli_token_info = CliTokenInfo {
+        address: address.to_string(),
+        mint_meta: mint_info.to_string(),
+        mint_meta_authority: match token_data.authority {
+            COption::Some(key) => Some(key.to_string()),
+            COption::None => None,
+        },
+        symbol: token_data.symbol,
+        name: token_data.name,
+        icon: token_data.icon,
     };
-    println_name_value("Token Meta:", mint_info.to_string().as_str());
-    println_name_value("Token Meta Authority:",auth.to_string().as_str());
-    println_name_value("Token Symbol:", token_data.symbol.as_str());
-    println_name_value("Token Name:", token_data.name.as_str());
-    println_name_value("Token Icon:", token_data.icon.as_str());
-
+    println!("{}", format_output(cli_token_info, "token-info", config));
     Ok(None)
 }
 
@@ -1535,6 +2763,458 @@ fn command_update_token_info(
     }
 }
 
+fn command_create_group(
+    config: &Config,
+    token: Pubkey,
+    mint_authority: Pubkey,
+    update_authority: Option<Pubkey>,
+    max_size: u32,
+) -> CommandResult {
+    let minimum_balance_for_rent_exemption = if !config.sign_only {
+        validate_mint(config, token)?;
+        config
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(TokenGroup::LEN)?
+    } else {
+        0
+    };
+
+    let group = hpl_token::get_group_address(&token);
+    println_display(config, format!("Creating group {}", group));
+    println_display(config, format!("  Mint: {}", token));
+    println_display(config, format!("  Max size: {}", max_size));
+
+    let instructions = vec![initialize_group(
+        &hpl_token::id(),
+        &config.fee_payer,
+        &token,
+        &mint_authority,
+        &config.multisigner_pubkeys,
+        update_authority.as_ref(),
+        max_size,
+    )?];
+    Ok(Some((
+        minimum_balance_for_rent_exemption,
+        vec![instructions],
+    )))
+}
+
+fn command_add_group_member(
+    config: &Config,
+    group_token: Pubkey,
+    member_token: Pubkey,
+    member_mint_authority: Pubkey,
+    update_authority: Pubkey,
+) -> CommandResult {
+    let group = hpl_token::get_group_address(&group_token);
+    let minimum_balance_for_rent_exemption = if !config.sign_only {
+        validate_mint(config, member_token)?;
+        let group_data = get_token_group(config, &group)?;
+        if group_data.size >= group_data.max_size {
+            return Err(format!(
+                "Error: group {} is full ({}/{} members)",
+                group, group_data.size, group_data.max_size
+            )
+            .into());
+        }
+        config
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(TokenGroupMember::LEN)?
+    } else {
+        0
+    };
+
+    println_display(
+        config,
+        format!(
+            "Adding {} to group {}\n  Member: {}",
+            member_token,
+            group,
+            hpl_token::get_group_member_address(&member_token)
+        ),
+    );
+
+    let instructions = vec![initialize_member(
+        &hpl_token::id(),
+        &config.fee_payer,
+        &member_token,
+        &member_mint_authority,
+        &group_token,
+        &update_authority,
+        &config.multisigner_pubkeys,
+    )?];
+    Ok(Some((
+        minimum_balance_for_rent_exemption,
+        vec![instructions],
+    )))
+}
+
+fn command_update_group_max_size(
+    config: &Config,
+    group_token: Pubkey,
+    update_authority: Pubkey,
+    max_size: u32,
+) -> CommandResult {
+    let group = hpl_token::get_group_address(&group_token);
+    if !config.sign_only {
+        let group_data = get_token_group(config, &group)?;
+        if max_size < group_data.size {
+            return Err(format!(
+                "Error: group {} already has {} members",
+                group, group_data.size
+            )
+            .into());
+        }
+        println_display(
+            config,
+            format!(
+                "Updating group {}\n  Current max size: {}\n  New max size: {}",
+                group, group_data.max_size, max_size
+            ),
+        );
+    }
+
+    let instructions = vec![update_group_max_size(
+        &hpl_token::id(),
+        &group_token,
+        &update_authority,
+        &config.multisigner_pubkeys,
+        max_size,
+    )?];
+    Ok(Some((0, vec![instructions])))
+}
+
+fn get_token_group(config: &Config, address: &Pubkey) -> Result<TokenGroup, Error> {
+    let account = config
+        .rpc_client
+        .get_account(address)
+        .map_err(|_| format!("Could not find token group {}", address))?;
+    TokenGroup::unpack(&account.data).map_err(|e| e.into())
+}
+
+fn get_token_group_members(
+    config: &Config,
+    group: &Pubkey,
+) -> Result<Vec<(Pubkey, TokenGroupMember)>, Error> {
+    let accounts = config.rpc_client.get_program_accounts_with_config(
+        &hpl_token::id(),
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(TokenGroupMember::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    TokenGroupMember::GROUP_OFFSET,
+                    &group.to_bytes(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(config.rpc_client.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    let mut members = accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            TokenGroupMember::unpack(&account.data)
+                .ok()
+                .map(|member| (address, member))
+        })
+        .collect::<Vec<_>>();
+    members.sort_by_key(|(_, member)| member.member_number);
+    Ok(members)
+}
+
+fn command_group_info(config: &Config, group_token: Pubkey) -> CommandResult {
+    let group = hpl_token::get_group_address(&group_token);
+    let group_data = get_token_group(config, &group)?;
+    let members = get_token_group_members(config, &group)?;
+
+    let cli_token_group = CliTokenGroup {
+        address: group.to_string(),
+        mint: group_data.mint.to_string(),
+        update_authority: group_data.update_authority.map(|pubkey| pubkey.to_string()),
+        size: group_data.size,
+        max_size: group_data.max_size,
+        members: members
+            .into_iter()
+            .map(|(_, member)| CliTokenGroupMember {
+                member_number: member.member_number,
+                mint: member.mint.to_string(),
+            })
+            .collect(),
+    };
+    println!("{}", format_output(cli_token_group, "group-info", config));
+    Ok(None)
+}
+
+/// Returns every token account of a mint, and the slot they were read at
+fn get_mint_token_accounts(config: &Config, token: &Pubkey) -> Result<(Slot, Vec<Account>), Error> {
+    let program_accounts_config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(Account::LEN as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &token.to_bytes())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(config.rpc_client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: Some(true),
+        ..RpcProgramAccountsConfig::default()
+    };
+    let response: Response<Vec<RpcKeyedAccount>> = config.rpc_client.send(
+        RpcRequest::GetProgramAccounts,
+        serde_json::json!([hpl_token::id().to_string(), program_accounts_config]),
+    )?;
+    let accounts = response
+        .value
+        .into_iter()
+        .filter_map(|keyed_account| keyed_account.account.decode::<HuiOneAccount>())
+        .filter_map(|account| Account::unpack(&account.data).ok())
+        .collect();
+    Ok((response.context.slot, accounts))
+}
+
+/// Returns `token` and its token accounts as of a single slot.
+///
+/// The mint is read before and after its accounts; when it is unchanged and
+/// the accounts were read between the two, the mint held that state at the
+/// accounts' slot.
+fn get_mint_and_token_accounts(
+    config: &Config,
+    token: &Pubkey,
+) -> Result<(Slot, Mint, Vec<Account>), Error> {
+    let get_mint = || -> Result<(Slot, Vec<u8>), Error> {
+        let response = config
+            .rpc_client
+            .get_account_with_commitment(token, config.rpc_client.commitment())?;
+        let account = response
+            .value
+            .ok_or_else(|| format!("Invalid token: {}", token))?;
+        Ok((response.context.slot, account.data))
+    };
+    for _ in 0..CONSISTENT_READ_ATTEMPTS {
+        let (before_slot, before_data) = get_mint()?;
+        let (slot, accounts) = get_mint_token_accounts(config, token)?;
+        let (after_slot, after_data) = get_mint()?;
+        if before_data == after_data && before_slot <= slot && slot <= after_slot {
+            let mint =
+                Mint::unpack(&after_data).map_err(|_| format!("Invalid token: {}", token))?;
+            return Ok((slot, mint, accounts));
+        }
+    }
+    Err(format!(
+        "{} kept changing while its accounts were read, try again later",
+        token
+    )
+    .into())
+}
+
+fn command_snapshot(config: &Config, token: Pubkey, output_file: &str) -> CommandResult {
+    let (slot, mint, accounts) = get_mint_and_token_accounts(config, &token)?;
+    let snapshot = Snapshot::new(token, slot, mint.decimals, accounts);
+    snapshot.write(output_file)?;
+
+    let cli_snapshot = CliSnapshot {
+        mint: token.to_string(),
+        slot,
+        holders: snapshot.holders.len(),
+        total: hpl_token::amount_to_ui_amount(snapshot.total(), mint.decimals),
+        supply: hpl_token::amount_to_ui_amount(mint.supply, mint.decimals),
+        root: snapshot.tree().root().to_string(),
+        file: output_file.to_string(),
+    };
+    println!("{}", format_output(cli_snapshot, "snapshot", config));
+    Ok(None)
+}
+
+fn command_snapshot_proof(config: &Config, snapshot_file: &str, owner: Pubkey) -> CommandResult {
+    let snapshot = Snapshot::read(snapshot_file)?;
+    let (index, amount, proof) = snapshot
+        .proof(&owner)
+        .ok_or_else(|| format!("{} holds no {} in the snapshot", owner, snapshot.mint))?;
+
+    let cli_snapshot_proof = CliSnapshotProof {
+        mint: snapshot.mint.to_string(),
+        slot: snapshot.slot,
+        index,
+        owner: owner.to_string(),
+        balance: hpl_token::amount_to_ui_amount(amount, snapshot.decimals),
+        amount: amount.to_string(),
+        root: snapshot.tree().root().to_string(),
+        proof: proof.iter().map(|hash| hash.to_string()).collect(),
+    };
+    println!(
+        "{}",
+        format_output(cli_snapshot_proof, "snapshot-proof", config)
+    );
+    Ok(None)
+}
+
+fn command_verify_snapshot_proof(
+    config: &Config,
+    root: Hash,
+    index: u64,
+    owner: Pubkey,
+    amount: u128,
+    proof: Vec<Hash>,
+) -> CommandResult {
+    let verification = CliSnapshotProofVerification {
+        index,
+        owner: owner.to_string(),
+        amount: amount.to_string(),
+        root: root.to_string(),
+        valid: snapshot::verify_balance(&root, index, &owner, amount, &proof),
+    };
+    if verification.valid {
+        println!(
+            "{}",
+            format_output(verification, "verify-snapshot-proof", config)
+        );
+        Ok(None)
+    } else {
+        let message = verification.to_string();
+        if let OutputFormat::Json | OutputFormat::JsonCompact = config.output_format {
+            println!(
+                "{}",
+                format_output(verification, "verify-snapshot-proof", config)
+            );
+        }
+        Err(message.into())
+    }
+}
+
+fn command_holders(config: &Config, token: Pubkey, top: usize, csv: bool) -> CommandResult {
+    let (slot, mint, accounts) = get_mint_and_token_accounts(config, &token)?;
+    let account_count = accounts.len();
+    let accounts_total: u128 = accounts.iter().map(|account| account.amount).sum();
+    let distribution =
+        Distribution::new(Snapshot::new(token, slot, mint.decimals, accounts).holders);
+    let ui_amount = |amount| hpl_token::amount_to_ui_amount(amount, mint.decimals);
+
+    let buckets = if distribution.holders.is_empty() {
+        vec![]
+    } else {
+        holders::PERCENTILES
+            .iter()
+            .map(|percentile| {
+                let (holders, amount) = distribution.bucket(*percentile);
+                CliHolderBucket {
+                    percentile: *percentile,
+                    holders,
+                    balance: ui_amount(amount),
+                    share: distribution.share(amount),
+                }
+            })
+            .collect()
+    };
+    // The CSV export lists every holder
+    let top = if csv { distribution.holders.len() } else { top };
+    let top_holders = distribution
+        .holders
+        .iter()
+        .take(top)
+        .enumerate()
+        .map(|(i, (owner, amount))| CliHolder {
+            rank: i + 1,
+            owner: owner.to_string(),
+            balance: ui_amount(*amount),
+            amount: amount.to_string(),
+            share: distribution.share(*amount),
+        })
+        .collect();
+
+    let cli_holders = CliHolders {
+        mint: token.to_string(),
+        slot,
+        decimals: mint.decimals,
+        holder_count: distribution.holders.len(),
+        account_count,
+        total: ui_amount(distribution.total),
+        supply: ui_amount(mint.supply),
+        supply_matches: accounts_total == mint.supply,
+        gini: distribution.gini(),
+        buckets,
+        top_holders,
+    };
+    if csv {
+        print!("{}", cli_holders.to_csv());
+    } else {
+        println!("{}", format_output(cli_holders, "holders", config));
+    }
+    Ok(None)
+}
+
+fn command_tx_sign(config: &Config, path: &Path, signers: Vec<Box<dyn Signer>>) -> CommandResult {
+    let mut transaction_file = TransactionFile::read(path)?;
+    for signer in &signers {
+        let pubkey = signer.pubkey();
+        if !transaction_file.signers().contains(&pubkey) {
+            return Err(format!("{} is not a signer of the transaction", pubkey).into());
+        }
+    }
+
+    let signers = signers
+        .iter()
+        .map(|signer| signer.as_ref())
+        .collect::<Vec<_>>();
+    // Signing with another blockhash would drop the signatures collected so far
+    let recent_blockhash = transaction_file.transaction.message.recent_blockhash;
+    transaction_file
+        .transaction
+        .try_partial_sign(&signers, recent_blockhash)?;
+    transaction_file.write(path)?;
+
+    println!(
+        "{}",
+        format_output(
+            CliTransactionFile::new(path, &transaction_file),
+            "tx-sign",
+            config
+        )
+    );
+    Ok(None)
+}
+
+fn command_tx_submit(config: &Config, path: &Path, no_wait: bool) -> CommandResult {
+    let transaction_file = TransactionFile::read(path)?;
+    let missing_signers = transaction_file.missing_signers();
+    if !missing_signers.is_empty() {
+        return Err(format!(
+            "Transaction is missing signatures from {}",
+            missing_signers
+                .iter()
+                .map(|signer| signer.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into());
+    }
+
+    let signature = if no_wait {
+        config
+            .rpc_client
+            .send_transaction(&transaction_file.transaction)?
+    } else {
+        config
+            .rpc_client
+            .send_and_confirm_transaction_with_spinner(&transaction_file.transaction)?
+    };
+    println!(
+        "{}",
+        format_output(
+            CliSignature {
+                signature: signature.to_string(),
+            },
+            "tx-submit",
+            config
+        )
+    );
+    Ok(None)
+}
+
 struct SignOnlyNeedsFullMintSpec {}
 impl offline::ArgsConfig for SignOnlyNeedsFullMintSpec {
     fn sign_only_arg<'a, 'b>(&self, arg: Arg<'a, 'b>) -> Arg<'a, 'b> {
@@ -1566,7 +3246,9 @@ impl offline::ArgsConfig for SignOnlyNeedsDelegateAddress {
 fn main() {
     let default_decimals = &format!("{}", native_mint::DECIMALS);
     let mut no_wait = false;
-    let app_matches = App::new(crate_name!())
+    // Account created by the command, reported with the transaction result
+    let mut created_address = None;
+    let app = App::new(crate_name!())
         .about(crate_description!())
         .version(crate_version!())
         .setting(AppSettings::SubcommandRequiredElseHelp)
@@ -1592,6 +3274,7 @@ fn main() {
                 .global(true)
                 .help("Show additional information"),
         )
+        .arg(output_format_arg().global(true))
         .arg(
             Arg::with_name("json_rpc_url")
                 .short("u")
@@ -1678,6 +3361,24 @@ fn main() {
                         .takes_value(true)
                         .help("Specify text that should be written as a memo when the token is created"),
                 )
+                .arg(
+                    Arg::with_name("spec")
+                        .long("spec")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with_all(&["mint_authority", "enable_freeze", "symbol", "name", "icon"])
+                        .help(
+                            "Create the token from a TOML or JSON spec file holding its decimals, \
+                             authorities, metadata, initial supply and recipients"
+                        ),
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .takes_value(false)
+                        .conflicts_with(SIGN_ONLY_ARG.name)
+                        .help("Print the accounts, rent and fees the token needs without creating it"),
+                )
                 .nonce_args(true)
                 .offline_args(),
         )
@@ -1732,10 +3433,20 @@ fn main() {
                         .index(2)
                         .required(true)
                         .min_values(MIN_SIGNERS as u64)
-                        .max_values(MAX_SIGNERS as u64)
+                        .max_values(MAX_SIGNERS_V2 as u64)
                         .help(&format!("The public keys for each of the N \
                             signing members of this account. [{} <= N <= {}]",
-                            MIN_SIGNERS, MAX_SIGNERS,
+                            MIN_SIGNERS, MAX_SIGNERS_V2,
+                        )),
+                )
+                .arg(
+                    Arg::with_name("v2")
+                        .long("v2")
+                        .takes_value(false)
+                        .help(&format!("Create a resizable multisig v2 account, whose signers \
+                            can be updated with `hpl-token update-multisig`. \
+                            Implied by more than {} members",
+                            MAX_SIGNERS,
                         )),
                 )
                 .arg(
@@ -1810,44 +3521,88 @@ fn main() {
                         .hidden(true)
                         .help("Force re-authorize the wallet's associate token account. Don't use this flag"),
                 )
+                .arg(
+                    Arg::with_name("two_step")
+                        .long("two-step")
+                        .takes_value(false)
+                        .conflicts_with("disable")
+                        .help("Only propose the new authority. The transfer completes once the \
+                            new authority signs `hpl-token accept-authority`"),
+                )
                 .arg(multisig_signer_arg())
                 .nonce_args(true)
                 .offline_args(),
         )
         .subcommand(
-            SubCommand::with_name("transfer")
-                .about("Transfer tokens between accounts")
+            SubCommand::with_name("accept-authority")
+                .about("Accept an authority proposed with `authorize --two-step`")
                 .arg(
-                    Arg::with_name("token")
+                    Arg::with_name("address")
                         .validator(is_valid_pubkey)
                         .value_name("TOKEN_ADDRESS")
                         .takes_value(true)
                         .index(1)
                         .required(true)
-                        .help("Token to transfer"),
+                        .help("The address of the token or token account"),
                 )
                 .arg(
-                    Arg::with_name("amount")
-                        .validator(is_amount_or_all)
-                        .value_name("TOKEN_AMOUNT")
+                    Arg::with_name("authority_type")
+                        .value_name("AUTHORITY_TYPE")
                         .takes_value(true)
+                        .possible_values(&["mint", "freeze", "owner", "close", "meta"])
                         .index(2)
                         .required(true)
-                        .help("Amount to send, in tokens; accepts keyword ALL"),
+                        .help("The proposed authority type"),
                 )
                 .arg(
-                    Arg::with_name("recipient")
-                        .validator(is_valid_pubkey)
-                        .value_name("RECIPIENT_ADDRESS or RECIPIENT_TOKEN_ACCOUNT_ADDRESS")
+                    Arg::with_name("new_authority")
+                        .long("new-authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
                         .takes_value(true)
-                        .index(3)
-                        .required(true)
-                        .help("If a token account address is provided, use it as the recipient. \
-                               Otherwise assume the recipient address is a user wallet and transfer to \
-                               the associated token account")
+                        .help(
+                            "Specify the proposed authority keypair. \
+                             Defaults to the client keypair."
+                        ),
                 )
-                .arg(
-                    Arg::with_name("from")
+                .arg(multisig_signer_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("transfer")
+                .about("Transfer tokens between accounts")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("Token to transfer"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .validator(is_amount_or_all)
+                        .value_name("TOKEN_AMOUNT")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("Amount to send, in tokens; accepts keyword ALL"),
+                )
+                .arg(
+                    Arg::with_name("recipient")
+                        .validator(is_valid_pubkey)
+                        .value_name("RECIPIENT_ADDRESS or RECIPIENT_TOKEN_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .index(3)
+                        .required(true)
+                        .help("If a token account address is provided, use it as the recipient. \
+                               Otherwise assume the recipient address is a user wallet and transfer to \
+                               the associated token account")
+                )
+                .arg(
+                    Arg::with_name("from")
                         .validator(is_valid_pubkey)
                         .value_name("SENDER_TOKEN_ACCOUNT_ADDRESS")
                         .takes_value(true)
@@ -1898,6 +3653,73 @@ fn main() {
                 .nonce_args(true)
                 .offline_args_config(&SignOnlyNeedsMintDecimals{}),
         )
+        .subcommand(
+            SubCommand::with_name("distribute")
+                .about("Transfer tokens to every recipient of a CSV distribution file")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("Token to distribute"),
+                )
+                .arg(
+                    Arg::with_name("input")
+                        .long("input")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("CSV file of `recipient,amount` rows, with amounts in tokens. \
+                               Recipients are paid like with `transfer`: a token account \
+                               address is paid directly, a wallet address through its \
+                               associated token account"),
+                )
+                .arg(
+                    Arg::with_name("ledger")
+                        .long("ledger")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .help("File recording the transactions of the distribution. \
+                               Running the command again with the same ledger only pays \
+                               the rows that were not confirmed yet \
+                               [default: the input file path with a .ledger extension]"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .validator(is_valid_pubkey)
+                        .value_name("SENDER_TOKEN_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .long("from")
+                        .help("Specify the sending token account \
+                            [default: owner's associated token account]")
+                )
+                .arg(owner_keypair_arg_with_value_name("SENDER_TOKEN_OWNER_KEYPAIR")
+                        .help(
+                            "Specify the owner of the sending token account. \
+                            This may be a keypair file, the ASK keyword. \
+                            Defaults to the client keypair.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("fund_recipient")
+                        .long("fund-recipient")
+                        .takes_value(false)
+                        .help("Create the associated token account of the recipients that don't have one")
+                )
+                .arg(
+                    Arg::with_name("batch_size")
+                        .long("batch-size")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value("6")
+                        .help("Number of transfers packed in each transaction"),
+                )
+                .arg(multisig_signer_arg())
+                .nonce_pool_args(),
+        )
         .subcommand(
             SubCommand::with_name("burn")
                 .about("Burn tokens from an account")
@@ -2229,7 +4051,8 @@ fn main() {
                         .conflicts_with("token")
                         .help("Specify the token account to query \
                             [default: owner's associated token account]"),
-                ),
+                )
+                .watch_args(),
         )
         .subcommand(
             SubCommand::with_name("supply")
@@ -2256,6 +4079,44 @@ fn main() {
                         .help("Limit results to the given token. [Default: list accounts for all tokens]"),
                 )
                 .arg(owner_address_arg())
+                .watch_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("history")
+                .about("List the transactions of a token account and its balance changes")
+                .arg(
+                    Arg::with_name("address")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The token account address"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value("25")
+                        .help("Maximum number of transactions to list"),
+                )
+                .arg(
+                    Arg::with_name("before")
+                        .long("before")
+                        .value_name("SIGNATURE")
+                        .takes_value(true)
+                        .validator(is_parsable::<Signature>)
+                        .help("Start listing from the transaction preceding this one"),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .takes_value(false)
+                        .conflicts_with("output_format")
+                        .help("Print the transactions as CSV"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("address")
@@ -2323,85 +4184,639 @@ fn main() {
                 ),
         )
         .subcommand(
-            SubCommand::with_name("gc")
-                .about("Cleanup unnecessary token accounts")
-                .arg(owner_keypair_arg())
-        )
-        .subcommand(
-            SubCommand::with_name("sync-native")
-                .about("Sync a native HC token account to its underlying lamports")
+            SubCommand::with_name("update-multisig")
+                .about("Replace the signers of a multisig account, converting it to a multisig v2")
+                .arg(
+                    Arg::with_name("address")
+                        .validator(is_valid_pubkey)
+                        .value_name("MULTISIG_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the multisig account to update"),
+                )
+                .arg(
+                    Arg::with_name("minimum_signers")
+                        .value_name("MINIMUM_SIGNERS")
+                        .validator(is_multisig_minimum_signers)
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help(&format!("The new minimum number of signers required \
+                            to allow the operation. [{} <= M <= N]",
+                            MIN_SIGNERS,
+                        )),
+                )
+                .arg(
+                    Arg::with_name("multisig_member")
+                        .value_name("MULTISIG_MEMBER_PUBKEY")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .index(3)
+                        .required(true)
+                        .min_values(MIN_SIGNERS as u64)
+                        .max_values(MAX_SIGNERS_V2 as u64)
+                        .help(&format!("The public keys for each of the N \
+                            new signing members of this account. [{} <= N <= {}]",
+                            MIN_SIGNERS, MAX_SIGNERS_V2,
+                        )),
+                )
+                .arg(multisig_signer_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("gc")
+                .about("Cleanup unnecessary token accounts")
+                .arg(owner_keypair_arg())
+                .arg(
+                    Arg::with_name("burn_dust_below")
+                        .long("burn-dust-below")
+                        .value_name("TOKEN_AMOUNT")
+                        .takes_value(true)
+                        .validator(is_amount)
+                        .help("Burn balances smaller than this amount instead of \
+                               moving them into the associated token account"),
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .takes_value(false)
+                        .help("Print what would be closed and recovered without sending anything"),
+                )
+                .nonce_args(true)
+                .nonce_pool_args()
+                .offline_args()
+        )
+        .subcommand(
+            SubCommand::with_name("sync-native")
+                .about("Sync a native HC token account to its underlying lamports")
+                .arg(
+                    owner_address_arg()
+                        .index(1)
+                        .conflicts_with("address")
+                        .help("Owner of the associated account for the native token. \
+                               To query a specific account, use the `--address` parameter instead. \
+                               Defaults to the client keypair."),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .long("address")
+                        .conflicts_with("owner")
+                        .help("Specify the specific token account address to sync"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("token-info")
+                .about("Query details of an HPL Token")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .help("Token address."),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("update-token-info")
+                .about("Update info of an HPL Token")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .help("Token address."),
+                )
+                .arg(
+                    Arg::with_name("meta_authority")
+                    .long("meta-authority")
+                    .alias("owner")
+                    .value_name("KEYPAIR")
+                    .validator(is_valid_signer)
+                    .takes_value(true)
+                    .help(
+                        "Specify the mint meta data update authority address. \
+                         Defaults to the client keypair address."
+                    ),
+                )
+                .arg(
+                    Arg::with_name("symbol")
+                        .long("symbol")
+                        .takes_value(true)
+                        .help("Specify the symbol for token to update"),
+                ).arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("Specify the name for token to update"),
+                )
+                .arg(
+                    Arg::with_name("icon")
+                        .long("icon")
+                        .takes_value(true)
+                        .help("Specify the icon for token to update"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create-group")
+                .about("Create a token group for a mint, so other mints can join it")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The mint of the group"),
+                )
+                .arg(
+                    Arg::with_name("max_size")
+                        .long("max-size")
+                        .validator(is_parsable::<u32>)
+                        .value_name("MAX_SIZE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The maximum number of members of the group"),
+                )
+                .arg(
+                    Arg::with_name("mint_authority")
+                        .long("mint-authority")
+                        .alias("owner")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the mint authority keypair of the group mint. \
+                             This may be a keypair file or the ASK keyword. \
+                             Defaults to the client keypair."
+                        ),
+                )
+                .arg(
+                    Arg::with_name("update_authority")
+                        .long("update-authority")
+                        .value_name("ADDRESS")
+                        .validator(is_valid_pubkey)
+                        .takes_value(true)
+                        .help(
+                            "Specify the group update authority address. \
+                             Defaults to the client keypair address."
+                        ),
+                )
+                .arg(
+                    Arg::with_name("no_update_authority")
+                        .long("no-update-authority")
+                        .takes_value(false)
+                        .conflicts_with("update_authority")
+                        .help(
+                            "Create the group without an update authority, so no \
+                             member can be added and its max size is fixed"
+                        ),
+                )
+                .arg(multisig_signer_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("add-group-member")
+                .about("Add a mint to a token group")
+                .arg(
+                    Arg::with_name("group_token")
+                        .validator(is_valid_pubkey)
+                        .value_name("GROUP_TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The mint of the group"),
+                )
+                .arg(
+                    Arg::with_name("member_token")
+                        .validator(is_valid_pubkey)
+                        .value_name("MEMBER_TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("The mint to add to the group"),
+                )
+                .arg(
+                    Arg::with_name("mint_authority")
+                        .long("mint-authority")
+                        .alias("owner")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the mint authority keypair of the member mint. \
+                             This may be a keypair file or the ASK keyword. \
+                             Defaults to the client keypair."
+                        ),
+                )
+                .arg(
+                    Arg::with_name("update_authority")
+                        .long("update-authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the group update authority keypair. \
+                             This may be a keypair file or the ASK keyword. \
+                             Defaults to the client keypair."
+                        ),
+                )
+                .arg(multisig_signer_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("update-group-max-size")
+                .about("Update the maximum number of members of a token group")
+                .arg(
+                    Arg::with_name("group_token")
+                        .validator(is_valid_pubkey)
+                        .value_name("GROUP_TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The mint of the group"),
+                )
+                .arg(
+                    Arg::with_name("max_size")
+                        .validator(is_parsable::<u32>)
+                        .value_name("MAX_SIZE")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("The new maximum number of members of the group"),
+                )
+                .arg(
+                    Arg::with_name("update_authority")
+                        .long("update-authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the group update authority keypair. \
+                             This may be a keypair file or the ASK keyword. \
+                             Defaults to the client keypair."
+                        ),
+                )
+                .arg(multisig_signer_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
+        .subcommand(
+            SubCommand::with_name("group-info")
+                .about("Query details and members of a token group")
+                .arg(
+                    Arg::with_name("group_token")
+                        .validator(is_valid_pubkey)
+                        .value_name("GROUP_TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The mint of the group"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot")
+                .about("Dump the balances of every holder of a token into a snapshot file")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The token to snapshot"),
+                )
+                .arg(
+                    Arg::with_name("output_file")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("Path of the snapshot file to write"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("holders")
+                .about("Rank the holders of a token and summarize how its supply is distributed")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The token to list the holders of"),
+                )
+                .arg(
+                    Arg::with_name("top")
+                        .long("top")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value("10")
+                        .help("Number of largest holders to list"),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .takes_value(false)
+                        .conflicts_with("output_format")
+                        .help("Print every holder as CSV"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot-proof")
+                .about("Produce the Merkle inclusion proof of a holder's balance in a snapshot")
                 .arg(
-                    owner_address_arg()
+                    Arg::with_name("snapshot_file")
+                        .value_name("FILE")
+                        .takes_value(true)
                         .index(1)
-                        .conflicts_with("address")
-                        .help("Owner of the associated account for the native token. \
-                               To query a specific account, use the `--address` parameter instead. \
-                               Defaults to the client keypair."),
+                        .required(true)
+                        .help("Path of the snapshot file"),
                 )
                 .arg(
-                    Arg::with_name("address")
+                    Arg::with_name("owner")
                         .validator(is_valid_pubkey)
-                        .value_name("TOKEN_ACCOUNT_ADDRESS")
+                        .value_name("OWNER_ADDRESS")
                         .takes_value(true)
-                        .long("address")
-                        .conflicts_with("owner")
-                        .help("Specify the specific token account address to sync"),
+                        .index(2)
+                        .required(true)
+                        .help("The holder to prove the balance of"),
                 ),
         )
         .subcommand(
-            SubCommand::with_name("token-info")
-                .about("Query details of an HPL Token")
+            SubCommand::with_name("verify-snapshot-proof")
+                .about("Check a holder's balance against a snapshot Merkle root")
                 .arg(
-                    Arg::with_name("token")
-                        .validator(is_valid_pubkey)
-                        .value_name("TOKEN_ADDRESS")
+                    Arg::with_name("root")
+                        .validator(is_parsable::<Hash>)
+                        .value_name("MERKLE_ROOT")
                         .takes_value(true)
                         .index(1)
-                        .help("Token address."),
+                        .required(true)
+                        .help("The Merkle root of the snapshot"),
                 )
-        )
-        .subcommand(
-            SubCommand::with_name("update-token-info")
-                .about("Update info of an HPL Token")
                 .arg(
-                    Arg::with_name("token")
+                    Arg::with_name("owner")
                         .validator(is_valid_pubkey)
-                        .value_name("TOKEN_ADDRESS")
+                        .value_name("OWNER_ADDRESS")
                         .takes_value(true)
-                        .index(1)
-                        .help("Token address."),
+                        .index(2)
+                        .required(true)
+                        .help("The holder"),
                 )
                 .arg(
-                    Arg::with_name("meta_authority")
-                    .long("meta-authority")
-                    .alias("owner")
-                    .value_name("KEYPAIR")
-                    .validator(is_valid_signer)
-                    .takes_value(true)
-                    .help(
-                        "Specify the mint meta data update authority address. \
-                         Defaults to the client keypair address."
-                    ),
+                    Arg::with_name("amount")
+                        .validator(is_parsable::<u128>)
+                        .value_name("RAW_AMOUNT")
+                        .takes_value(true)
+                        .index(3)
+                        .required(true)
+                        .help("The balance of the holder, in base units"),
                 )
                 .arg(
-                    Arg::with_name("symbol")
-                        .long("symbol")
-                        .takes_value(true)
-                        .help("Specify the symbol for token to update"),
-                ).arg(
-                    Arg::with_name("name")
-                        .long("name")
+                    Arg::with_name("index")
+                        .long("index")
+                        .validator(is_parsable::<u64>)
+                        .value_name("INDEX")
                         .takes_value(true)
-                        .help("Specify the name for token to update"),
+                        .required(true)
+                        .help("The position of the holder in the snapshot, as given by `snapshot-proof`"),
                 )
                 .arg(
-                    Arg::with_name("icon")
-                        .long("icon")
+                    Arg::with_name("proof")
+                        .long("proof")
+                        .validator(is_parsable::<Hash>)
+                        .value_name("HASH")
                         .takes_value(true)
-                        .help("Specify the icon for token to update"),
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .help("The sibling hashes of the proof, in order"),
                 ),
         )
-        .get_matches();
+        .subcommand(
+            SubCommand::with_name("nonce-pool")
+                .about("Manage a pool of durable nonce accounts for bulk commands")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("Create nonce accounts and write them to a pool file")
+                        .after_help(
+                            "The accounts are derived from the fee payer, which pays \
+                             their rent, so the fee payer must be able to sign.\n\n\
+                             Bulk commands given --nonce-pool use one account of the \
+                             pool per transaction, continuing from the account after \
+                             the last one signed with. When the transactions are signed \
+                             offline or sent without waiting, the pool needs an \
+                             account for each transaction not yet sent.\n\n\
+                             Running the command again with the same pool file creates \
+                             the accounts an earlier run failed to create.",
+                        )
+                        .arg(
+                            Arg::with_name("pool_file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("File to write the pool to"),
+                        )
+                        .arg(
+                            Arg::with_name("count")
+                                .value_name("NUMBER")
+                                .takes_value(true)
+                                .index(2)
+                                .required(true)
+                                .validator(is_parsable::<usize>)
+                                .help("Number of nonce accounts to create"),
+                        )
+                        .arg(
+                            Arg::with_name("authority")
+                                .long("authority")
+                                .value_name("ADDRESS")
+                                .takes_value(true)
+                                .validator(is_valid_pubkey)
+                                .help("Nonce authority of the accounts. Defaults to the client keypair."),
+                        )
+                        .arg(
+                            Arg::with_name("seed_prefix")
+                                .long("seed-prefix")
+                                .value_name("TEXT")
+                                .takes_value(true)
+                                .default_value("nonce-pool-")
+                                .help("Prefix of the seeds the accounts are derived with"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Show the nonce accounts of a pool and their current nonces")
+                        .arg(
+                            Arg::with_name("pool_file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("The pool file"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("advance")
+                        .about("Advance every nonce account of a pool")
+                        .after_help(
+                            "Transactions signed with the previous nonces can no longer land.",
+                        )
+                        .arg(
+                            Arg::with_name("pool_file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("The pool file"),
+                        )
+                        .arg(
+                            Arg::with_name("nonce_pool_authority")
+                                .long("nonce-pool-authority")
+                                .value_name("KEYPAIR")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("Nonce authority of the pool. Defaults to the client keypair."),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("tx")
+                .about("Collect the signatures of a transaction through a file")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("build")
+                        .about("Run a command in sign-only mode and write its transaction to a file")
+                        .after_help(
+                            "The command takes the usual sign-only arguments, such as \
+                             --blockhash or --nonce, and its signers may be given by \
+                             address. A durable nonce is recommended when collecting \
+                             signatures takes longer than a blockhash stays valid.\n\n\
+                             Example: hpl-token tx build transfer.json transfer TOKEN 10 \
+                             RECIPIENT --owner MULTISIG --multisig-signer SIGNER_1 \
+                             --multisig-signer SIGNER_2 --fee-payer FEE_PAYER \
+                             --blockhash BLOCKHASH"
+                        )
+                        .setting(AppSettings::TrailingVarArg)
+                        .arg(
+                            Arg::with_name("file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("File to write the transaction to"),
+                        )
+                        .arg(
+                            Arg::with_name("command")
+                                .value_name("COMMAND")
+                                .takes_value(true)
+                                .index(2)
+                                .multiple(true)
+                                .required(true)
+                                .allow_hyphen_values(true)
+                                .help("The command building the transaction, with its arguments"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("sign")
+                        .about("Add signatures to a transaction file")
+                        .arg(
+                            Arg::with_name("file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("The transaction file"),
+                        )
+                        .arg(
+                            Arg::with_name("signer")
+                                .long("signer")
+                                .value_name("SIGNER")
+                                .validator(is_valid_signer)
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .help(
+                                    "Keypair file or hardware wallet to sign with. \
+                                     May be specified multiple times. \
+                                     Defaults to the client keypair.",
+                                ),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("submit")
+                        .about("Send a fully signed transaction file")
+                        .arg(
+                            Arg::with_name("file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .index(1)
+                                .required(true)
+                                .help("The transaction file"),
+                        )
+                        .arg(
+                            Arg::with_name("no_wait")
+                                .long("no-wait")
+                                .takes_value(false)
+                                .help("Return signature immediately after submitting the transaction, instead of waiting for confirmations"),
+                        ),
+                ),
+        );
+
+    // `tx build` runs the command it wraps, in sign-only mode and with the
+    // global arguments given before it
+    let mut transaction_file_path = None;
+    let app_matches = {
+        let app_matches = app.clone().get_matches();
+        let command = match app_matches.subcommand() {
+            ("tx", Some(tx_matches)) => match tx_matches.subcommand() {
+                ("build", Some(build_matches)) => {
+                    transaction_file_path = build_matches.value_of("file").map(PathBuf::from);
+                    let mut args = vec![crate_name!().to_string()];
+                    for (name, long) in [
+                        ("config_file", "--config"),
+                        ("json_rpc_url", "--url"),
+                        ("fee_payer", "--fee-payer"),
+                        ("output_format", "--output"),
+                    ]
+                    .iter()
+                    {
+                        if build_matches.occurrences_of(name) > 0 {
+                            args.push(long.to_string());
+                            args.push(build_matches.value_of(name).unwrap().to_string());
+                        }
+                    }
+                    if build_matches.is_present("verbose") {
+                        args.push("--verbose".to_string());
+                    }
+                    args.extend(
+                        build_matches
+                            .values_of("command")
+                            .unwrap()
+                            .map(String::from),
+                    );
+                    args.push(format!("--{}", SIGN_ONLY_ARG.long));
+                    Some(args)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match command {
+            Some(command) => app.get_matches_from(command),
+            None => app_matches,
+        }
+    };
 
     let mut wallet_manager = None;
     let mut bulk_signers: Vec<Box<dyn Signer>> = Vec::new();
@@ -2409,6 +4824,12 @@ fn main() {
 
     let (sub_command, sub_matches) = app_matches.subcommand();
     let matches = sub_matches.unwrap();
+    // `tx` and `nonce-pool` have subcommands, which take the global arguments
+    // themselves
+    let matches = match matches.subcommand() {
+        (_, Some(tx_matches)) => tx_matches,
+        _ => matches,
+    };
 
     let config = {
         let cli_config = if let Some(config_file) = matches.value_of("config_file") {
@@ -2421,6 +4842,12 @@ fn main() {
                 .value_of("json_rpc_url")
                 .unwrap_or(&cli_config.json_rpc_url),
         );
+        let websocket_url =
+            if matches.is_present("json_rpc_url") || cli_config.websocket_url.is_empty() {
+                huione_cli_config::Config::compute_websocket_url(&json_rpc_url)
+            } else {
+                cli_config.websocket_url.clone()
+            };
 
         let (signer, fee_payer) = signer_from_path(
             matches,
@@ -2441,19 +4868,42 @@ fn main() {
         bulk_signers.push(signer);
 
         let verbose = matches.is_present("verbose");
+        let output_format = matches
+            .value_of("output_format")
+            .map(|value| match value {
+                "json" => OutputFormat::Json,
+                "json-compact" => OutputFormat::JsonCompact,
+                _ => unreachable!(),
+            })
+            .unwrap_or(if verbose {
+                OutputFormat::DisplayVerbose
+            } else {
+                OutputFormat::Display
+            });
 
         let nonce_account = pubkey_of_signer(matches, NONCE_ARG.name, &mut wallet_manager)
             .unwrap_or_else(|e| {
                 eprintln!("error: {}", e);
                 exit(1);
             });
-        let nonce_authority = if nonce_account.is_some() {
+        let nonce_pool = matches.value_of("nonce_pool").map(|pool_file| {
+            NoncePool::read(pool_file).unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                exit(1);
+            })
+        });
+        let nonce_authority_name = if nonce_pool.is_some() {
+            "nonce_pool_authority"
+        } else {
+            NONCE_AUTHORITY_ARG.name
+        };
+        let nonce_authority = if nonce_account.is_some() || nonce_pool.is_some() {
             let (signer, nonce_authority) = signer_from_path(
                 matches,
                 matches
-                    .value_of(NONCE_AUTHORITY_ARG.name)
+                    .value_of(nonce_authority_name)
                     .unwrap_or(&cli_config.keypair_path),
-                NONCE_AUTHORITY_ARG.name,
+                nonce_authority_name,
                 &mut wallet_manager,
             )
             .map(|s| {
@@ -2470,6 +4920,15 @@ fn main() {
         } else {
             None
         };
+        if let (Some(nonce_pool), Some(nonce_authority)) = (&nonce_pool, nonce_authority) {
+            if nonce_pool.authority != nonce_authority {
+                eprintln!(
+                    "error: The nonce authority of the pool is {}, not {}",
+                    nonce_pool.authority, nonce_authority
+                );
+                exit(1);
+            }
+        }
 
         let blockhash_query = BlockhashQuery::new_from_matches(matches);
         let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
@@ -2489,11 +4948,14 @@ fn main() {
 
         Config {
             rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+            websocket_url,
             verbose,
+            output_format,
             fee_payer,
             default_keypair_path: cli_config.keypair_path,
             nonce_account,
             nonce_authority,
+            nonce_pool,
             blockhash_query,
             sign_only,
             multisigner_pubkeys,
@@ -2504,28 +4966,58 @@ fn main() {
 
     let _ = match (sub_command, sub_matches) {
         ("create-token", Some(arg_matches)) => {
-            let decimals = value_t_or_exit!(arg_matches, "decimals", u8);
-            let mint_authority =
-                config.pubkey_or_default(arg_matches, "mint_authority", &mut wallet_manager);
-            let symbol = value_t!(arg_matches, "symbol", String).ok();
-            let name = value_t!(arg_matches, "name", String).ok();
-            let icon = value_t!(arg_matches, "icon", String).ok();
             let memo = value_t!(arg_matches, "memo", String).ok();
             let (token_signer, token) =
                 get_signer(arg_matches, "token_keypair", &mut wallet_manager)
                     .unwrap_or_else(new_throwaway_signer);
             bulk_signers.push(token_signer);
+            created_address = Some(token);
+
+            let spec = if let Some(spec_path) = arg_matches.value_of("spec") {
+                if arg_matches.occurrences_of("decimals") > 0 {
+                    eprintln!("error: --decimals cannot be used with --spec");
+                    exit(1);
+                }
+                let default_authority =
+                    config.pubkey_or_default(arg_matches, "owner", &mut wallet_manager);
+                TokenSpec::read(spec_path, default_authority).unwrap_or_else(|e| {
+                    eprintln!("error: {}", e);
+                    exit(1);
+                })
+            } else {
+                let mint_authority =
+                    config.pubkey_or_default(arg_matches, "mint_authority", &mut wallet_manager);
+                TokenSpec {
+                    decimals: value_t_or_exit!(arg_matches, "decimals", u8),
+                    mint_authority: Some(mint_authority),
+                    freeze_authority: if arg_matches.is_present("enable_freeze") {
+                        Some(mint_authority)
+                    } else {
+                        None
+                    },
+                    meta_authority: mint_authority,
+                    symbol: value_t!(arg_matches, "symbol", String).unwrap_or_default(),
+                    name: value_t!(arg_matches, "name", String).unwrap_or_default(),
+                    icon: value_t!(arg_matches, "icon", String).unwrap_or_default(),
+                    recipients: vec![],
+                }
+            };
+            let setup_authority = if spec.needs_setup_authority() {
+                let (signer, setup_authority) =
+                    config.signer_or_default(arg_matches, "owner", &mut wallet_manager);
+                bulk_signers.push(signer);
+                setup_authority
+            } else {
+                spec.meta_authority
+            };
 
             command_create_token(
                 &config,
-                decimals,
                 token,
-                mint_authority,
-                arg_matches.is_present("enable_freeze"),
-                symbol,
-                name,
-                icon,
+                spec,
+                setup_authority,
                 memo,
+                arg_matches.is_present("dry_run"),
             )
         }
         ("create-account", Some(arg_matches)) => {
@@ -2542,6 +5034,8 @@ fn main() {
             );
 
             let owner = config.pubkey_or_default(arg_matches, "owner", &mut wallet_manager);
+            created_address =
+                Some(account.unwrap_or_else(|| get_associated_token_address(&owner, &token)));
             command_create_account(&config, token, owner, account)
         }
         ("create-multisig", Some(arg_matches)) => {
@@ -2565,21 +5059,37 @@ fn main() {
                 .unwrap_or_else(new_throwaway_signer);
             bulk_signers.push(signer);
 
-            command_create_multisig(&config, account, minimum_signers, multisig_members)
+            let v2 = arg_matches.is_present("v2");
+            created_address = Some(account);
+            command_create_multisig(&config, account, minimum_signers, multisig_members, v2)
+        }
+        ("update-multisig", Some(arg_matches)) => {
+            let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let minimum_signers = value_of::<u8>(arg_matches, "minimum_signers").unwrap();
+            let multisig_members =
+                pubkeys_of_multiple_signers(arg_matches, "multisig_member", &mut wallet_manager)
+                    .unwrap_or_else(|e| {
+                        eprintln!("error: {}", e);
+                        exit(1);
+                    })
+                    .unwrap();
+            if minimum_signers as usize > multisig_members.len() {
+                eprintln!(
+                    "error: MINIMUM_SIGNERS cannot be greater than the number \
+                          of MULTISIG_MEMBERs passed"
+                );
+                exit(1);
+            }
+            command_update_multisig(&config, address, minimum_signers, multisig_members)
         }
         ("authorize", Some(arg_matches)) => {
             let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
                 .unwrap()
                 .unwrap();
-            let authority_type = arg_matches.value_of("authority_type").unwrap();
-            let authority_type = match authority_type {
-                "mint" => AuthorityType::MintTokens,
-                "freeze" => AuthorityType::FreezeAccount,
-                "owner" => AuthorityType::AccountOwner,
-                "close" => AuthorityType::CloseAccount,
-                "meta" => AuthorityType::UpdateMeta,
-                _ => unreachable!(),
-            };
+            let authority_type =
+                parse_authority_type(arg_matches.value_of("authority_type").unwrap());
 
             let (authority_signer, authority) =
                 config.signer_or_default(arg_matches, "authority", &mut wallet_manager);
@@ -2588,6 +5098,7 @@ fn main() {
             let new_authority =
                 pubkey_of_signer(arg_matches, "new_authority", &mut wallet_manager).unwrap();
             let force_authorize = arg_matches.is_present("force");
+            let two_step = arg_matches.is_present("two_step");
             command_authorize(
                 &config,
                 address,
@@ -2595,8 +5106,22 @@ fn main() {
                 authority,
                 new_authority,
                 force_authorize,
+                two_step,
             )
         }
+        ("accept-authority", Some(arg_matches)) => {
+            let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let authority_type =
+                parse_authority_type(arg_matches.value_of("authority_type").unwrap());
+
+            let (new_authority_signer, new_authority) =
+                config.signer_or_default(arg_matches, "new_authority", &mut wallet_manager);
+            bulk_signers.push(new_authority_signer);
+
+            command_accept_authority(&config, address, authority_type, new_authority)
+        }
         ("transfer", Some(arg_matches)) => {
             let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
                 .unwrap()
@@ -2634,6 +5159,35 @@ fn main() {
                 recipient_is_ata_owner,
             )
         }
+        ("distribute", Some(arg_matches)) => {
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let input = arg_matches.value_of("input").unwrap();
+            let ledger = arg_matches.value_of("ledger");
+            let sender = pubkey_of_signer(arg_matches, "from", &mut wallet_manager).unwrap();
+            let (owner_signer, owner) =
+                config.signer_or_default(arg_matches, "owner", &mut wallet_manager);
+            bulk_signers.push(owner_signer);
+            let fund_recipient = arg_matches.is_present("fund_recipient");
+            let batch_size = value_t_or_exit!(arg_matches, "batch_size", usize);
+
+            // The distribution sends its own transactions to record them in the ledger
+            let signer_info = CliSignerInfo {
+                signers: std::mem::take(&mut bulk_signers),
+            };
+            command_distribute(
+                &config,
+                &signer_info,
+                token,
+                input,
+                ledger,
+                sender,
+                owner,
+                fund_recipient,
+                batch_size,
+            )
+        }
         ("burn", Some(arg_matches)) => {
             let source = pubkey_of_signer(arg_matches, "source", &mut wallet_manager)
                 .unwrap()
@@ -2711,6 +5265,9 @@ fn main() {
             let (wallet_signer, wallet_address) =
                 config.signer_or_default(arg_matches, "wallet_keypair", &mut wallet_manager);
             bulk_signers.push(wallet_signer);
+            created_address = Some(account.unwrap_or_else(|| {
+                get_associated_token_address(&wallet_address, &native_mint::id())
+            }));
 
             command_wrap(&config, amount, wallet_address, account)
         }
@@ -2779,7 +5336,12 @@ fn main() {
                 "address",
                 &mut wallet_manager,
             );
-            command_balance(&config, address)
+            let mut alert = balance_alert_of(arg_matches);
+            if arg_matches.is_present("watch") {
+                command_watch_balance(&config, address, alert)
+            } else {
+                command_balance(&config, address, alert.as_mut())
+            }
         }
         ("supply", Some(arg_matches)) => {
             let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
@@ -2790,7 +5352,26 @@ fn main() {
         ("accounts", Some(arg_matches)) => {
             let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager).unwrap();
             let owner = config.pubkey_or_default(arg_matches, "owner", &mut wallet_manager);
-            command_accounts(&config, token, owner)
+            let mut alert = balance_alert_of(arg_matches);
+            if arg_matches.is_present("watch") {
+                command_watch_accounts(&config, token, owner, alert)
+            } else {
+                command_accounts(&config, token, owner, alert.as_mut())
+            }
+        }
+        ("history", Some(arg_matches)) => {
+            let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let limit = value_t_or_exit!(arg_matches, "limit", usize);
+            let before = value_t!(arg_matches, "before", Signature).ok();
+            command_history(
+                &config,
+                address,
+                limit,
+                before,
+                arg_matches.is_present("csv"),
+            )
         }
         ("address", Some(arg_matches)) => {
             let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager).unwrap();
@@ -2816,7 +5397,13 @@ fn main() {
                 config.signer_or_default(arg_matches, "owner", &mut wallet_manager);
             bulk_signers.push(owner_signer);
 
-            command_gc(&config, owner_address)
+            let burn_dust_below = arg_matches.value_of("burn_dust_below").map(String::from);
+            command_gc(
+                &config,
+                owner_address,
+                burn_dust_below,
+                arg_matches.is_present("dry_run"),
+            )
         }
         ("sync-native", Some(arg_matches)) => {
             let address = config.associated_token_address_for_token_or_override(
@@ -2859,33 +5446,178 @@ fn main() {
                 icon,
             )
         }
+        ("create-group", Some(arg_matches)) => {
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let max_size = value_t_or_exit!(arg_matches, "max_size", u32);
+            let (mint_authority_signer, mint_authority) =
+                config.signer_or_default(arg_matches, "mint_authority", &mut wallet_manager);
+            bulk_signers.push(mint_authority_signer);
+            let update_authority = if arg_matches.is_present("no_update_authority") {
+                None
+            } else {
+                Some(config.pubkey_or_default(arg_matches, "update_authority", &mut wallet_manager))
+            };
+
+            command_create_group(&config, token, mint_authority, update_authority, max_size)
+        }
+        ("add-group-member", Some(arg_matches)) => {
+            let group_token = pubkey_of_signer(arg_matches, "group_token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let member_token = pubkey_of_signer(arg_matches, "member_token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let (mint_authority_signer, mint_authority) =
+                config.signer_or_default(arg_matches, "mint_authority", &mut wallet_manager);
+            bulk_signers.push(mint_authority_signer);
+            let (update_authority_signer, update_authority) =
+                config.signer_or_default(arg_matches, "update_authority", &mut wallet_manager);
+            bulk_signers.push(update_authority_signer);
+
+            command_add_group_member(
+                &config,
+                group_token,
+                member_token,
+                mint_authority,
+                update_authority,
+            )
+        }
+        ("update-group-max-size", Some(arg_matches)) => {
+            let group_token = pubkey_of_signer(arg_matches, "group_token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let max_size = value_t_or_exit!(arg_matches, "max_size", u32);
+            let (update_authority_signer, update_authority) =
+                config.signer_or_default(arg_matches, "update_authority", &mut wallet_manager);
+            bulk_signers.push(update_authority_signer);
+
+            command_update_group_max_size(&config, group_token, update_authority, max_size)
+        }
+        ("group-info", Some(arg_matches)) => {
+            let group_token = pubkey_of_signer(arg_matches, "group_token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            command_group_info(&config, group_token)
+        }
+        ("snapshot", Some(arg_matches)) => {
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let output_file = arg_matches.value_of("output_file").unwrap();
+            command_snapshot(&config, token, output_file)
+        }
+        ("holders", Some(arg_matches)) => {
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let top = value_t_or_exit!(arg_matches, "top", usize);
+            command_holders(&config, token, top, arg_matches.is_present("csv"))
+        }
+        ("snapshot-proof", Some(arg_matches)) => {
+            let snapshot_file = arg_matches.value_of("snapshot_file").unwrap();
+            let owner = pubkey_of_signer(arg_matches, "owner", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            command_snapshot_proof(&config, snapshot_file, owner)
+        }
+        ("verify-snapshot-proof", Some(arg_matches)) => {
+            let root = value_t_or_exit!(arg_matches, "root", Hash);
+            let owner = pubkey_of_signer(arg_matches, "owner", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let amount = value_t_or_exit!(arg_matches, "amount", u128);
+            let index = value_t_or_exit!(arg_matches, "index", u64);
+            let proof = values_t!(arg_matches, "proof", Hash).unwrap_or_default();
+            command_verify_snapshot_proof(&config, root, index, owner, amount, proof)
+        }
+        ("nonce-pool", Some(arg_matches)) => match arg_matches.subcommand() {
+            ("create", Some(arg_matches)) => {
+                let pool_file = PathBuf::from(arg_matches.value_of("pool_file").unwrap());
+                let count = value_t_or_exit!(arg_matches, "count", usize);
+                let seed_prefix = arg_matches.value_of("seed_prefix").unwrap().to_string();
+                let authority =
+                    config.pubkey_or_default(arg_matches, "authority", &mut wallet_manager);
+                command_nonce_pool_create(&config, &pool_file, count, seed_prefix, authority)
+            }
+            ("list", Some(arg_matches)) => {
+                let pool_file = PathBuf::from(arg_matches.value_of("pool_file").unwrap());
+                command_nonce_pool_list(&config, &pool_file)
+            }
+            ("advance", Some(arg_matches)) => {
+                let pool_file = PathBuf::from(arg_matches.value_of("pool_file").unwrap());
+                let (authority_signer, authority) = config.signer_or_default(
+                    arg_matches,
+                    "nonce_pool_authority",
+                    &mut wallet_manager,
+                );
+                bulk_signers.push(authority_signer);
+                command_nonce_pool_advance(&config, &pool_file, authority)
+            }
+            _ => unreachable!(),
+        },
+        ("tx", Some(arg_matches)) => match arg_matches.subcommand() {
+            ("build", _) => Err("`tx build` cannot build another `tx` command".into()),
+            ("sign", Some(arg_matches)) => {
+                let path = PathBuf::from(arg_matches.value_of("file").unwrap());
+                let signer_paths = arg_matches
+                    .values_of("signer")
+                    .map(|paths| paths.collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec![config.default_keypair_path.as_str()]);
+                let signers = signer_paths
+                    .into_iter()
+                    .map(|signer_path| {
+                        signer_from_path(arg_matches, signer_path, "signer", &mut wallet_manager)
+                            .unwrap_or_else(|e| {
+                                eprintln!("error: {}", e);
+                                exit(1);
+                            })
+                    })
+                    .collect();
+                command_tx_sign(&config, &path, signers)
+            }
+            ("submit", Some(arg_matches)) => {
+                let path = PathBuf::from(arg_matches.value_of("file").unwrap());
+                command_tx_submit(&config, &path, arg_matches.is_present("no_wait"))
+            }
+            _ => unreachable!(),
+        },
         _ => unreachable!(),
     }
     .and_then(|transaction_info| {
         if let Some((minimum_balance_for_rent_exemption, instruction_batches)) = transaction_info {
-            let fee_payer = Some(&config.fee_payer);
             let signer_info = CliSignerInfo {
                 signers: bulk_signers,
             };
-
-            for instructions in instruction_batches {
-                let message = if let Some(nonce_account) = config.nonce_account.as_ref() {
-                    Message::new_with_nonce(
-                        instructions,
-                        fee_payer,
-                        nonce_account,
-                        config.nonce_authority.as_ref().unwrap(),
-                    )
-                } else {
-                    Message::new(&instructions, fee_payer)
-                };
-                let (recent_blockhash, fee_calculator) = config
-                    .blockhash_query
-                    .get_blockhash_and_fee_calculator(
-                        &config.rpc_client,
-                        config.rpc_client.commitment(),
+            if let Some(nonce_pool) = config.nonce_pool.as_ref() {
+                // A nonce account can't be reused before the transaction
+                // advancing it is confirmed
+                if (config.sign_only || no_wait)
+                    && instruction_batches.len() > nonce_pool.accounts.len()
+                {
+                    return Err(format!(
+                        "`{}` builds {} transactions but the nonce pool has only {} accounts",
+                        sub_command,
+                        instruction_batches.len(),
+                        nonce_pool.accounts.len()
                     )
-                    .unwrap_or_else(|e| {
+                    .into());
+                }
+            }
+            if transaction_file_path.is_some() && instruction_batches.len() > 1 {
+                return Err(format!(
+                    "`{}` builds {} transactions, a transaction file holds only one",
+                    sub_command,
+                    instruction_batches.len()
+                )
+                .into());
+            }
+
+            for (index, instructions) in instruction_batches.iter().enumerate() {
+                let message = new_message(&config, instructions, index);
+                let (recent_blockhash, fee_calculator) =
+                    get_blockhash_and_fee_calculator(&config, index).unwrap_or_else(|e| {
                         eprintln!("error: {}", e);
                         exit(1);
                     });
@@ -2900,11 +5632,31 @@ fn main() {
                 let signers = signer_info.signers_for_message(&message);
                 let mut transaction = Transaction::new_unsigned(message);
 
-                if config.sign_only {
+                let result = if config.sign_only {
                     transaction.try_partial_sign(&signers, recent_blockhash)?;
-                    println!("{}", return_signers(&transaction, &OutputFormat::Display)?);
+                    record_nonce_signed(&config, index)?;
+                    if let Some(path) = transaction_file_path.as_ref() {
+                        let transaction_file = TransactionFile {
+                            transaction,
+                            multisig_signers: multisigner_ids.clone(),
+                        };
+                        transaction_file.write(path)?;
+                        let cli_transaction_file = CliTransactionFile::new(path, &transaction_file);
+                        println!(
+                            "{}",
+                            format_output(cli_transaction_file, sub_command, &config)
+                        );
+                        continue;
+                    }
+                    CliTransactionResult::SignOnly(return_signers_data(
+                        &transaction,
+                        &ReturnSignersConfig {
+                            dump_transaction_message: false,
+                        },
+                    ))
                 } else {
                     transaction.try_sign(&signers, recent_blockhash)?;
+                    record_nonce_signed(&config, index)?;
                     let signature = if no_wait {
                         config.rpc_client.send_transaction(&transaction)?
                     } else {
@@ -2912,9 +5664,20 @@ fn main() {
                             .rpc_client
                             .send_and_confirm_transaction_with_spinner(&transaction)?
                     };
-                    println!("Signature: {}", signature);
-                }
+                    CliTransactionResult::Signature(CliSignature {
+                        signature: signature.to_string(),
+                    })
+                };
+                // Each transaction of a command is printed as its own document,
+                // which json-compact keeps on a single line
+                let cli_transaction = CliTransaction {
+                    address: created_address.map(|address| address.to_string()),
+                    result,
+                };
+                println!("{}", format_output(cli_transaction, sub_command, &config));
             }
+        } else if transaction_file_path.is_some() {
+            return Err(format!("`{}` doesn't build a transaction", sub_command).into());
         }
         Ok(())
     })
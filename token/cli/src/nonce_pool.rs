@@ -0,0 +1,160 @@
+use huione_sdk::{pubkey::Pubkey, system_program};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Longest seed an address derived with a seed may use, in bytes
+const MAX_SEED_LEN: usize = 32;
+
+/// Nonce accounts that bulk commands rotate through, one per transaction.
+///
+/// The accounts are derived from `base` with the seeds `<seed_prefix><index>`,
+/// so the pool file can be rebuilt from those alone. All of them share the
+/// same nonce authority.
+///
+/// The pool file also keeps a cursor on the next account to use. Each signed
+/// transaction moves it forward, so that commands signed offline or sent
+/// without waiting one after the other don't reuse the same nonce.
+pub struct NoncePool {
+    pub base: Pubkey,
+    pub seed_prefix: String,
+    pub authority: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    /// Account used by the first transaction of a command
+    pub cursor: usize,
+    /// File the pool was read from, where the cursor is saved
+    file: Option<PathBuf>,
+}
+
+impl NoncePool {
+    pub fn new(
+        base: Pubkey,
+        seed_prefix: String,
+        authority: Pubkey,
+        count: usize,
+    ) -> Result<Self, Error> {
+        let mut accounts = vec![];
+        for index in 0..count {
+            let seed = format!("{}{}", seed_prefix, index);
+            if seed.len() > MAX_SEED_LEN {
+                return Err(
+                    format!("Seed `{}` is longer than {} bytes", seed, MAX_SEED_LEN).into(),
+                );
+            }
+            accounts.push(Pubkey::create_with_seed(
+                &base,
+                &seed,
+                &system_program::id(),
+            )?);
+        }
+        Ok(Self {
+            base,
+            seed_prefix,
+            authority,
+            accounts,
+            cursor: 0,
+            file: None,
+        })
+    }
+
+    /// Seed of the `index`th account of the pool
+    pub fn seed(&self, index: usize) -> String {
+        format!("{}{}", self.seed_prefix, index)
+    }
+
+    /// Nonce account for the `index`th transaction of a command
+    pub fn account(&self, index: usize) -> Pubkey {
+        self.accounts[(self.cursor + index) % self.accounts.len()]
+    }
+
+    /// Saves that the `index`th transaction of the command was signed, moving
+    /// the cursor of the pool file past its nonce account
+    pub fn record_signed(&self, index: usize) -> Result<(), Error> {
+        match self.file.as_ref() {
+            Some(file) => {
+                self.write_with_cursor(file, (self.cursor + index + 1) % self.accounts.len())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let pool_error = |err: &str| format!("{}: {}", path.display(), err);
+        let value: Value = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| pool_error(&err.to_string()))?;
+        let pubkey = |name: &str| {
+            value[name]
+                .as_str()
+                .and_then(|pubkey| Pubkey::from_str(pubkey).ok())
+                .ok_or_else(|| pool_error(&format!("missing or invalid field `{}`", name)))
+        };
+        let base = pubkey("base")?;
+        let authority = pubkey("authority")?;
+        let seed_prefix = value["seedPrefix"]
+            .as_str()
+            .ok_or_else(|| pool_error("missing field `seedPrefix`"))?
+            .to_string();
+        let count = value["accounts"]
+            .as_array()
+            .ok_or_else(|| pool_error("missing field `accounts`"))?
+            .len();
+        if count == 0 {
+            return Err(pool_error("the pool has no accounts").into());
+        }
+        // Files written before the cursor existed start from the first account
+        let cursor = match &value["cursor"] {
+            Value::Null => 0,
+            cursor => cursor
+                .as_u64()
+                .map(|cursor| cursor as usize)
+                .filter(|cursor| *cursor < count)
+                .ok_or_else(|| pool_error("invalid field `cursor`"))?,
+        };
+
+        // Derive the accounts again so that an edited file can't point the pool
+        // at other accounts
+        let mut pool = Self::new(base, seed_prefix, authority, count)?;
+        for (account, listed) in pool
+            .accounts
+            .iter()
+            .zip(value["accounts"].as_array().unwrap())
+        {
+            if listed.as_str() != Some(account.to_string().as_str()) {
+                return Err(pool_error(&format!(
+                    "account {} does not derive from the base and seed prefix",
+                    listed
+                ))
+                .into());
+            }
+        }
+        pool.cursor = cursor;
+        pool.file = Some(path.to_path_buf());
+        Ok(pool)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_with_cursor(path, self.cursor)
+    }
+
+    fn write_with_cursor<P: AsRef<Path>>(&self, path: P, cursor: usize) -> Result<(), Error> {
+        let file = json!({
+            "base": self.base.to_string(),
+            "seedPrefix": self.seed_prefix,
+            "authority": self.authority.to_string(),
+            "accounts": self
+                .accounts
+                .iter()
+                .map(|account| account.to_string())
+                .collect::<Vec<_>>(),
+            "cursor": cursor,
+        });
+        fs::write(path, serde_json::to_string_pretty(&file)? + "\n")?;
+        Ok(())
+    }
+}
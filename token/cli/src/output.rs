@@ -0,0 +1,933 @@
+use crate::{config::Config, transaction_file::TransactionFile};
+use huione_account_decoder::parse_token::{UiTokenAccount, UiTokenAmount};
+use huione_cli_output::{
+    display::{unix_timestamp_to_string, writeln_name_value},
+    CliSignOnlyData, CliSignature, OutputFormat, QuietDisplay, VerboseDisplay,
+};
+use huione_sdk::{native_token::lamports_to_hc, pubkey::Pubkey};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommandOutput<T>
+where
+    T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    pub(crate) command_name: String,
+    pub(crate) command_output: T,
+}
+
+impl<T> Display for CommandOutput<T>
+where
+    T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.command_output, f)
+    }
+}
+
+impl<T> QuietDisplay for CommandOutput<T>
+where
+    T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    fn write_str(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        QuietDisplay::write_str(&self.command_output, w)
+    }
+}
+
+impl<T> VerboseDisplay for CommandOutput<T>
+where
+    T: Serialize + Display + QuietDisplay + VerboseDisplay,
+{
+    fn write_str(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        writeln_name_value(w, "Command: ", &self.command_name)?;
+        VerboseDisplay::write_str(&self.command_output, w)
+    }
+}
+
+/// Prints a progress or informational message, which only the display output
+/// formats show
+pub(crate) fn println_display(config: &Config, message: String) {
+    match config.output_format {
+        OutputFormat::Display | OutputFormat::DisplayVerbose => {
+            println!("{}", message);
+        }
+        _ => {}
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum CliTransactionResult {
+    Signature(CliSignature),
+    SignOnly(CliSignOnlyData),
+}
+
+/// Result of a transaction sent or signed by a command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTransaction {
+    /// Account created by the transaction, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) address: Option<String>,
+    #[serde(flatten)]
+    pub(crate) result: CliTransactionResult,
+}
+
+impl QuietDisplay for CliTransaction {}
+impl VerboseDisplay for CliTransaction {}
+
+impl fmt::Display for CliTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The created account is already part of the progress messages
+        match &self.result {
+            CliTransactionResult::Signature(signature) => write!(f, "{}", signature),
+            CliTransactionResult::SignOnly(sign_only_data) => write!(f, "{}", sign_only_data),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenAmount {
+    pub(crate) address: String,
+    #[serde(flatten)]
+    pub(crate) amount: UiTokenAmount,
+}
+
+impl QuietDisplay for CliTokenAmount {}
+
+impl VerboseDisplay for CliTokenAmount {
+    fn write_str(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(w, "ui amount: {}", self.amount.real_number_string_trimmed())?;
+        writeln!(w, "decimals: {}", self.amount.decimals)?;
+        write!(w, "amount: {}", self.amount.amount)
+    }
+}
+
+impl fmt::Display for CliTokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.amount.real_number_string_trimmed())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenAccount {
+    pub(crate) address: String,
+    pub(crate) is_associated: bool,
+    pub(crate) mint: String,
+    pub(crate) is_native: bool,
+    pub(crate) owner: String,
+    pub(crate) state: String,
+    pub(crate) token_amount: UiTokenAmount,
+    pub(crate) delegate: Option<String>,
+    pub(crate) delegated_amount: Option<UiTokenAmount>,
+    pub(crate) close_authority: Option<String>,
+}
+
+impl CliTokenAccount {
+    pub(crate) fn new(address: String, is_associated: bool, account: UiTokenAccount) -> Self {
+        Self {
+            address,
+            is_associated,
+            mint: account.mint,
+            is_native: account.is_native,
+            owner: account.owner,
+            state: format!("{:?}", account.state),
+            token_amount: account.token_amount,
+            delegate: account.delegate,
+            delegated_amount: account.delegated_amount,
+            close_authority: account.close_authority,
+        }
+    }
+}
+
+impl QuietDisplay for CliTokenAccount {}
+impl VerboseDisplay for CliTokenAccount {}
+
+impl fmt::Display for CliTokenAccount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        if self.is_associated {
+            writeln_name_value(f, "Address:", &self.address)?;
+        } else {
+            writeln_name_value(f, "Address:", &format!("{}  (Aux*)", self.address))?;
+        }
+        writeln_name_value(
+            f,
+            "Balance:",
+            &self.token_amount.real_number_string_trimmed(),
+        )?;
+        let mint = format!(
+            "{}{}",
+            self.mint,
+            if self.is_native { " (native)" } else { "" }
+        );
+        writeln_name_value(f, "Mint:", &mint)?;
+        writeln_name_value(f, "Owner:", &self.owner)?;
+        writeln_name_value(f, "State:", &self.state)?;
+        if let (Some(delegate), Some(allowance)) = (&self.delegate, &self.delegated_amount) {
+            writeln!(f, "Delegation:")?;
+            writeln_name_value(f, "  Delegate:", delegate)?;
+            writeln_name_value(f, "  Allowance:", &allowance.real_number_string_trimmed())?;
+        } else {
+            writeln_name_value(f, "Delegation:", "")?;
+        }
+        writeln_name_value(
+            f,
+            "Close authority:",
+            self.close_authority.as_deref().unwrap_or_default(),
+        )?;
+        if !self.is_associated {
+            writeln!(f)?;
+            writeln!(f, "* Please run `hpl-token gc` to clean up Aux accounts")?;
+        }
+        Ok(())
+    }
+}
+
+/// Token accounts of an owner. Only used by the JSON output formats, the display
+/// formats print a table.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenAccounts {
+    pub(crate) accounts: Vec<CliTokenAccount>,
+    pub(crate) unsupported_accounts: Vec<CliUnsupportedAccount>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliUnsupportedAccount {
+    pub(crate) address: String,
+    pub(crate) error: String,
+}
+
+impl QuietDisplay for CliTokenAccounts {}
+impl VerboseDisplay for CliTokenAccounts {}
+
+impl fmt::Display for CliTokenAccounts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for account in &self.accounts {
+            write!(f, "{}", account)?;
+        }
+        for account in &self.unsupported_accounts {
+            writeln!(f, "{:<44}  {}", account.address, account.error)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliWalletAddress {
+    pub(crate) wallet_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) associated_token_address: Option<String>,
+}
+
+impl QuietDisplay for CliWalletAddress {}
+impl VerboseDisplay for CliWalletAddress {}
+
+impl fmt::Display for CliWalletAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Wallet address: {}", self.wallet_address)?;
+        if let Some(associated_token_address) = &self.associated_token_address {
+            writeln!(f, "Associated token address: {}", associated_token_address)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliMultisig {
+    pub(crate) address: String,
+    pub(crate) version: u8,
+    pub(crate) m: u8,
+    pub(crate) n: u8,
+    pub(crate) signers: Vec<String>,
+}
+
+impl QuietDisplay for CliMultisig {}
+impl VerboseDisplay for CliMultisig {}
+
+impl fmt::Display for CliMultisig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Address:", &self.address)?;
+        writeln_name_value(f, "Version:", &self.version.to_string())?;
+        writeln_name_value(f, "M/N:", &format!("{}/{}", self.m, self.n))?;
+        writeln_name_value(f, "Signers:", " ")?;
+        let width = if self.n >= 9 { 4 } else { 3 };
+        for (i, signer) in self.signers.iter().enumerate() {
+            let title = format!("{1:>0$}:", width, i + 1);
+            writeln_name_value(f, &title, signer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenInfo {
+    pub(crate) address: String,
+    pub(crate) mint_meta: String,
+    pub(crate) mint_meta_authority: Option<String>,
+    pub(crate) symbol: String,
+    pub(crate) name: String,
+    pub(crate) icon: String,
+}
+
+impl QuietDisplay for CliTokenInfo {}
+impl VerboseDisplay for CliTokenInfo {}
+
+impl fmt::Display for CliTokenInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Token:", &self.address)?;
+        writeln_name_value(f, "Token Meta:", &self.mint_meta)?;
+        writeln_name_value(
+            f,
+            "Token Meta Authority:",
+            self.mint_meta_authority.as_deref().unwrap_or("None"),
+        )?;
+        writeln_name_value(f, "Token Symbol:", &self.symbol)?;
+        writeln_name_value(f, "Token Name:", &self.name)?;
+        writeln_name_value(f, "Token Icon:", &self.icon)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenGroup {
+    pub(crate) address: String,
+    pub(crate) mint: String,
+    pub(crate) update_authority: Option<String>,
+    pub(crate) size: u32,
+    pub(crate) max_size: u32,
+    pub(crate) members: Vec<CliTokenGroupMember>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenGroupMember {
+    pub(crate) member_number: u32,
+    pub(crate) mint: String,
+}
+
+impl QuietDisplay for CliTokenGroup {}
+impl VerboseDisplay for CliTokenGroup {}
+
+impl fmt::Display for CliTokenGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Group:", &self.address)?;
+        writeln_name_value(f, "Mint:", &self.mint)?;
+        writeln_name_value(
+            f,
+            "Update authority:",
+            self.update_authority.as_deref().unwrap_or("disabled"),
+        )?;
+        writeln_name_value(f, "Size:", &format!("{}/{}", self.size, self.max_size))?;
+        if self.members.is_empty() {
+            writeln_name_value(f, "Members:", "None")?;
+        } else {
+            writeln_name_value(f, "Members:", " ")?;
+            let width = self.members.len().to_string().len() + 1;
+            for member in &self.members {
+                let title = format!("{1:>0$}:", width, member.member_number);
+                writeln_name_value(f, &title, &member.mint)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliSnapshot {
+    pub(crate) mint: String,
+    pub(crate) slot: u64,
+    pub(crate) holders: usize,
+    pub(crate) total: String,
+    pub(crate) supply: String,
+    pub(crate) root: String,
+    pub(crate) file: String,
+}
+
+impl QuietDisplay for CliSnapshot {}
+impl VerboseDisplay for CliSnapshot {}
+
+impl fmt::Display for CliSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Mint:", &self.mint)?;
+        writeln_name_value(f, "Slot:", &self.slot.to_string())?;
+        writeln_name_value(f, "Holders:", &self.holders.to_string())?;
+        writeln_name_value(f, "Total:", &self.total)?;
+        writeln_name_value(f, "Supply:", &self.supply)?;
+        writeln_name_value(f, "Merkle root:", &self.root)?;
+        writeln_name_value(f, "Snapshot written to:", &self.file)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliSnapshotProof {
+    pub(crate) mint: String,
+    pub(crate) slot: u64,
+    pub(crate) index: u64,
+    pub(crate) owner: String,
+    pub(crate) balance: String,
+    pub(crate) amount: String,
+    pub(crate) root: String,
+    pub(crate) proof: Vec<String>,
+}
+
+impl QuietDisplay for CliSnapshotProof {}
+impl VerboseDisplay for CliSnapshotProof {}
+
+impl fmt::Display for CliSnapshotProof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Mint:", &self.mint)?;
+        writeln_name_value(f, "Slot:", &self.slot.to_string())?;
+        writeln_name_value(f, "Index:", &self.index.to_string())?;
+        writeln_name_value(f, "Owner:", &self.owner)?;
+        writeln_name_value(f, "Balance:", &self.balance)?;
+        writeln_name_value(f, "Amount:", &self.amount)?;
+        writeln_name_value(f, "Merkle root:", &self.root)?;
+        writeln_name_value(f, "Proof:", &self.proof.join(","))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliSnapshotProofVerification {
+    pub(crate) index: u64,
+    pub(crate) owner: String,
+    pub(crate) amount: String,
+    pub(crate) root: String,
+    pub(crate) valid: bool,
+}
+
+impl QuietDisplay for CliSnapshotProofVerification {}
+impl VerboseDisplay for CliSnapshotProofVerification {}
+
+impl fmt::Display for CliSnapshotProofVerification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.valid {
+            write!(
+                f,
+                "Proof is valid: {} holds {} in {}",
+                self.owner, self.amount, self.root
+            )
+        } else {
+            write!(
+                f,
+                "Proof is invalid: {} does not hold {} in {}",
+                self.owner, self.amount, self.root
+            )
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliHolders {
+    pub(crate) mint: String,
+    pub(crate) slot: u64,
+    pub(crate) decimals: u8,
+    pub(crate) holder_count: usize,
+    pub(crate) account_count: usize,
+    pub(crate) total: String,
+    pub(crate) supply: String,
+    /// Whether the balances of all token accounts add up to the mint's supply
+    pub(crate) supply_matches: bool,
+    pub(crate) gini: f64,
+    pub(crate) buckets: Vec<CliHolderBucket>,
+    pub(crate) top_holders: Vec<CliHolder>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliHolderBucket {
+    /// Top share of holders, in percent
+    pub(crate) percentile: u8,
+    pub(crate) holders: usize,
+    pub(crate) balance: String,
+    /// Share of the total balance, in percent
+    pub(crate) share: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliHolder {
+    pub(crate) rank: usize,
+    pub(crate) owner: String,
+    pub(crate) balance: String,
+    pub(crate) amount: String,
+    /// Share of the total balance, in percent
+    pub(crate) share: f64,
+}
+
+impl CliHolders {
+    pub(crate) fn to_csv(&self) -> String {
+        let mut csv = String::from("rank,owner,balance,amount,share\n");
+        for holder in &self.top_holders {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.6}\n",
+                holder.rank, holder.owner, holder.balance, holder.amount, holder.share
+            ));
+        }
+        csv
+    }
+}
+
+impl QuietDisplay for CliHolders {}
+impl VerboseDisplay for CliHolders {}
+
+impl fmt::Display for CliHolders {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Mint:", &self.mint)?;
+        writeln_name_value(f, "Slot:", &self.slot.to_string())?;
+        writeln_name_value(f, "Holders:", &self.holder_count.to_string())?;
+        writeln_name_value(f, "Token accounts:", &self.account_count.to_string())?;
+        writeln_name_value(f, "Total:", &self.total)?;
+        if self.supply_matches {
+            writeln_name_value(f, "Supply:", &self.supply)?;
+        } else {
+            writeln_name_value(
+                f,
+                "Supply:",
+                &format!("{} (does not match the total)", self.supply),
+            )?;
+        }
+        writeln_name_value(f, "Gini coefficient:", &format!("{:.4}", self.gini))?;
+        if self.top_holders.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<10}  {:>8}  {:>30}  {:>8}",
+            "Top", "Holders", "Balance", "Share"
+        )?;
+        for bucket in &self.buckets {
+            writeln!(
+                f,
+                "{:<10}  {:>8}  {:>30}  {:>7.2}%",
+                format!("{}%", bucket.percentile),
+                bucket.holders,
+                bucket.balance,
+                bucket.share
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>5}  {:<44}  {:>30}  {:>8}",
+            "Rank", "Owner", "Balance", "Share"
+        )?;
+        for holder in &self.top_holders {
+            writeln!(
+                f,
+                "{:>5}  {:<44}  {:>30}  {:>7.2}%",
+                holder.rank, holder.owner, holder.balance, holder.share
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliDistribution {
+    pub(crate) ledger: String,
+    pub(crate) already_paid: usize,
+    pub(crate) paid: usize,
+    pub(crate) signatures: Vec<String>,
+}
+
+impl QuietDisplay for CliDistribution {}
+impl VerboseDisplay for CliDistribution {}
+
+impl fmt::Display for CliDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Distributed {} payments", self.paid)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTokenPlan {
+    pub(crate) token: String,
+    pub(crate) decimals: u8,
+    pub(crate) mint_authority: Option<String>,
+    pub(crate) freeze_authority: Option<String>,
+    pub(crate) meta_authority: String,
+    pub(crate) symbol: String,
+    pub(crate) name: String,
+    pub(crate) icon: String,
+    pub(crate) supply: String,
+    pub(crate) accounts: Vec<CliPlannedAccount>,
+    pub(crate) transactions: usize,
+    /// Lamports
+    pub(crate) rent: u128,
+    /// Lamports
+    pub(crate) fees: u128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliPlannedAccount {
+    pub(crate) address: String,
+    pub(crate) kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) owner: Option<String>,
+    /// Tokens minted to the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) amount: Option<String>,
+    /// Lamports
+    pub(crate) rent: u128,
+}
+
+impl QuietDisplay for CliTokenPlan {}
+impl VerboseDisplay for CliTokenPlan {}
+
+impl fmt::Display for CliTokenPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let authority = |authority: &Option<String>| {
+            authority.clone().unwrap_or_else(|| "disabled".to_string())
+        };
+        writeln!(f, "Dry run, nothing was sent")?;
+        writeln!(f)?;
+        writeln_name_value(f, "Token:", &self.token)?;
+        writeln_name_value(f, "Decimals:", &self.decimals.to_string())?;
+        writeln_name_value(f, "Mint authority:", &authority(&self.mint_authority))?;
+        writeln_name_value(f, "Freeze authority:", &authority(&self.freeze_authority))?;
+        writeln_name_value(f, "Meta authority:", &self.meta_authority)?;
+        writeln_name_value(f, "Symbol:", &self.symbol)?;
+        writeln_name_value(f, "Name:", &self.name)?;
+        writeln_name_value(f, "Icon:", &self.icon)?;
+        writeln_name_value(f, "Initial supply:", &self.supply)?;
+        writeln!(f)?;
+        writeln!(f, "Accounts:")?;
+        for account in &self.accounts {
+            write!(
+                f,
+                "  {:<44}  {:<13}  {:>14} HC",
+                account.address,
+                account.kind,
+                lamports_to_hc(account.rent)
+            )?;
+            if let (Some(owner), Some(amount)) = (&account.owner, &account.amount) {
+                write!(f, "  {} tokens to {}", amount, owner)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+        writeln_name_value(f, "Transactions:", &self.transactions.to_string())?;
+        writeln_name_value(f, "Rent:", &format!("{} HC", lamports_to_hc(self.rent)))?;
+        writeln_name_value(f, "Fees:", &format!("{} HC", lamports_to_hc(self.fees)))?;
+        write!(f, "Total: {} HC", lamports_to_hc(self.rent + self.fees))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliGc {
+    pub(crate) dry_run: bool,
+    pub(crate) accounts: Vec<CliGcAccount>,
+    pub(crate) associated_accounts_created: usize,
+    pub(crate) transactions: usize,
+    /// Lamports returned by the closed accounts
+    pub(crate) rent_recovered: u128,
+    /// Lamports paid for the associated token accounts created
+    pub(crate) rent_spent: u128,
+    /// Lamports
+    pub(crate) fees: u128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliGcAccount {
+    pub(crate) address: String,
+    pub(crate) mint: String,
+    /// What happens to the account: "skip", or the steps taken such as
+    /// "transfer and close" or "burn and close"
+    pub(crate) action: String,
+    pub(crate) balance: String,
+    /// Lamports recovered by closing the account
+    pub(crate) rent: u128,
+    /// Why the account is skipped or left open
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) reason: Option<String>,
+}
+
+impl QuietDisplay for CliGc {}
+impl VerboseDisplay for CliGc {}
+
+impl fmt::Display for CliGc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.dry_run {
+            writeln!(f, "Dry run, nothing was sent")?;
+            writeln!(f)?;
+        }
+        writeln!(f, "Accounts:")?;
+        for account in &self.accounts {
+            write!(
+                f,
+                "  {:<44}  {:<18}  {:>14} HC  {} of {}",
+                account.address,
+                account.action,
+                lamports_to_hc(account.rent),
+                account.balance,
+                account.mint
+            )?;
+            if let Some(reason) = &account.reason {
+                write!(f, " ({})", reason)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+        writeln_name_value(f, "Transactions:", &self.transactions.to_string())?;
+        writeln_name_value(
+            f,
+            "Associated accounts created:",
+            &self.associated_accounts_created.to_string(),
+        )?;
+        writeln_name_value(
+            f,
+            "Rent recovered:",
+            &format!("{} HC", lamports_to_hc(self.rent_recovered)),
+        )?;
+        writeln_name_value(
+            f,
+            "Rent spent:",
+            &format!("{} HC", lamports_to_hc(self.rent_spent)),
+        )?;
+        writeln_name_value(f, "Fees:", &format!("{} HC", lamports_to_hc(self.fees)))?;
+        let cost = self.rent_spent + self.fees;
+        if self.rent_recovered >= cost {
+            write!(
+                f,
+                "Net recovered: {} HC",
+                lamports_to_hc(self.rent_recovered - cost)
+            )
+        } else {
+            write!(
+                f,
+                "Net recovered: -{} HC",
+                lamports_to_hc(cost - self.rent_recovered)
+            )
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliNoncePool {
+    pub(crate) file: String,
+    pub(crate) authority: String,
+    pub(crate) accounts: Vec<CliNoncePoolAccount>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliNoncePoolAccount {
+    pub(crate) address: String,
+    /// "ready" when the account can be used by the pool
+    pub(crate) status: String,
+    /// Current nonce of a ready account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) blockhash: Option<String>,
+}
+
+impl QuietDisplay for CliNoncePool {}
+impl VerboseDisplay for CliNoncePool {}
+
+impl fmt::Display for CliNoncePool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Pool file:", &self.file)?;
+        writeln_name_value(f, "Authority:", &self.authority)?;
+        writeln!(f)?;
+        writeln!(f, "{:<44}  Nonce / Status", "Address")?;
+        for account in &self.accounts {
+            writeln!(
+                f,
+                "{:<44}  {}",
+                account.address,
+                account.blockhash.as_ref().unwrap_or(&account.status)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTransactionFile {
+    pub(crate) file: String,
+    pub(crate) missing_signers: Vec<String>,
+    pub(crate) missing_multisig_signers: Vec<String>,
+}
+
+impl CliTransactionFile {
+    pub(crate) fn new(path: &Path, transaction_file: &TransactionFile) -> Self {
+        let to_strings = |pubkeys: Vec<Pubkey>| {
+            pubkeys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect::<Vec<_>>()
+        };
+        Self {
+            file: path.display().to_string(),
+            missing_signers: to_strings(transaction_file.missing_signers()),
+            missing_multisig_signers: to_strings(transaction_file.missing_multisig_signers()),
+        }
+    }
+}
+
+impl QuietDisplay for CliTransactionFile {}
+impl VerboseDisplay for CliTransactionFile {}
+
+impl fmt::Display for CliTransactionFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Transaction file: {}", self.file)?;
+        if self.missing_signers.is_empty() {
+            return write!(f, "All signatures collected, ready to submit");
+        }
+        write!(f, "Missing signatures:")?;
+        for signer in &self.missing_signers {
+            if self.missing_multisig_signers.contains(signer) {
+                write!(f, "\n  {} (multisig signer)", signer)?;
+            } else {
+                write!(f, "\n  {}", signer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliHistory {
+    pub(crate) address: String,
+    pub(crate) mint: String,
+    pub(crate) decimals: u8,
+    pub(crate) transactions: Vec<CliHistoryTransaction>,
+    /// Signature to pass as `--before` to get the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) next_page_before: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliHistoryTransaction {
+    pub(crate) signature: String,
+    pub(crate) slot: u64,
+    pub(crate) block_time: Option<i64>,
+    pub(crate) error: Option<String>,
+    /// HPL Token instructions of the transaction involving the account
+    pub(crate) instructions: Vec<String>,
+    /// Balance change in tokens, unknown when the node didn't record the token
+    /// balances of the transaction
+    pub(crate) change: Option<String>,
+    pub(crate) post_balance: Option<String>,
+    pub(crate) memo: Option<String>,
+}
+
+impl CliHistoryTransaction {
+    fn status(&self) -> &str {
+        if self.error.is_some() {
+            "failed"
+        } else {
+            "ok"
+        }
+    }
+
+    fn time(&self) -> String {
+        self.block_time
+            .map(unix_timestamp_to_string)
+            .unwrap_or_default()
+    }
+}
+
+impl CliHistory {
+    pub(crate) fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("signature,slot,block_time,status,instructions,change,balance,memo\n");
+        for transaction in &self.transactions {
+            let fields = [
+                transaction.signature.clone(),
+                transaction.slot.to_string(),
+                transaction.time(),
+                transaction.status().to_string(),
+                transaction.instructions.join(";"),
+                transaction.change.clone().unwrap_or_default(),
+                transaction.post_balance.clone().unwrap_or_default(),
+                transaction.memo.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl QuietDisplay for CliHistory {}
+impl VerboseDisplay for CliHistory {}
+
+impl fmt::Display for CliHistory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Address:", &self.address)?;
+        writeln_name_value(f, "Mint:", &self.mint)?;
+        writeln!(f)?;
+        if self.transactions.is_empty() {
+            return writeln!(f, "None");
+        }
+        writeln!(
+            f,
+            "{:<88}  {:<23}  {:<6}  {:>20}  {:>20}  Instructions / Memo",
+            "Signature", "Time", "Status", "Change", "Balance"
+        )?;
+        for transaction in &self.transactions {
+            let mut details = transaction.instructions.join(", ");
+            if let Some(memo) = &transaction.memo {
+                if !details.is_empty() {
+                    details.push_str(" / ");
+                }
+                details.push_str(memo);
+            }
+            writeln!(
+                f,
+                "{:<88}  {:<23}  {:<6}  {:>20}  {:>20}  {}",
+                transaction.signature,
+                transaction.time(),
+                transaction.status(),
+                transaction.change.as_deref().unwrap_or("?"),
+                transaction.post_balance.as_deref().unwrap_or("?"),
+                details,
+            )?;
+        }
+        if let Some(before) = &self.next_page_before {
+            writeln!(f)?;
+            writeln!(f, "More transactions: --before {}", before)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use hpl_token::{
+    merkle::{balance_leaf, verify_proof, MerkleTree},
+    state::Account,
+};
+use huione_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Balances of every holder of a mint at a given slot.
+///
+/// Holders are aggregated by owner and ordered by owner address, so the same
+/// chain state always produces the same file and the same Merkle root. The
+/// leaf of a holder includes its position in that order, as the merkle
+/// distributor expects of a claim.
+pub struct Snapshot {
+    pub mint: Pubkey,
+    pub slot: Slot,
+    pub decimals: u8,
+    pub holders: Vec<(Pubkey, u128)>,
+}
+
+impl Snapshot {
+    pub fn new<I: IntoIterator<Item = Account>>(
+        mint: Pubkey,
+        slot: Slot,
+        decimals: u8,
+        accounts: I,
+    ) -> Self {
+        let mut balances: BTreeMap<Pubkey, u128> = BTreeMap::new();
+        for account in accounts {
+            if account.mint == mint && account.amount > 0 {
+                *balances.entry(account.owner).or_default() += account.amount;
+            }
+        }
+        Self {
+            mint,
+            slot,
+            decimals,
+            holders: balances.into_iter().collect(),
+        }
+    }
+
+    pub fn total(&self) -> u128 {
+        self.holders.iter().map(|(_, amount)| amount).sum()
+    }
+
+    pub fn tree(&self) -> MerkleTree {
+        MerkleTree::new(
+            self.holders
+                .iter()
+                .enumerate()
+                .map(|(index, (owner, amount))| balance_leaf(index as u64, owner, *amount))
+                .collect(),
+        )
+    }
+
+    /// Returns the leaf index, balance and inclusion proof of `owner`
+    pub fn proof(&self, owner: &Pubkey) -> Option<(u64, u128, Vec<Hash>)> {
+        let index = self
+            .holders
+            .binary_search_by_key(owner, |(owner, _)| *owner)
+            .ok()?;
+        let proof = self.tree().proof(index)?;
+        Some((index as u64, self.holders[index].1, proof))
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "mint": self.mint.to_string(),
+            "slot": self.slot,
+            "decimals": self.decimals,
+            "total": self.total().to_string(),
+            "root": self.tree().root().to_string(),
+            "holders": self
+                .holders
+                .iter()
+                .map(|(owner, amount)| json!({
+                    "owner": owner.to_string(),
+                    "amount": amount.to_string(),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Parses a snapshot, rejecting it if the recorded root does not match the
+    /// recorded balances
+    pub fn from_json(value: &Value) -> Result<Self, Error> {
+        let field = |value: &Value, name: &str| -> Result<String, Error> {
+            match &value[name] {
+                Value::String(s) => Ok(s.clone()),
+                Value::Number(n) => Ok(n.to_string()),
+                _ => Err(format!("Snapshot is missing field `{}`", name).into()),
+            }
+        };
+        let mut holders = vec![];
+        for holder in value["holders"]
+            .as_array()
+            .ok_or("Snapshot is missing field `holders`")?
+        {
+            holders.push((
+                Pubkey::from_str(&field(holder, "owner")?)?,
+                field(holder, "amount")?.parse::<u128>()?,
+            ));
+        }
+        if holders.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err("Snapshot holders are not sorted by owner".into());
+        }
+        let snapshot = Self {
+            mint: Pubkey::from_str(&field(value, "mint")?)?,
+            slot: field(value, "slot")?.parse()?,
+            decimals: field(value, "decimals")?.parse()?,
+            holders,
+        };
+        let root = Hash::from_str(&field(value, "root")?)
+            .map_err(|err| format!("Invalid snapshot root: {:?}", err))?;
+        if snapshot.tree().root() != root {
+            return Err("Snapshot root does not match its balances".into());
+        }
+        Ok(snapshot)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(&self.to_json())? + "\n")?;
+        Ok(())
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Self::from_json(&value)
+    }
+}
+
+/// Checks an inclusion proof produced by [`Snapshot::proof`]
+pub fn verify_balance(
+    root: &Hash,
+    index: u64,
+    owner: &Pubkey,
+    amount: u128,
+    proof: &[Hash],
+) -> bool {
+    verify_proof(proof, root, balance_leaf(index, owner, amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_account(mint: Pubkey, owner: Pubkey, amount: u128) -> Account {
+        Account {
+            mint,
+            owner,
+            amount,
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_is_deterministic() {
+        let mint = Pubkey::new_unique();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let accounts = vec![
+            token_account(mint, bob, 30),
+            token_account(mint, alice, 10),
+            token_account(mint, alice, 5),
+            token_account(mint, Pubkey::new_unique(), 0),
+            token_account(Pubkey::new_unique(), bob, 100),
+        ];
+        let snapshot = Snapshot::new(mint, 42, 2, accounts.clone());
+        let mut holders = vec![(alice, 15), (bob, 30)];
+        holders.sort();
+        assert_eq!(snapshot.holders, holders);
+        assert_eq!(snapshot.total(), 45);
+
+        // the account order does not change the file or the root
+        let reversed = Snapshot::new(mint, 42, 2, accounts.into_iter().rev());
+        assert_eq!(
+            reversed.to_json().to_string(),
+            snapshot.to_json().to_string()
+        );
+        assert_eq!(reversed.tree().root(), snapshot.tree().root());
+
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.json");
+        snapshot.write(&first).unwrap();
+        reversed.write(&second).unwrap();
+        assert_eq!(
+            fs::read_to_string(&first).unwrap(),
+            fs::read_to_string(&second).unwrap()
+        );
+
+        let read = Snapshot::read(&first).unwrap();
+        assert_eq!(read.mint, mint);
+        assert_eq!(read.slot, 42);
+        assert_eq!(read.decimals, 2);
+        assert_eq!(read.holders, snapshot.holders);
+    }
+
+    #[test]
+    fn test_snapshot_proofs() {
+        let mint = Pubkey::new_unique();
+        let accounts: Vec<Account> = (1..=5u128)
+            .map(|amount| token_account(mint, Pubkey::new_unique(), amount * 10))
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        Snapshot::new(mint, 7, 0, accounts.clone())
+            .write(&path)
+            .unwrap();
+
+        // proofs from a snapshot read back verify against its root
+        let snapshot = Snapshot::read(&path).unwrap();
+        let root = snapshot.tree().root();
+        for account in &accounts {
+            let (index, amount, proof) = snapshot.proof(&account.owner).unwrap();
+            assert_eq!(snapshot.holders[index as usize].0, account.owner);
+            assert_eq!(amount, account.amount);
+            assert!(verify_balance(&root, index, &account.owner, amount, &proof));
+            assert!(!verify_balance(
+                &root,
+                index,
+                &account.owner,
+                amount + 1,
+                &proof
+            ));
+            assert!(!verify_balance(
+                &root,
+                (index + 1) % accounts.len() as u64,
+                &account.owner,
+                amount,
+                &proof
+            ));
+        }
+        assert_eq!(snapshot.proof(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_snapshot_rejects_tampering() {
+        let mint = Pubkey::new_unique();
+        let accounts = vec![
+            token_account(mint, Pubkey::new_unique(), 10),
+            token_account(mint, Pubkey::new_unique(), 20),
+        ];
+        let value = Snapshot::new(mint, 1, 0, accounts).to_json();
+        assert!(Snapshot::from_json(&value).is_ok());
+
+        let mut changed_amount = value.clone();
+        changed_amount["holders"][0]["amount"] = json!("11");
+        assert!(Snapshot::from_json(&changed_amount).is_err());
+
+        let mut unsorted = value.clone();
+        unsorted["holders"].as_array_mut().unwrap().reverse();
+        assert!(Snapshot::from_json(&unsorted).is_err());
+
+        let mut missing_root = value;
+        missing_root.as_object_mut().unwrap().remove("root");
+        assert!(Snapshot::from_json(&missing_root).is_err());
+    }
+}
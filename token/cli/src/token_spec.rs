@@ -0,0 +1,373 @@
+use huione_clap_utils::input_validators::is_amount;
+use huione_sdk::pubkey::Pubkey;
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Longest symbol, name and icon url a mint meta account holds, in bytes
+const MAX_SYMBOL_LEN: usize = 8;
+const MAX_NAME_LEN: usize = 32;
+const MAX_ICON_LEN: usize = 128;
+
+/// An account receiving part of the initial supply
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recipient {
+    /// Owner of the associated token account receiving the tokens
+    pub owner: Pubkey,
+    /// Raw token amount
+    pub amount: u128,
+}
+
+/// Everything needed to create a token, read from a TOML or JSON file:
+///
+/// ```toml
+/// decimals = 6
+/// mint-authority = "<ADDRESS>"   # or "none" to fix the supply once minted
+/// freeze-authority = "<ADDRESS>"
+/// meta-authority = "<ADDRESS>"
+/// symbol = "TKN"
+/// name = "Token"
+/// icon = "https://example.com/token.png"
+/// initial-supply = "1000000"
+/// treasury = "<ADDRESS>"
+///
+/// [[recipients]]
+/// address = "<ADDRESS>"
+/// amount = "2500.5"
+/// ```
+///
+/// Authorities default to the client keypair, except the freeze authority
+/// which is disabled unless given, and the meta authority which follows the
+/// mint authority. Amounts are in tokens. The part of the initial supply not
+/// sent to a recipient goes to the treasury, the client keypair by default.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenSpec {
+    pub decimals: u8,
+    /// `None` fixes the supply once the initial supply is minted
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub meta_authority: Pubkey,
+    pub symbol: String,
+    pub name: String,
+    pub icon: String,
+    pub recipients: Vec<Recipient>,
+}
+
+impl TokenSpec {
+    /// Reads a spec file, `.toml` files as TOML and anything else as JSON.
+    /// `default_authority` stands in for the authorities the spec leaves out.
+    pub fn read<P: AsRef<Path>>(path: P, default_authority: Pubkey) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let spec_error = |err: &str| format!("{}: {}", path.display(), err);
+        let contents = fs::read_to_string(path)?;
+        let value: Value = if path
+            .extension()
+            .map_or(false, |extension| extension == "toml")
+        {
+            let value: toml::Value =
+                toml::from_str(&contents).map_err(|err| spec_error(&err.to_string()))?;
+            serde_json::to_value(value)?
+        } else {
+            serde_json::from_str(&contents).map_err(|err| spec_error(&err.to_string()))?
+        };
+        let spec = value
+            .as_object()
+            .ok_or_else(|| spec_error("expected a table of token settings"))?;
+
+        const FIELDS: [&str; 10] = [
+            "decimals",
+            "mint-authority",
+            "freeze-authority",
+            "meta-authority",
+            "symbol",
+            "name",
+            "icon",
+            "initial-supply",
+            "treasury",
+            "recipients",
+        ];
+        if let Some(field) = spec.keys().find(|field| !FIELDS.contains(&field.as_str())) {
+            return Err(spec_error(&format!("unknown field `{}`", field)).into());
+        }
+
+        let string = |name: &str| -> Result<Option<&str>, Error> {
+            match spec.get(name) {
+                None => Ok(None),
+                Some(value) => {
+                    Ok(Some(value.as_str().ok_or_else(|| {
+                        spec_error(&format!("`{}` must be a string", name))
+                    })?))
+                }
+            }
+        };
+        let address = |name: &str, value: &str| {
+            Pubkey::from_str(value).map_err(|err| spec_error(&format!("`{}`: {}", name, err)))
+        };
+        let text = |name: &str, max_len: usize| -> Result<String, Error> {
+            let text = string(name)?.unwrap_or_default();
+            if text.len() > max_len {
+                return Err(
+                    spec_error(&format!("`{}` is longer than {} bytes", name, max_len)).into(),
+                );
+            }
+            Ok(text.to_string())
+        };
+
+        let decimals = match spec.get("decimals") {
+            None => hpl_token::native_mint::DECIMALS,
+            Some(decimals) => decimals
+                .as_u64()
+                .filter(|decimals| *decimals <= u8::MAX as u64)
+                .ok_or_else(|| spec_error("`decimals` must be a number from 0 to 255"))?
+                as u8,
+        };
+        let amount = |name: &str, value: &Value| -> Result<u128, Error> {
+            let amount = match value {
+                Value::String(amount) => amount.clone(),
+                Value::Number(amount) => amount.to_string(),
+                _ => return Err(spec_error(&format!("`{}` must be an amount", name)).into()),
+            };
+            is_amount(amount.clone()).map_err(|err| spec_error(&format!("`{}`: {}", name, err)))?;
+            let (inte, frac) = crate::parse_amount(&amount);
+            Ok(hpl_token::ui_amount_to_amount(inte, frac, decimals))
+        };
+
+        let mint_authority = match string("mint-authority")? {
+            None => Some(default_authority),
+            Some("none") => None,
+            Some(mint_authority) => Some(address("mint-authority", mint_authority)?),
+        };
+        let freeze_authority = string("freeze-authority")?
+            .map(|freeze_authority| address("freeze-authority", freeze_authority))
+            .transpose()?;
+        let meta_authority = match string("meta-authority")? {
+            Some(meta_authority) => address("meta-authority", meta_authority)?,
+            None => mint_authority.unwrap_or(default_authority),
+        };
+
+        let mut recipients: Vec<Recipient> = vec![];
+        let mut positions = HashMap::new();
+        if let Some(rows) = spec.get("recipients") {
+            let rows = rows
+                .as_array()
+                .ok_or_else(|| spec_error("`recipients` must be a list"))?;
+            for row in rows {
+                let owner = row["address"]
+                    .as_str()
+                    .ok_or_else(|| spec_error("a recipient is missing its `address`"))?;
+                let owner = address("address", owner)?;
+                let amount = amount("amount", &row["amount"])?;
+                if positions.insert(owner, recipients.len()).is_some() {
+                    return Err(spec_error(&format!("{} is listed twice", owner)).into());
+                }
+                recipients.push(Recipient { owner, amount });
+            }
+        }
+
+        let distributed: u128 = recipients.iter().map(|recipient| recipient.amount).sum();
+        if let Some(initial_supply) = spec.get("initial-supply") {
+            let initial_supply = amount("initial-supply", initial_supply)?;
+            if initial_supply < distributed {
+                return Err(spec_error(
+                    "`initial-supply` is less than the amounts sent to `recipients`",
+                )
+                .into());
+            }
+            let treasury = match string("treasury")? {
+                Some(treasury) => address("treasury", treasury)?,
+                None => default_authority,
+            };
+            let remainder = initial_supply - distributed;
+            if remainder > 0 {
+                match positions.get(&treasury) {
+                    Some(position) => recipients[*position].amount += remainder,
+                    None => recipients.push(Recipient {
+                        owner: treasury,
+                        amount: remainder,
+                    }),
+                }
+            }
+        } else if spec.contains_key("treasury") {
+            return Err(spec_error("`treasury` requires an `initial-supply`").into());
+        }
+        recipients.retain(|recipient| recipient.amount > 0);
+
+        Ok(Self {
+            decimals,
+            mint_authority,
+            freeze_authority,
+            meta_authority,
+            symbol: text("symbol", MAX_SYMBOL_LEN)?,
+            name: text("name", MAX_NAME_LEN)?,
+            icon: text("icon", MAX_ICON_LEN)?,
+            recipients,
+        })
+    }
+
+    /// Whether the token must be created under a temporary authority that
+    /// mints the initial supply or sets up the final authorities afterwards.
+    /// The mint meta authority starts out as the mint authority.
+    pub fn needs_setup_authority(&self) -> bool {
+        !self.recipients.is_empty() || self.mint_authority != Some(self.meta_authority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(name: &str, contents: &str, default_authority: Pubkey) -> Result<TokenSpec, Error> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        fs::write(&path, contents).unwrap();
+        TokenSpec::read(&path, default_authority)
+    }
+
+    #[test]
+    fn test_read_spec() {
+        let default_authority = Pubkey::new_unique();
+        let spec = read("token.toml", "", default_authority).unwrap();
+        assert_eq!(spec.decimals, hpl_token::native_mint::DECIMALS);
+        assert_eq!(spec.mint_authority, Some(default_authority));
+        assert_eq!(spec.freeze_authority, None);
+        assert_eq!(spec.meta_authority, default_authority);
+        assert!(spec.recipients.is_empty());
+        assert!(!spec.needs_setup_authority());
+
+        // a fixed supply keeps the meta authority with the default authority
+        let freeze_authority = Pubkey::new_unique();
+        let spec = read(
+            "token.json",
+            &format!(
+                r#"{{"decimals": 2, "mint-authority": "none", "freeze-authority": "{}",
+                    "symbol": "TKN", "name": "Token"}}"#,
+                freeze_authority
+            ),
+            default_authority,
+        )
+        .unwrap();
+        assert_eq!(spec.decimals, 2);
+        assert_eq!(spec.mint_authority, None);
+        assert_eq!(spec.freeze_authority, Some(freeze_authority));
+        assert_eq!(spec.meta_authority, default_authority);
+        assert_eq!(spec.symbol, "TKN");
+        assert_eq!(spec.name, "Token");
+        assert!(spec.needs_setup_authority());
+    }
+
+    #[test]
+    fn test_read_spec_unknown_field() {
+        let err = read("token.toml", "decimal = 2\n", Pubkey::new_unique()).unwrap_err();
+        assert!(err.to_string().contains("unknown field `decimal`"));
+    }
+
+    #[test]
+    fn test_read_spec_treasury() {
+        let default_authority = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+
+        // the remainder goes to the default authority
+        let spec = read(
+            "token.toml",
+            &format!(
+                "decimals = 2\ninitial-supply = \"100\"\n\
+                 [[recipients]]\naddress = \"{}\"\namount = \"40.5\"\n",
+                recipient
+            ),
+            default_authority,
+        )
+        .unwrap();
+        assert_eq!(
+            spec.recipients,
+            vec![
+                Recipient {
+                    owner: recipient,
+                    amount: 4050,
+                },
+                Recipient {
+                    owner: default_authority,
+                    amount: 5950,
+                },
+            ]
+        );
+
+        // a treasury that is also a recipient gets a single account
+        let spec = read(
+            "token.toml",
+            &format!(
+                "decimals = 2\ninitial-supply = 100\ntreasury = \"{}\"\n\
+                 [[recipients]]\naddress = \"{}\"\namount = 40\n\
+                 [[recipients]]\naddress = \"{}\"\namount = 10\n",
+                treasury, treasury, recipient
+            ),
+            default_authority,
+        )
+        .unwrap();
+        assert_eq!(
+            spec.recipients,
+            vec![
+                Recipient {
+                    owner: treasury,
+                    amount: 9000,
+                },
+                Recipient {
+                    owner: recipient,
+                    amount: 1000,
+                },
+            ]
+        );
+
+        // nothing is left for the treasury
+        let spec = read(
+            "token.toml",
+            &format!(
+                "initial-supply = 10\n[[recipients]]\naddress = \"{}\"\namount = 10\n",
+                recipient
+            ),
+            default_authority,
+        )
+        .unwrap();
+        assert_eq!(spec.recipients.len(), 1);
+
+        assert!(read(
+            "token.toml",
+            &format!("treasury = \"{}\"\n", treasury),
+            default_authority
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_read_spec_duplicate_recipient() {
+        let recipient = Pubkey::new_unique();
+        let err = read(
+            "token.toml",
+            &format!(
+                "[[recipients]]\naddress = \"{}\"\namount = 1\n\
+                 [[recipients]]\naddress = \"{}\"\namount = 2\n",
+                recipient, recipient
+            ),
+            Pubkey::new_unique(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("is listed twice"));
+    }
+
+    #[test]
+    fn test_read_spec_initial_supply_too_low() {
+        let err = read(
+            "token.toml",
+            &format!(
+                "initial-supply = 10\n[[recipients]]\naddress = \"{}\"\namount = 10.5\n",
+                Pubkey::new_unique()
+            ),
+            Pubkey::new_unique(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`initial-supply` is less than the amounts sent to `recipients`"));
+    }
+}
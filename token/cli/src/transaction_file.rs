@@ -0,0 +1,248 @@
+use huione_sdk::{
+    pubkey::Pubkey, sanitize::Sanitize, signature::Signature, transaction::Transaction,
+};
+use serde_json::{json, Value};
+use std::{fs, path::Path, str::FromStr};
+
+type Error = Box<dyn std::error::Error>;
+
+/// A partially signed transaction passed from signer to signer.
+///
+/// The file is a JSON object holding the bincode-serialized transaction in
+/// base64 and the signers acting for a multisig. It also lists the signers
+/// that still have to sign, which is written for whoever opens the file and
+/// never read back.
+pub struct TransactionFile {
+    pub transaction: Transaction,
+    /// Signers of the transaction that sign on behalf of a multisig
+    pub multisig_signers: Vec<Pubkey>,
+}
+
+impl TransactionFile {
+    /// Reads a transaction file, checking the signatures it already holds
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file_error = |err: &str| format!("{}: {}", path.display(), err);
+        let value: Value = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| file_error(&err.to_string()))?;
+
+        let transaction = value["transaction"]
+            .as_str()
+            .ok_or_else(|| file_error("missing field `transaction`"))?;
+        let transaction =
+            base64::decode(transaction).map_err(|err| file_error(&err.to_string()))?;
+        let transaction: Transaction =
+            bincode::deserialize(&transaction).map_err(|err| file_error(&err.to_string()))?;
+        transaction
+            .sanitize()
+            .map_err(|err| file_error(&format!("invalid transaction: {:?}", err)))?;
+
+        let mut multisig_signers = vec![];
+        for signer in value["multisigSigners"]
+            .as_array()
+            .ok_or_else(|| file_error("missing field `multisigSigners`"))?
+        {
+            multisig_signers.push(
+                signer
+                    .as_str()
+                    .and_then(|signer| Pubkey::from_str(signer).ok())
+                    .ok_or_else(|| file_error("invalid multisig signer"))?,
+            );
+        }
+
+        let transaction_file = Self {
+            transaction,
+            multisig_signers,
+        };
+        let signatures = transaction_file.transaction.signatures.iter();
+        let results = transaction_file.transaction.verify_with_results();
+        for ((signer, signature), valid) in transaction_file
+            .signers()
+            .iter()
+            .zip(signatures)
+            .zip(results)
+        {
+            if *signature != Signature::default() && !valid {
+                return Err(file_error(&format!("invalid signature for {}", signer)).into());
+            }
+        }
+        Ok(transaction_file)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let to_strings = |pubkeys: &[Pubkey]| {
+            pubkeys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect::<Vec<_>>()
+        };
+        let file = json!({
+            "transaction": base64::encode(bincode::serialize(&self.transaction)?),
+            "multisigSigners": to_strings(&self.multisig_signers),
+            "missingSigners": to_strings(&self.missing_signers()),
+            "missingMultisigSigners": to_strings(&self.missing_multisig_signers()),
+        });
+        fs::write(path, serde_json::to_string_pretty(&file)? + "\n")?;
+        Ok(())
+    }
+
+    /// Accounts whose signature the transaction requires
+    pub fn signers(&self) -> &[Pubkey] {
+        let message = &self.transaction.message;
+        &message.account_keys[..message.header.num_required_signatures as usize]
+    }
+
+    /// Required signers that haven't signed yet
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.signers()
+            .iter()
+            .zip(&self.transaction.signatures)
+            .filter(|(_, signature)| **signature == Signature::default())
+            .map(|(signer, _)| *signer)
+            .collect()
+    }
+
+    /// Multisig signers that haven't signed yet
+    pub fn missing_multisig_signers(&self) -> Vec<Pubkey> {
+        self.missing_signers()
+            .into_iter()
+            .filter(|signer| self.multisig_signers.contains(signer))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use huione_sdk::{
+        hash::Hash,
+        message::Message,
+        signature::{Keypair, Signer},
+    };
+
+    /// A transfer out of a 2-of-3 multisig account, signed by no one yet
+    fn multisig_transfer(fee_payer: &Keypair, multisig_signers: &[&Keypair]) -> TransactionFile {
+        let multisig_signer_pubkeys = multisig_signers
+            .iter()
+            .map(|signer| signer.pubkey())
+            .collect::<Vec<_>>();
+        let instruction = hpl_token::instruction::transfer(
+            &hpl_token::id(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &multisig_signer_pubkeys.iter().collect::<Vec<_>>(),
+            10,
+        )
+        .unwrap();
+        let mut message = Message::new(&[instruction], Some(&fee_payer.pubkey()));
+        message.recent_blockhash = Hash::new(&[7; 32]);
+        TransactionFile {
+            transaction: Transaction::new_unsigned(message),
+            multisig_signers: multisig_signer_pubkeys,
+        }
+    }
+
+    fn sign(transaction_file: &mut TransactionFile, signers: &[&Keypair]) {
+        let recent_blockhash = transaction_file.transaction.message.recent_blockhash;
+        transaction_file
+            .transaction
+            .try_partial_sign(signers, recent_blockhash)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_transaction_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transaction.json");
+        let fee_payer = Keypair::new();
+        let first = Keypair::new();
+        let second = Keypair::new();
+        let mut transaction_file = multisig_transfer(&fee_payer, &[&first, &second]);
+
+        assert_eq!(
+            transaction_file.signers(),
+            &[fee_payer.pubkey(), first.pubkey(), second.pubkey()]
+        );
+        assert_eq!(
+            transaction_file.missing_signers(),
+            vec![fee_payer.pubkey(), first.pubkey(), second.pubkey()]
+        );
+        assert_eq!(
+            transaction_file.missing_multisig_signers(),
+            vec![first.pubkey(), second.pubkey()]
+        );
+        transaction_file.write(&path).unwrap();
+        let read = TransactionFile::read(&path).unwrap();
+        assert_eq!(read.transaction, transaction_file.transaction);
+        assert_eq!(read.multisig_signers, transaction_file.multisig_signers);
+
+        // A partial signature is kept and the file tells who is left to sign
+        sign(&mut transaction_file, &[&first]);
+        transaction_file.write(&path).unwrap();
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            written["missingSigners"],
+            json!([fee_payer.pubkey().to_string(), second.pubkey().to_string()])
+        );
+        assert_eq!(
+            written["missingMultisigSigners"],
+            json!([second.pubkey().to_string()])
+        );
+        let mut read = TransactionFile::read(&path).unwrap();
+        assert_eq!(read.transaction, transaction_file.transaction);
+        assert_eq!(
+            read.missing_signers(),
+            vec![fee_payer.pubkey(), second.pubkey()]
+        );
+        assert_eq!(read.missing_multisig_signers(), vec![second.pubkey()]);
+
+        sign(&mut read, &[&fee_payer, &second]);
+        assert!(read.missing_signers().is_empty());
+        assert!(read.missing_multisig_signers().is_empty());
+        assert!(read.transaction.verify().is_ok());
+    }
+
+    #[test]
+    fn test_transaction_file_rejects_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transaction.json");
+        let fee_payer = Keypair::new();
+        let first = Keypair::new();
+        let second = Keypair::new();
+        let mut transaction_file = multisig_transfer(&fee_payer, &[&first, &second]);
+        sign(&mut transaction_file, &[&first]);
+        transaction_file.write(&path).unwrap();
+        let value: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let read_value = |value: &Value| {
+            fs::write(&path, value.to_string()).unwrap();
+            TransactionFile::read(&path)
+        };
+        assert!(read_value(&value).is_ok());
+
+        // A signature that doesn't match its signer
+        let mut tampered = transaction_file.transaction.clone();
+        tampered.signatures[1] = Signature::new(&[1; 64]);
+        let mut tampered_value = value.clone();
+        tampered_value["transaction"] =
+            json!(base64::encode(bincode::serialize(&tampered).unwrap()));
+        let error = read_value(&tampered_value).err().unwrap().to_string();
+        assert!(error.contains(&format!("invalid signature for {}", first.pubkey())));
+
+        // Malformed multisig signers
+        let mut invalid_signer = value.clone();
+        invalid_signer["multisigSigners"] = json!(["not a pubkey"]);
+        assert!(read_value(&invalid_signer).is_err());
+        let mut not_a_list = value.clone();
+        not_a_list["multisigSigners"] = json!(first.pubkey().to_string());
+        assert!(read_value(&not_a_list).is_err());
+        let mut missing = value.clone();
+        missing.as_object_mut().unwrap().remove("multisigSigners");
+        assert!(read_value(&missing).is_err());
+
+        // A transaction that doesn't decode
+        let mut garbage = value;
+        garbage["transaction"] = json!(base64::encode(&[1, 2, 3]));
+        assert!(read_value(&garbage).is_err());
+    }
+}
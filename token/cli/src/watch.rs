@@ -0,0 +1,215 @@
+use huione_account_decoder::{parse_token::UiTokenAmount, UiAccountEncoding};
+use huione_client::{
+    pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use huione_sdk::{commitment_config::CommitmentConfig, program_pack::Pack, pubkey::Pubkey};
+use std::{collections::HashSet, process::Command, time::Duration};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Offset of the owner in a token account
+const ACCOUNT_OWNER_OFFSET: usize = 32;
+
+/// Time after which the accounts of an owner are shown again without a
+/// notification
+const OWNER_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Runs a command when the balance of an account falls below a threshold.
+///
+/// The command runs through `sh -c` once per crossing, with the account, its
+/// mint, its balance and the threshold in the `HPL_TOKEN_ACCOUNT`,
+/// `HPL_TOKEN_MINT`, `HPL_TOKEN_BALANCE` and `HPL_TOKEN_THRESHOLD`
+/// environment variables.
+pub struct BalanceAlert {
+    /// Threshold in tokens, applied with the decimals of each account's mint
+    threshold: String,
+    command: String,
+    /// Accounts last seen below the threshold
+    below: HashSet<String>,
+}
+
+impl BalanceAlert {
+    pub fn new(threshold: String, command: String) -> Self {
+        Self {
+            threshold,
+            command,
+            below: HashSet::new(),
+        }
+    }
+
+    /// Checks the balance of an account, running the command if it just fell
+    /// below the threshold
+    pub fn check(&mut self, address: &str, mint: Option<&str>, balance: &UiTokenAmount) {
+        let (inte, frac) = crate::parse_amount(&self.threshold);
+        let threshold = hpl_token::ui_amount_to_amount(inte, frac, balance.decimals);
+        let amount = balance.amount.parse::<u128>().unwrap_or_default();
+        if amount >= threshold {
+            self.below.remove(address);
+            return;
+        }
+        if !self.below.insert(address.to_string()) {
+            return;
+        }
+
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("HPL_TOKEN_ACCOUNT", address)
+            .env("HPL_TOKEN_MINT", mint.unwrap_or_default())
+            .env("HPL_TOKEN_BALANCE", balance.real_number_string_trimmed())
+            .env("HPL_TOKEN_THRESHOLD", &self.threshold)
+            .status();
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => eprintln!("Alert command for {} failed: {}", address, status),
+            Err(err) => eprintln!("Alert command for {} failed: {}", address, err),
+        }
+    }
+}
+
+/// Calls `render` once, then again after every change to `address`
+pub fn watch_account<F>(
+    websocket_url: &str,
+    commitment: CommitmentConfig,
+    address: &Pubkey,
+    render: F,
+) -> Result<(), Error>
+where
+    F: FnMut() -> Result<(), Error>,
+{
+    let (_subscription, receiver) = PubsubClient::account_subscribe(
+        websocket_url,
+        address,
+        Some(RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        }),
+    )?;
+    render_on_change(
+        render,
+        || receiver.recv().is_ok(),
+        || receiver.try_recv().is_ok(),
+    )
+}
+
+/// Calls `render` once, then again after every change to a token account of
+/// `owner`, including accounts created after the watch started.
+///
+/// An account that is closed or given to another owner no longer matches the
+/// subscription filters and sends no notification, so `render` is also called
+/// every `OWNER_REFRESH_INTERVAL` without one.
+pub fn watch_owner<F>(
+    websocket_url: &str,
+    commitment: CommitmentConfig,
+    owner: &Pubkey,
+    render: F,
+) -> Result<(), Error>
+where
+    F: FnMut() -> Result<(), Error>,
+{
+    let (_subscription, receiver) = PubsubClient::program_subscribe(
+        websocket_url,
+        &hpl_token::id(),
+        Some(RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(hpl_token::state::Account::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    ACCOUNT_OWNER_OFFSET,
+                    &owner.to_bytes(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(commitment),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        }),
+    )?;
+    render_on_change(
+        render,
+        || match receiver.recv_timeout(OWNER_REFRESH_INTERVAL) {
+            Ok(_) => true,
+            Err(err) => err.is_timeout(),
+        },
+        || receiver.try_recv().is_ok(),
+    )
+}
+
+/// Calls `render` once, then again each time `recv` returns, until it returns
+/// false. Notifications already queued by then are drained with `try_recv`
+fn render_on_change<F, R, T>(mut render: F, mut recv: R, mut try_recv: T) -> Result<(), Error>
+where
+    F: FnMut() -> Result<(), Error>,
+    R: FnMut() -> bool,
+    T: FnMut() -> bool,
+{
+    render()?;
+    loop {
+        if !recv() {
+            return Err("Subscription closed by the server".into());
+        }
+        // A transaction touching several accounts sends a notification for
+        // each of them; render once for the lot
+        while try_recv() {}
+        render()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, collections::VecDeque};
+
+    #[test]
+    fn test_render_on_change_coalesces_bursts() {
+        // Bursts of 3 and 2 notifications, then the subscription closes
+        let bursts = RefCell::new(VecDeque::from(vec![3, 2]));
+        let queued = RefCell::new(0);
+        let mut renders = 0;
+
+        let result = render_on_change(
+            || {
+                renders += 1;
+                Ok(())
+            },
+            || match bursts.borrow_mut().pop_front() {
+                Some(burst) => {
+                    *queued.borrow_mut() = burst - 1;
+                    true
+                }
+                None => false,
+            },
+            || {
+                let mut queued = queued.borrow_mut();
+                if *queued == 0 {
+                    return false;
+                }
+                *queued -= 1;
+                true
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(renders, 3);
+        assert_eq!(*queued.borrow(), 0);
+    }
+
+    #[test]
+    fn test_render_on_change_stops_on_render_error() {
+        let mut renders = 0;
+        let result = render_on_change(
+            || {
+                renders += 1;
+                Err("closed".into())
+            },
+            || true,
+            || false,
+        );
+        assert!(result.is_err());
+        assert_eq!(renders, 1);
+    }
+}
@@ -0,0 +1,748 @@
+// Runs the `hpl-token` binary against a test validator, which loads the programs built by
+// `cargo build-bpf` from `target/deploy`
+#![cfg(feature = "test-bpf")]
+
+use {
+    hpl_associated_token_account::get_associated_token_address,
+    hpl_token::{
+        native_mint,
+        state::{Account, Mint, MintMeta, TokenGroup, TokenGroupMember},
+    },
+    huione_client::rpc_client::RpcClient,
+    huione_sdk::{
+        bpf_loader,
+        commitment_config::CommitmentConfig,
+        native_token::hc_to_lamports,
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{write_keypair_file, Keypair, Signer},
+    },
+    huione_test_validator::{ProgramInfo, TestValidator, TestValidatorGenesis},
+    serde_json::Value,
+    std::{path::PathBuf, process::Command, str::FromStr},
+    tempfile::TempDir,
+};
+
+fn program_info(name: &str, program_id: Pubkey) -> ProgramInfo {
+    ProgramInfo {
+        program_id,
+        loader: bpf_loader::id(),
+        program_path: PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../target/deploy")
+            .join(format!("{}.so", name)),
+    }
+}
+
+struct TestContext {
+    _validator: TestValidator,
+    rpc_client: RpcClient,
+    payer: Keypair,
+    dir: TempDir,
+    config_file: String,
+}
+
+impl TestContext {
+    fn new() -> Self {
+        let mut genesis = TestValidatorGenesis::default();
+        genesis.add_programs_with_path(&[
+            program_info("hpl_token", hpl_token::id()),
+            program_info(
+                "hpl_associated_token_account",
+                hpl_associated_token_account::id(),
+            ),
+            program_info("hpl_memo", hpl_memo::id()),
+        ]);
+        let (validator, payer) = genesis.start();
+        let rpc_client =
+            RpcClient::new_with_commitment(validator.rpc_url(), CommitmentConfig::confirmed());
+
+        let dir = TempDir::new().unwrap();
+        let keypair_path = dir.path().join("payer.json");
+        write_keypair_file(&payer, &keypair_path).unwrap();
+        let config_file = dir.path().join("config.yml").to_str().unwrap().to_string();
+        huione_cli_config::Config {
+            json_rpc_url: validator.rpc_url(),
+            keypair_path: keypair_path.to_str().unwrap().to_string(),
+            ..huione_cli_config::Config::default()
+        }
+        .save(&config_file)
+        .unwrap();
+
+        Self {
+            _validator: validator,
+            rpc_client,
+            payer,
+            dir,
+            config_file,
+        }
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = self.display_command(&["--output", "json"]);
+        command.args(args);
+        command
+    }
+
+    /// A command printing in the display format, for commands with their own
+    /// output options
+    fn display_command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_hpl-token"));
+        command.args(&["--config", &self.config_file]).args(args);
+        command
+    }
+
+    /// Runs a command that must succeed and returns the documents it printed,
+    /// one per transaction sent or query made
+    fn run(&self, args: &[&str]) -> Vec<Value> {
+        let output = self.command(args).output().unwrap();
+        assert!(
+            output.status.success(),
+            "`hpl-token {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::Deserializer::from_slice(&output.stdout)
+            .into_iter::<Value>()
+            .map(|document| document.unwrap())
+            .collect()
+    }
+
+    /// Runs a command that must fail and returns its error output
+    fn run_err(&self, args: &[&str]) -> String {
+        let output = self.command(args).output().unwrap();
+        assert!(
+            !output.status.success(),
+            "`hpl-token {}` succeeded",
+            args.join(" ")
+        );
+        String::from_utf8_lossy(&output.stderr).to_string()
+    }
+
+    fn write_keypair(&self, name: &str, keypair: &Keypair) -> String {
+        let path = self.dir.path().join(format!("{}.json", name));
+        write_keypair_file(keypair, &path).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn create_token(&self, args: &[&str]) -> Pubkey {
+        let mut create_token = vec!["create-token", "--decimals", "6"];
+        create_token.extend_from_slice(args);
+        let output = self.run(&create_token);
+        address(&output[0])
+    }
+
+    fn unpack<T: Pack>(&self, address: &Pubkey) -> T {
+        T::unpack(&self.rpc_client.get_account_data(address).unwrap()).unwrap()
+    }
+
+    fn token_balance(&self, address: &Pubkey) -> u128 {
+        self.unpack::<Account>(address).amount
+    }
+
+    fn exists(&self, address: &Pubkey) -> bool {
+        self.rpc_client
+            .get_account_with_commitment(address, self.rpc_client.commitment())
+            .unwrap()
+            .value
+            .is_some()
+    }
+}
+
+fn command_output(document: &Value) -> &Value {
+    &document["commandOutput"]
+}
+
+/// Address created by a transaction command
+fn address(document: &Value) -> Pubkey {
+    Pubkey::from_str(command_output(document)["address"].as_str().unwrap()).unwrap()
+}
+
+fn assert_signature(document: &Value) {
+    assert!(command_output(document)["signature"].is_string());
+}
+
+fn mint_meta_address(token: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"MintMeta", &token.to_bytes()], &hpl_token::id()).0
+}
+
+#[test]
+fn test_create_token() {
+    let context = TestContext::new();
+    let token = context.create_token(&["--symbol", "TKN", "--name", "Test token"]);
+
+    let mint = context.unpack::<Mint>(&token);
+    assert_eq!(mint.decimals, 6);
+    assert_eq!(mint.supply, 0);
+    assert_eq!(mint.mint_authority, COption::Some(context.payer.pubkey()));
+    assert_eq!(mint.freeze_authority, COption::None);
+    let mint_meta = context.unpack::<MintMeta>(&mint_meta_address(&token));
+    assert_eq!(mint_meta.symbol, "TKN");
+    assert_eq!(mint_meta.name, "Test token");
+    assert_eq!(mint_meta.authority, COption::Some(context.payer.pubkey()));
+
+    let output = context.run(&["token-info", &token.to_string()]);
+    assert_eq!(output[0]["commandName"], "token-info");
+    let token_info = command_output(&output[0]);
+    assert_eq!(token_info["address"], token.to_string());
+    assert_eq!(token_info["symbol"], "TKN");
+    assert_eq!(token_info["name"], "Test token");
+
+    // A freezable token, from an existing keypair
+    let token_keypair = Keypair::new();
+    let token_path = context.write_keypair("token", &token_keypair);
+    let token = context.create_token(&[&token_path, "--enable-freeze"]);
+    assert_eq!(token, token_keypair.pubkey());
+    let mint = context.unpack::<Mint>(&token);
+    assert_eq!(mint.freeze_authority, COption::Some(context.payer.pubkey()));
+}
+
+#[test]
+fn test_mint_and_transfer() {
+    let context = TestContext::new();
+    let token = context.create_token(&[]);
+    let owner = context.payer.pubkey();
+    let account = get_associated_token_address(&owner, &token);
+
+    let output = context.run(&["create-account", &token.to_string()]);
+    assert_eq!(address(&output[0]), account);
+    assert_signature(&output[0]);
+    assert_eq!(context.token_balance(&account), 0);
+
+    let output = context.run(&["mint", &token.to_string(), "100"]);
+    assert_signature(&output[0]);
+    assert_eq!(context.token_balance(&account), 100_000_000);
+    assert_eq!(context.unpack::<Mint>(&token).supply, 100_000_000);
+
+    let output = context.run(&["balance", &token.to_string()]);
+    let balance = command_output(&output[0]);
+    assert_eq!(balance["address"], account.to_string());
+    assert_eq!(balance["amount"], "100000000");
+    assert_eq!(balance["uiAmountString"], "100");
+
+    let recipient = Pubkey::new_unique();
+    let recipient_account = get_associated_token_address(&recipient, &token);
+    let output = context.run(&[
+        "transfer",
+        &token.to_string(),
+        "40.5",
+        &recipient.to_string(),
+        "--fund-recipient",
+    ]);
+    assert_signature(&output[0]);
+    assert_eq!(context.token_balance(&recipient_account), 40_500_000);
+    assert_eq!(context.token_balance(&account), 59_500_000);
+
+    // The sender's balance is checked before sending anything
+    let error = context.run_err(&[
+        "transfer",
+        &token.to_string(),
+        "1000",
+        &recipient.to_string(),
+    ]);
+    assert!(error.contains("insufficient funds"), "{}", error);
+    assert_eq!(context.token_balance(&account), 59_500_000);
+}
+
+#[test]
+fn test_wrap_unwrap() {
+    let context = TestContext::new();
+    let account = get_associated_token_address(&context.payer.pubkey(), &native_mint::id());
+
+    let output = context.run(&["wrap", "1.5"]);
+    assert_eq!(address(&output[0]), account);
+    assert_signature(&output[0]);
+    let wrapped = context.unpack::<Account>(&account);
+    assert!(wrapped.is_native());
+    assert_eq!(wrapped.amount, hc_to_lamports(1.5));
+
+    let output = context.run(&["unwrap"]);
+    assert_signature(&output[0]);
+    assert!(!context.exists(&account));
+
+    // Nothing left to unwrap
+    let error = context.run_err(&["unwrap"]);
+    assert!(error.contains("No wrapped HC"), "{}", error);
+}
+
+#[test]
+fn test_gc() {
+    let context = TestContext::new();
+    let token = context.create_token(&[]);
+    let account = get_associated_token_address(&context.payer.pubkey(), &token);
+    let aux = Keypair::new();
+    let aux_path = context.write_keypair("aux", &aux);
+    let empty_aux = Keypair::new();
+    let empty_aux_path = context.write_keypair("empty-aux", &empty_aux);
+
+    context.run(&["create-account", &token.to_string(), &aux_path]);
+    context.run(&["create-account", &token.to_string(), &empty_aux_path]);
+    context.run(&["mint", &token.to_string(), "10", &aux.pubkey().to_string()]);
+    assert!(!context.exists(&account));
+
+    // A dry run only prints the plan
+    let output = context.run(&["gc", "--dry-run"]);
+    assert_eq!(output.len(), 1);
+    let plan = command_output(&output[0]);
+    assert_eq!(plan["dryRun"], true);
+    assert_eq!(plan["associatedAccountsCreated"], 1);
+    assert_eq!(plan["transactions"], 1);
+    assert_eq!(plan["accounts"].as_array().unwrap().len(), 2);
+    assert!(context.exists(&aux.pubkey()));
+
+    // The balance of the auxiliary accounts moves to a new associated account
+    // and both are closed, in a single transaction
+    let output = context.run(&["gc"]);
+    assert_eq!(output.len(), 2);
+    let summary = command_output(&output[0]);
+    assert_eq!(summary["dryRun"], false);
+    assert!(summary["rentRecovered"].as_u64().unwrap() > 0);
+    assert_signature(&output[1]);
+    assert!(!context.exists(&aux.pubkey()));
+    assert!(!context.exists(&empty_aux.pubkey()));
+    assert_eq!(context.token_balance(&account), 10_000_000);
+
+    // The associated account is left alone
+    assert!(context.run(&["gc"]).is_empty());
+    assert!(context.exists(&account));
+}
+
+#[test]
+fn test_json_compact_output() {
+    let context = TestContext::new();
+    let token = context.create_token(&[]);
+    let aux = Keypair::new();
+    let aux_path = context.write_keypair("aux", &aux);
+    context.run(&["create-account", &token.to_string(), &aux_path]);
+
+    // The summary of gc and its transaction are printed one per line
+    let output = context
+        .display_command(&["--output", "json-compact", "gc"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let documents: Vec<Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(documents.len(), 2);
+    assert!(documents
+        .iter()
+        .all(|document| document["commandName"] == "gc"));
+    assert_eq!(command_output(&documents[0])["dryRun"], false);
+    assert_signature(&documents[1]);
+}
+
+#[test]
+fn test_gc_burn_dust() {
+    let context = TestContext::new();
+    let token = context.create_token(&[]);
+    let account = get_associated_token_address(&context.payer.pubkey(), &token);
+    let aux = Keypair::new();
+    let aux_path = context.write_keypair("aux", &aux);
+
+    context.run(&["create-account", &token.to_string(), &aux_path]);
+    context.run(&["mint", &token.to_string(), "0.5", &aux.pubkey().to_string()]);
+
+    // The dust is burned instead of moved, so no associated account is needed
+    let output = context.run(&["gc", "--burn-dust-below", "1"]);
+    let summary = command_output(&output[0]);
+    assert_eq!(summary["accounts"][0]["action"], "burn and close");
+    assert_eq!(summary["associatedAccountsCreated"], 0);
+    assert!(!context.exists(&aux.pubkey()));
+    assert!(!context.exists(&account));
+    assert_eq!(context.unpack::<Mint>(&token).supply, 0);
+}
+
+#[test]
+fn test_nonce_pool() {
+    let context = TestContext::new();
+    let pool_file = context.dir.path().join("pool.json");
+    let pool_file = pool_file.to_str().unwrap();
+    let nonces = || -> Vec<Value> {
+        let output = context.run(&["nonce-pool", "list", pool_file]);
+        let accounts = command_output(&output[0])["accounts"].as_array().unwrap();
+        assert!(accounts.iter().all(|account| account["status"] == "ready"));
+        accounts
+            .iter()
+            .map(|account| account["blockhash"].clone())
+            .collect()
+    };
+
+    let output = context.run(&["nonce-pool", "create", pool_file, "2"]);
+    assert_signature(&output[0]);
+    let created = nonces();
+    assert_eq!(created.len(), 2);
+    let cursor = || -> Value {
+        let pool: Value = serde_json::from_slice(&std::fs::read(pool_file).unwrap()).unwrap();
+        pool["cursor"].clone()
+    };
+    assert_eq!(cursor(), 0);
+    // Creating the same pool again has nothing left to create, but the file
+    // can't be replaced by another pool
+    assert!(context
+        .run(&["nonce-pool", "create", pool_file, "2"])
+        .is_empty());
+    assert_eq!(nonces(), created);
+    context.run_err(&["nonce-pool", "create", pool_file, "3"]);
+
+    // A bulk command uses the nonce at the cursor of the pool, and the next
+    // command the one after it
+    let token = context.create_token(&[]);
+    let gc = || {
+        let aux = Keypair::new();
+        let aux_path = context.write_keypair("aux", &aux);
+        context.run(&["create-account", &token.to_string(), &aux_path]);
+        let output = context.run(&["gc", "--nonce-pool", pool_file]);
+        assert_signature(&output[1]);
+        assert!(!context.exists(&aux.pubkey()));
+        nonces()
+    };
+    let first = gc();
+    assert_ne!(first[0], created[0]);
+    assert_eq!(first[1], created[1]);
+    assert_eq!(cursor(), 1);
+    let second = gc();
+    assert_eq!(second[0], first[0]);
+    assert_ne!(second[1], first[1]);
+    assert_eq!(cursor(), 0);
+
+    let output = context.run(&["nonce-pool", "advance", pool_file]);
+    assert_signature(&output[0]);
+    let advanced = nonces();
+    assert_ne!(advanced[0], second[0]);
+    assert_ne!(advanced[1], second[1]);
+}
+
+#[test]
+fn test_authorize() {
+    let context = TestContext::new();
+    let token = context.create_token(&["--enable-freeze"]);
+    let new_authority = Pubkey::new_unique();
+
+    let output = context.run(&[
+        "authorize",
+        &token.to_string(),
+        "freeze",
+        &new_authority.to_string(),
+    ]);
+    assert_signature(&output[0]);
+    let mint = context.unpack::<Mint>(&token);
+    assert_eq!(mint.freeze_authority, COption::Some(new_authority));
+    assert_eq!(mint.mint_authority, COption::Some(context.payer.pubkey()));
+
+    context.run(&["authorize", &token.to_string(), "mint", "--disable"]);
+    assert_eq!(context.unpack::<Mint>(&token).mint_authority, COption::None);
+
+    // The supply is now fixed
+    let error = context.run_err(&["mint", &token.to_string(), "1"]);
+    assert!(!error.is_empty());
+    assert_eq!(context.unpack::<Mint>(&token).supply, 0);
+}
+
+#[test]
+fn test_create_group() {
+    let context = TestContext::new();
+    let group_token = context.create_token(&[]);
+    let member_token = context.create_token(&[]);
+
+    context.run(&["create-group", &group_token.to_string(), "--max-size", "2"]);
+    let group = hpl_token::get_group_address(&group_token);
+    assert_eq!(
+        context.unpack::<TokenGroup>(&group).update_authority,
+        COption::Some(context.payer.pubkey())
+    );
+    context.run(&[
+        "add-group-member",
+        &group_token.to_string(),
+        &member_token.to_string(),
+    ]);
+    let member =
+        context.unpack::<TokenGroupMember>(&hpl_token::get_group_member_address(&member_token));
+    assert_eq!(member.group, group);
+    assert_eq!(context.unpack::<TokenGroup>(&group).size, 1);
+
+    // Without an update authority, no one can add members
+    let fixed_group_token = context.create_token(&[]);
+    let other_token = context.create_token(&[]);
+    context.run(&[
+        "create-group",
+        &fixed_group_token.to_string(),
+        "--max-size",
+        "2",
+        "--no-update-authority",
+    ]);
+    let fixed_group = hpl_token::get_group_address(&fixed_group_token);
+    assert_eq!(
+        context.unpack::<TokenGroup>(&fixed_group).update_authority,
+        COption::None
+    );
+    context.run_err(&[
+        "add-group-member",
+        &fixed_group_token.to_string(),
+        &other_token.to_string(),
+    ]);
+    assert!(!context.exists(&hpl_token::get_group_member_address(&other_token)));
+}
+
+#[test]
+fn test_update_token_info() {
+    let context = TestContext::new();
+    let token = context.create_token(&["--symbol", "OLD", "--name", "Old name"]);
+
+    let output = context.run(&[
+        "update-token-info",
+        &token.to_string(),
+        "--symbol",
+        "NEW",
+        "--icon",
+        "https://example.com/icon.png",
+    ]);
+    assert_signature(&output[0]);
+    let mint_meta = context.unpack::<MintMeta>(&mint_meta_address(&token));
+    assert_eq!(mint_meta.symbol, "NEW");
+    assert_eq!(mint_meta.name, "Old name");
+    assert_eq!(mint_meta.icon, "https://example.com/icon.png");
+
+    let output = context.run(&["token-info", &token.to_string()]);
+    let token_info = command_output(&output[0]);
+    assert_eq!(token_info["symbol"], "NEW");
+    assert_eq!(
+        token_info["mintMetaAuthority"],
+        context.payer.pubkey().to_string()
+    );
+
+    // Only the meta authority may update the token info
+    let other = Keypair::new();
+    let other_path = context.write_keypair("other", &other);
+    let error = context.run_err(&[
+        "update-token-info",
+        &token.to_string(),
+        "--meta-authority",
+        &other_path,
+        "--name",
+        "Stolen",
+    ]);
+    assert!(!error.is_empty());
+    assert_eq!(
+        context.unpack::<MintMeta>(&mint_meta_address(&token)).name,
+        "Old name"
+    );
+}
+
+#[test]
+fn test_holders() {
+    let context = TestContext::new();
+    let token = context.create_token(&[]);
+    let other = Pubkey::new_unique();
+    context.run(&["create-account", &token.to_string()]);
+    context.run(&["mint", &token.to_string(), "75"]);
+    context.run(&[
+        "transfer",
+        &token.to_string(),
+        "25",
+        &other.to_string(),
+        "--fund-recipient",
+    ]);
+
+    let output = context.run(&["holders", &token.to_string(), "--top", "1"]);
+    assert_eq!(output[0]["commandName"], "holders");
+    let holders = command_output(&output[0]);
+    assert_eq!(holders["holderCount"], 2);
+    assert_eq!(holders["accountCount"], 2);
+    assert_eq!(holders["total"], "75");
+    assert_eq!(holders["supplyMatches"], true);
+    assert!((holders["gini"].as_f64().unwrap() - 1.0 / 6.0).abs() < 1e-9);
+    let top_holders = holders["topHolders"].as_array().unwrap();
+    assert_eq!(top_holders.len(), 1);
+    assert_eq!(top_holders[0]["owner"], context.payer.pubkey().to_string());
+    assert_eq!(top_holders[0]["balance"], "50");
+
+    let output = context
+        .display_command(&["holders", &token.to_string(), "--csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    assert!(csv
+        .lines()
+        .nth(2)
+        .unwrap()
+        .starts_with(&format!("2,{},25,", other)));
+}
+
+/// Writes a `create-token --spec` file minting 1000 tokens, 250 of them to `recipient`
+fn write_token_spec(context: &TestContext, recipient: &Pubkey) -> String {
+    let path = context.dir.path().join("token.toml");
+    std::fs::write(
+        &path,
+        format!(
+            "decimals = 2\n\
+             mint-authority = \"none\"\n\
+             freeze-authority = \"{}\"\n\
+             symbol = \"TKN\"\n\
+             name = \"Spec token\"\n\
+             initial-supply = \"1000\"\n\
+             [[recipients]]\n\
+             address = \"{}\"\n\
+             amount = \"250\"\n",
+            context.payer.pubkey(),
+            recipient
+        ),
+    )
+    .unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_create_token_spec() {
+    let context = TestContext::new();
+    let recipient = Pubkey::new_unique();
+    let spec_path = write_token_spec(&context, &recipient);
+
+    let output = context.run(&["create-token", "--spec", &spec_path]);
+    let token = address(&output[0]);
+    let mint = context.unpack::<Mint>(&token);
+    assert_eq!(mint.decimals, 2);
+    assert_eq!(mint.supply, 100_000);
+    assert_eq!(mint.mint_authority, COption::None);
+    assert_eq!(mint.freeze_authority, COption::Some(context.payer.pubkey()));
+    let mint_meta = context.unpack::<MintMeta>(&mint_meta_address(&token));
+    assert_eq!(mint_meta.symbol, "TKN");
+    assert_eq!(mint_meta.name, "Spec token");
+    assert_eq!(mint_meta.authority, COption::Some(context.payer.pubkey()));
+    assert_eq!(
+        context.token_balance(&get_associated_token_address(&recipient, &token)),
+        25_000
+    );
+    assert_eq!(
+        context.token_balance(&get_associated_token_address(
+            &context.payer.pubkey(),
+            &token
+        )),
+        75_000
+    );
+
+    // the spec replaces the command line settings
+    context.run_err(&["create-token", "--spec", &spec_path, "--decimals", "6"]);
+}
+
+#[test]
+fn test_create_token_dry_run() {
+    let context = TestContext::new();
+    let recipient = Pubkey::new_unique();
+    let spec_path = write_token_spec(&context, &recipient);
+
+    let output = context.run(&["create-token", "--spec", &spec_path, "--dry-run"]);
+    assert_eq!(output.len(), 1);
+    assert_eq!(output[0]["commandName"], "create-token");
+    let plan = command_output(&output[0]);
+    let token = Pubkey::from_str(plan["token"].as_str().unwrap()).unwrap();
+    assert_eq!(plan["decimals"], 2);
+    assert!(plan["mintAuthority"].is_null());
+    assert_eq!(plan["supply"], "1000");
+    let accounts = plan["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 4);
+    assert_eq!(accounts[2]["owner"], recipient.to_string());
+    assert_eq!(accounts[2]["amount"], "250");
+    assert_eq!(accounts[3]["owner"], context.payer.pubkey().to_string());
+    assert_eq!(accounts[3]["amount"], "750");
+    assert!(plan["rent"].as_u64().unwrap() > 0);
+
+    // nothing was created
+    assert!(!context.exists(&token));
+    assert!(!context.exists(&mint_meta_address(&token)));
+}
+
+#[test]
+fn test_tx_multisig() {
+    let context = TestContext::new();
+    let token = context.create_token(&[]);
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let signer_paths: Vec<String> = signers
+        .iter()
+        .enumerate()
+        .map(|(index, signer)| context.write_keypair(&format!("signer-{}", index), signer))
+        .collect();
+
+    let output = context.run(&[
+        "create-multisig",
+        "2",
+        &signers[0].pubkey().to_string(),
+        &signers[1].pubkey().to_string(),
+        &signers[2].pubkey().to_string(),
+    ]);
+    let multisig = address(&output[0]);
+    let source = get_associated_token_address(&multisig, &token);
+    context.run(&[
+        "create-account",
+        &token.to_string(),
+        "--owner",
+        &multisig.to_string(),
+    ]);
+    context.run(&["mint", &token.to_string(), "10", &source.to_string()]);
+    let output = context.run(&["create-account", &token.to_string()]);
+    let recipient = address(&output[0]);
+
+    // The transfer needs the fee payer and two of the three multisig signers
+    let file = context.dir.path().join("transfer.json");
+    let file = file.to_str().unwrap();
+    let blockhash = context.rpc_client.get_latest_blockhash().unwrap();
+    let output = context.run(&[
+        "tx",
+        "build",
+        file,
+        "transfer",
+        &token.to_string(),
+        "4",
+        &recipient.to_string(),
+        "--from",
+        &source.to_string(),
+        "--owner",
+        &multisig.to_string(),
+        "--multisig-signer",
+        &signers[0].pubkey().to_string(),
+        "--multisig-signer",
+        &signers[2].pubkey().to_string(),
+        "--mint-decimals",
+        "6",
+        "--blockhash",
+        &blockhash.to_string(),
+    ]);
+    let built = command_output(&output[0]);
+    assert_eq!(
+        built["missingMultisigSigners"],
+        serde_json::json!([
+            signers[0].pubkey().to_string(),
+            signers[2].pubkey().to_string()
+        ])
+    );
+    // The client keypair paying the fee signed when building
+    assert!(!built["missingSigners"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::String(context.payer.pubkey().to_string())));
+
+    // Not enough signatures yet, nor from a member the transaction doesn't use
+    context.run_err(&["tx", "submit", file]);
+    context.run_err(&["tx", "sign", file, "--signer", &signer_paths[1]]);
+
+    let output = context.run(&["tx", "sign", file, "--signer", &signer_paths[0]]);
+    let signed = command_output(&output[0]);
+    assert_eq!(
+        signed["missingMultisigSigners"],
+        serde_json::json!([signers[2].pubkey().to_string()])
+    );
+    context.run_err(&["tx", "submit", file]);
+    assert_eq!(context.token_balance(&source), 10_000_000);
+
+    let output = context.run(&["tx", "sign", file, "--signer", &signer_paths[2]]);
+    let signed = command_output(&output[0]);
+    assert_eq!(signed["missingSigners"], serde_json::json!([]));
+    let output = context.run(&["tx", "submit", file]);
+    assert_signature(&output[0]);
+    assert_eq!(context.token_balance(&source), 6_000_000);
+    assert_eq!(context.token_balance(&recipient), 4_000_000);
+}
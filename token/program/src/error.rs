@@ -74,6 +74,17 @@ pub enum TokenError {
     /// Instruction does not support non-native tokens
     #[error("Instruction does not support non-native tokens")]
     NonNativeNotSupported,
+
+    // 20
+    /// The group has reached its maximum number of members
+    #[error("Group has reached its maximum size")]
+    GroupMaxSizeExceeded,
+    /// The group max size cannot be lower than its current size
+    #[error("Group max size lower than its current size")]
+    InvalidGroupMaxSize,
+    /// The lamports of a pending authority must be returned to its payer
+    #[error("Destination is not the payer of the pending authority")]
+    PendingAuthorityPayerMismatch,
 }
 impl From<TokenError> for ProgramError {
     fn from(e: TokenError) -> Self {
@@ -1,6 +1,9 @@
 //! Instruction types
 
-use crate::{check_program_account, error::TokenError};
+use crate::{
+    check_program_account, error::TokenError, get_group_address_and_bump_seed,
+    get_group_member_address_and_bump_seed, get_pending_authority_address_and_bump_seed,
+};
 use huione_program::{
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
@@ -15,6 +18,8 @@ use std::mem::size_of;
 pub const MIN_SIGNERS: usize = 1;
 /// Maximum number of multisignature signers (max N)
 pub const MAX_SIGNERS: usize = 11;
+/// Maximum number of multisignature v2 signers (max N)
+pub const MAX_SIGNERS_V2: usize = 64;
 
 /// Instructions supported by the token program.
 #[repr(C)]
@@ -449,6 +454,167 @@ pub enum TokenInstruction {
         /// token icon url
         icon: String,
     },
+    /// Initializes the token group account of a mint, allowing other mints to be
+    /// registered as its members.  The group account is the program derived
+    /// address of the group mint, and is created by this instruction if needed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable,signer]` The fee payer.
+    ///   1. `[writable]` The group PDA address of the mint.
+    ///   2. `[]` The group mint.
+    ///   3. `[signer]` The mint's minting authority.
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable,signer]` The fee payer.
+    ///   1. `[writable]` The group PDA address of the mint.
+    ///   2. `[]` The group mint.
+    ///   3. `[]` The mint's multisignature minting authority.
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    ///   6. ..6+M `[signer]` M signer accounts.
+    InitializeGroup {
+        /// The authority allowed to add members and update the group.
+        update_authority: COption<Pubkey>,
+        /// The maximum number of group members.
+        max_size: u32,
+    },
+    /// Registers a mint as a member of a token group.  The member account is the
+    /// program derived address of the member mint, and is created by this
+    /// instruction if needed.  A mint may only be a member of one group.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable,signer]` The fee payer.
+    ///   1. `[writable]` The member PDA address of the member mint.
+    ///   2. `[]` The member mint.
+    ///   3. `[signer]` The member mint's minting authority.
+    ///   4. `[writable]` The group account.
+    ///   5. `[signer]` The group's update authority.
+    ///   6. `[]` System program
+    ///   7. `[]` Rent sysvar
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable,signer]` The fee payer.
+    ///   1. `[writable]` The member PDA address of the member mint.
+    ///   2. `[]` The member mint.
+    ///   3. `[]` The member mint's minting authority.
+    ///   4. `[writable]` The group account.
+    ///   5. `[]` The group's update authority.
+    ///   6. `[]` System program
+    ///   7. `[]` Rent sysvar
+    ///   8. ..8+M `[signer]` M signer accounts.
+    InitializeMember,
+    /// Updates the maximum number of members of a token group.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The group account.
+    ///   1. `[signer]` The group's update authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The group account.
+    ///   1. `[]` The group's multisignature update authority.
+    ///   2. ..2+M `[signer]` M signer accounts
+    UpdateGroupMaxSize {
+        /// The new maximum number of group members.
+        max_size: u32,
+    },
+    /// Proposes a new authority of a mint, account or mint meta.  Unlike
+    /// `SetAuthority`, the authority is not changed until the proposed
+    /// authority signs an `AcceptAuthority` instruction.  The proposal is
+    /// stored in the pending authority PDA of the target and authority type,
+    /// and replaces any outstanding proposal.  The payer of the PDA is
+    /// refunded when the proposal is accepted.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable,signer]` The fee payer.
+    ///   1. `[writable]` The pending authority PDA address.
+    ///   2. `[]` The mint, account or mint meta to change the authority of.
+    ///   3. `[signer]` The current authority of the mint, account or mint meta.
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable,signer]` The fee payer.
+    ///   1. `[writable]` The pending authority PDA address.
+    ///   2. `[]` The mint, account or mint meta to change the authority of.
+    ///   3. `[]` The current multisignature authority.
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    ///   6. ..6+M `[signer]` M signer accounts.
+    ProposeAuthority {
+        /// The type of authority to update.
+        authority_type: AuthorityType,
+        /// The proposed authority
+        new_authority: Pubkey,
+    },
+    /// Accepts a proposed authority, which then replaces the current
+    /// authority.  The pending authority account is closed and its lamports
+    /// are returned to the payer of the proposal.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint, account or mint meta to change the authority of.
+    ///   1. `[writable]` The pending authority PDA address.
+    ///   2. `[writable]` The payer of the proposal, receiving the pending account's lamports.
+    ///   3. `[signer]` The proposed authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint, account or mint meta to change the authority of.
+    ///   1. `[writable]` The pending authority PDA address.
+    ///   2. `[writable]` The payer of the proposal, receiving the pending account's lamports.
+    ///   3. `[]` The proposed multisignature authority.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    AcceptAuthority {
+        /// The type of authority to update.
+        authority_type: AuthorityType,
+    },
+    /// Like `InitializeMultisig`, but for a resizable multisignature v2
+    /// account holding up to 64 signers.  The account must be allocated with
+    /// exactly `MultisigV2::get_packed_len(N)` bytes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The multisignature account to initialize.
+    ///   1. `[]` Rent sysvar
+    ///   2. ..2+N. `[]` The signer accounts, must equal to N where 1 <= N <=
+    ///      64.
+    InitializeMultisigV2 {
+        /// The number of signers (M) required to validate this multisignature
+        /// account.
+        m: u8,
+    },
+    /// Replaces the signers and the number of required signers of a
+    /// multisignature account.  The update must be signed by M of the current
+    /// signers.  The account is resized to fit the new signers, the payer
+    /// funding any additional rent or receiving the rent freed by a smaller
+    /// account, and a v1 multisignature account is converted to a v2 one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The multisignature account to update.
+    ///   1. `[writable,signer]` The payer of any additional rent, refunded the
+    ///      rent freed by a smaller account.
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    ///   4. ..4+N. `[]` The new signer accounts, where 1 <= N <= 64.
+    ///   4+N. ..4+N+M `[signer]` M of the current signer accounts.
+    UpdateMultisig {
+        /// The new number of signers (M) required to validate this
+        /// multisignature account.
+        m: u8,
+        /// The number of new signer accounts (N).
+        n: u8,
+    },
 }
 impl TokenInstruction {
     /// Unpacks a byte buffer into a [TokenInstruction](enum.TokenInstruction.html).
@@ -589,6 +755,46 @@ impl TokenInstruction {
                     icon: icon.to_string(),
                 }
             }
+            23 => {
+                let (update_authority, rest) = Self::unpack_pubkey_option(rest)?;
+                let max_size = Self::unpack_u32(rest)?;
+                Self::InitializeGroup {
+                    update_authority,
+                    max_size,
+                }
+            }
+            24 => Self::InitializeMember,
+            25 => {
+                let max_size = Self::unpack_u32(rest)?;
+                Self::UpdateGroupMaxSize { max_size }
+            }
+            26 => {
+                let (authority_type, rest) = rest
+                    .split_first()
+                    .ok_or_else(|| ProgramError::from(InvalidInstruction))
+                    .and_then(|(&t, rest)| Ok((AuthorityType::from(t)?, rest)))?;
+                let (new_authority, _rest) = Self::unpack_pubkey(rest)?;
+
+                Self::ProposeAuthority {
+                    authority_type,
+                    new_authority,
+                }
+            }
+            27 => {
+                let &t = rest.get(0).ok_or(InvalidInstruction)?;
+                Self::AcceptAuthority {
+                    authority_type: AuthorityType::from(t)?,
+                }
+            }
+            28 => {
+                let &m = rest.get(0).ok_or(InvalidInstruction)?;
+                Self::InitializeMultisigV2 { m }
+            }
+            29 => {
+                let &m = rest.get(0).ok_or(InvalidInstruction)?;
+                let &n = rest.get(1).ok_or(InvalidInstruction)?;
+                Self::UpdateMultisig { m, n }
+            }
 
             _ => return Err(TokenError::InvalidInstruction.into()),
         })
@@ -690,6 +896,40 @@ impl TokenInstruction {
                 let data = symbol.to_string() + "\n" + name + "\n" + icon + "\n";
                 buf.extend_from_slice(data.as_ref());
             }
+            &Self::InitializeGroup {
+                ref update_authority,
+                max_size,
+            } => {
+                buf.push(23);
+                Self::pack_pubkey_option(update_authority, &mut buf);
+                buf.extend_from_slice(&max_size.to_le_bytes());
+            }
+            &Self::InitializeMember => buf.push(24),
+            &Self::UpdateGroupMaxSize { max_size } => {
+                buf.push(25);
+                buf.extend_from_slice(&max_size.to_le_bytes());
+            }
+            Self::ProposeAuthority {
+                authority_type,
+                ref new_authority,
+            } => {
+                buf.push(26);
+                buf.push(authority_type.into());
+                buf.extend_from_slice(new_authority.as_ref());
+            }
+            Self::AcceptAuthority { authority_type } => {
+                buf.push(27);
+                buf.push(authority_type.into());
+            }
+            &Self::InitializeMultisigV2 { m } => {
+                buf.push(28);
+                buf.push(m);
+            }
+            &Self::UpdateMultisig { m, n } => {
+                buf.push(29);
+                buf.push(m);
+                buf.push(n);
+            }
         };
         buf
     }
@@ -716,6 +956,14 @@ impl TokenInstruction {
         }
     }
 
+    fn unpack_u32(input: &[u8]) -> Result<u32, ProgramError> {
+        input
+            .get(..4)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or_else(|| TokenError::InvalidInstruction.into())
+    }
+
     fn pack_pubkey_option(value: &COption<Pubkey>, buf: &mut Vec<u8>) {
         match *value {
             COption::Some(ref key) => {
@@ -744,7 +992,7 @@ pub enum AuthorityType {
 }
 
 impl AuthorityType {
-    fn into(&self) -> u8 {
+    pub(crate) fn into(&self) -> u8 {
         match self {
             AuthorityType::MintTokens => 0,
             AuthorityType::FreezeAccount => 1,
@@ -754,7 +1002,7 @@ impl AuthorityType {
         }
     }
 
-    fn from(index: u8) -> Result<Self, ProgramError> {
+    pub(crate) fn from(index: u8) -> Result<Self, ProgramError> {
         match index {
             0 => Ok(AuthorityType::MintTokens),
             1 => Ok(AuthorityType::FreezeAccount),
@@ -974,14 +1222,10 @@ pub fn set_authority(
     check_program_account(token_program_id)?;
 
     let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-
-    if authority_type == AuthorityType::UpdateMeta {
-        let (mint_meta, _) =
-        Pubkey::find_program_address(&[b"MintMeta", &owned_pubkey.to_bytes()], token_program_id);
-        accounts.push(AccountMeta::new(mint_meta, false));
-    }else{
-        accounts.push(AccountMeta::new(*owned_pubkey, false));
-    }
+    accounts.push(AccountMeta::new(
+        authority_target(token_program_id, owned_pubkey, &authority_type),
+        false,
+    ));
     accounts.push(AccountMeta::new_readonly(
         *owner_pubkey,
         signer_pubkeys.is_empty(),
@@ -1428,11 +1672,291 @@ pub fn initialize_mint_meta(
     })
 }
 
+/// Creates an `InitializeGroup` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_group(
+    token_program_id: &Pubkey,
+    payer: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    update_authority_pubkey: Option<&Pubkey>,
+    max_size: u32,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let update_authority = update_authority_pubkey.cloned().into();
+    let data = TokenInstruction::InitializeGroup {
+        update_authority,
+        max_size,
+    }
+    .pack();
+
+    let (group_pubkey, _) = get_group_address_and_bump_seed(mint_pubkey, token_program_id);
+    let mut accounts = Vec::with_capacity(6 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new(group_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *mint_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeMember` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_member(
+    token_program_id: &Pubkey,
+    payer: &Pubkey,
+    member_mint_pubkey: &Pubkey,
+    member_mint_authority_pubkey: &Pubkey,
+    group_mint_pubkey: &Pubkey,
+    group_update_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeMember.pack();
+
+    let (member_pubkey, _) =
+        get_group_member_address_and_bump_seed(member_mint_pubkey, token_program_id);
+    let (group_pubkey, _) = get_group_address_and_bump_seed(group_mint_pubkey, token_program_id);
+    let mut accounts = Vec::with_capacity(8 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new(member_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*member_mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *member_mint_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(group_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *group_update_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateGroupMaxSize` instruction.
+pub fn update_group_max_size(
+    token_program_id: &Pubkey,
+    group_mint_pubkey: &Pubkey,
+    update_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    max_size: u32,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::UpdateGroupMaxSize { max_size }.pack();
+
+    let (group_pubkey, _) = get_group_address_and_bump_seed(group_mint_pubkey, token_program_id);
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(group_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *update_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Returns the address whose authority is changed by `SetAuthority`, `ProposeAuthority` and
+/// `AcceptAuthority`. The `UpdateMeta` authority lives in the MintMeta PDA of the mint.
+fn authority_target(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    authority_type: &AuthorityType,
+) -> Pubkey {
+    if *authority_type == AuthorityType::UpdateMeta {
+        Pubkey::find_program_address(&[b"MintMeta", &owned_pubkey.to_bytes()], token_program_id).0
+    } else {
+        *owned_pubkey
+    }
+}
+
+/// Creates a `ProposeAuthority` instruction.
+pub fn propose_authority(
+    token_program_id: &Pubkey,
+    payer: &Pubkey,
+    owned_pubkey: &Pubkey,
+    new_authority_pubkey: &Pubkey,
+    authority_type: AuthorityType,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    let target = authority_target(token_program_id, owned_pubkey, &authority_type);
+    let (pending_pubkey, _) =
+        get_pending_authority_address_and_bump_seed(&target, &authority_type, token_program_id);
+    let mut accounts = Vec::with_capacity(6 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new(pending_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(target, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    let data = TokenInstruction::ProposeAuthority {
+        authority_type,
+        new_authority: *new_authority_pubkey,
+    }
+    .pack();
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AcceptAuthority` instruction.
+pub fn accept_authority(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    authority_type: AuthorityType,
+    new_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    let target = authority_target(token_program_id, owned_pubkey, &authority_type);
+    let (pending_pubkey, _) =
+        get_pending_authority_address_and_bump_seed(&target, &authority_type, token_program_id);
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(target, false));
+    accounts.push(AccountMeta::new(pending_pubkey, false));
+    accounts.push(AccountMeta::new(*payer_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *new_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    let data = TokenInstruction::AcceptAuthority { authority_type }.pack();
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeMultisigV2` instruction.
+pub fn initialize_multisig_v2(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    m: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    if !is_valid_signer_index_v2(m as usize)
+        || !is_valid_signer_index_v2(signer_pubkeys.len())
+        || m as usize > signer_pubkeys.len()
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let data = TokenInstruction::InitializeMultisigV2 { m }.pack();
+
+    let mut accounts = Vec::with_capacity(1 + 1 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `UpdateMultisig` instruction.
+pub fn update_multisig(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    payer: &Pubkey,
+    new_signer_pubkeys: &[&Pubkey],
+    m: u8,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    if !is_valid_signer_index_v2(m as usize)
+        || !is_valid_signer_index_v2(new_signer_pubkeys.len())
+        || m as usize > new_signer_pubkeys.len()
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let data = TokenInstruction::UpdateMultisig {
+        m,
+        n: new_signer_pubkeys.len() as u8,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(4 + new_signer_pubkeys.len() + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new(*payer, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for new_signer_pubkey in new_signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**new_signer_pubkey, false));
+    }
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
 /// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS
 pub fn is_valid_signer_index(index: usize) -> bool {
     (MIN_SIGNERS..=MAX_SIGNERS).contains(&index)
 }
 
+/// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS_V2
+pub fn is_valid_signer_index_v2(index: usize) -> bool {
+    (MIN_SIGNERS..=MAX_SIGNERS_V2).contains(&index)
+}
+
 #[cfg(test)]
 mod test {
 
@@ -1605,5 +2129,65 @@ mod test {
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeGroup {
+            update_authority: COption::Some(Pubkey::new(&[5u8; 32])),
+            max_size: 3,
+        };
+        let packed = check.pack();
+        let mut expect = vec![23u8, 1];
+        expect.extend_from_slice(&[5u8; 32]);
+        expect.extend_from_slice(&[3, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeMember;
+        let packed = check.pack();
+        let expect = vec![24u8];
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::UpdateGroupMaxSize { max_size: 7 };
+        let packed = check.pack();
+        let expect = vec![25u8, 7, 0, 0, 0];
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::ProposeAuthority {
+            authority_type: AuthorityType::UpdateMeta,
+            new_authority: Pubkey::new(&[6u8; 32]),
+        };
+        let packed = check.pack();
+        let mut expect = vec![26u8, 4];
+        expect.extend_from_slice(&[6u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::AcceptAuthority {
+            authority_type: AuthorityType::CloseAccount,
+        };
+        let packed = check.pack();
+        let expect = vec![27u8, 3];
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeMultisigV2 { m: 40 };
+        let packed = check.pack();
+        let expect = vec![28u8, 40];
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::UpdateMultisig { m: 3, n: 64 };
+        let packed = check.pack();
+        let expect = vec![29u8, 3, 64];
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
     }
 }
@@ -6,6 +6,7 @@
 
 pub mod error;
 pub mod instruction;
+pub mod merkle;
 pub mod native_mint;
 pub mod native_mint_info;
 pub mod processor;
@@ -44,6 +45,64 @@ huione_program::declare_id!("HuiToken11111111111111111111111111111111111");
 // huione_program::declare_id!("3LbxkbtrnKaUeqYLpmXx8QNUAYfw8PUqcjib4xLdvGyJ");
 
 
+/// Seed prefix of the token group account derived from a group mint
+pub const TOKEN_GROUP_SEED: &[u8] = b"TokenGroup";
+/// Seed prefix of the token group member account derived from a member mint
+pub const TOKEN_GROUP_MEMBER_SEED: &[u8] = b"TokenGroupMember";
+
+/// Derives the token group address and bump seed for the given group mint
+pub fn get_group_address_and_bump_seed(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_GROUP_SEED, &mint.to_bytes()], program_id)
+}
+
+/// Derives the token group address for the given group mint
+pub fn get_group_address(mint: &Pubkey) -> Pubkey {
+    get_group_address_and_bump_seed(mint, &id()).0
+}
+
+/// Derives the token group member address and bump seed for the given member mint
+pub fn get_group_member_address_and_bump_seed(
+    member_mint: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TOKEN_GROUP_MEMBER_SEED, &member_mint.to_bytes()],
+        program_id,
+    )
+}
+
+/// Derives the token group member address for the given member mint
+pub fn get_group_member_address(member_mint: &Pubkey) -> Pubkey {
+    get_group_member_address_and_bump_seed(member_mint, &id()).0
+}
+
+/// Seed prefix of the pending authority account of a two-step authority transfer
+pub const PENDING_AUTHORITY_SEED: &[u8] = b"PendingAuthority";
+
+/// Derives the pending authority address and bump seed for the given target and authority type
+pub fn get_pending_authority_address_and_bump_seed(
+    target: &Pubkey,
+    authority_type: &instruction::AuthorityType,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PENDING_AUTHORITY_SEED,
+            &target.to_bytes(),
+            &[authority_type.into()],
+        ],
+        program_id,
+    )
+}
+
+/// Derives the pending authority address for the given target and authority type
+pub fn get_pending_authority_address(
+    target: &Pubkey,
+    authority_type: &instruction::AuthorityType,
+) -> Pubkey {
+    get_pending_authority_address_and_bump_seed(target, authority_type, &id()).0
+}
+
 /// Checks that the supplied program ID is the correct one for HPL-token
 pub fn check_program_account(hpl_token_program_id: &Pubkey) -> ProgramResult {
     if hpl_token_program_id != &id() {
@@ -0,0 +1,155 @@
+//! Merkle tree over token balances
+//!
+//! Leaves and inner nodes are hashed with distinct prefixes so that an inner
+//! node can never be presented as a leaf. Sibling pairs are hashed in sorted
+//! order, so a proof is a plain list of sibling hashes and can be checked on
+//! chain without any position bits.
+
+use huione_program::{
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+};
+
+/// Prefix of a hashed leaf
+pub const LEAF_PREFIX: &[u8] = &[0];
+/// Prefix of a hashed inner node
+pub const NODE_PREFIX: &[u8] = &[1];
+
+/// Hashes arbitrary leaf data
+pub fn hash_leaf(data: &[&[u8]]) -> Hash {
+    let mut parts = Vec::with_capacity(data.len() + 1);
+    parts.push(LEAF_PREFIX);
+    parts.extend_from_slice(data);
+    hashv(&parts)
+}
+
+/// Hashes the balance leaf of the holder at `index` in the tree.
+///
+/// This is also the leaf the merkle distributor claims against, so the root of
+/// a balance snapshot can be used as the root of a distributor as is.
+pub fn balance_leaf(index: u64, owner: &Pubkey, amount: u128) -> Hash {
+    hash_leaf(&[&index.to_le_bytes(), owner.as_ref(), &amount.to_le_bytes()])
+}
+
+/// Hashes two sibling nodes
+pub fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    if a.as_ref() <= b.as_ref() {
+        hashv(&[NODE_PREFIX, a.as_ref(), b.as_ref()])
+    } else {
+        hashv(&[NODE_PREFIX, b.as_ref(), a.as_ref()])
+    }
+}
+
+/// Checks that `leaf` is included in the tree with the given root
+pub fn verify_proof(proof: &[Hash], root: &Hash, leaf: Hash) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+/// Merkle tree keeping every layer, used to produce inclusion proofs
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleTree {
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// Builds a tree from the leaf hashes. An odd node at the end of a layer is
+    /// carried up unchanged.
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    /// Whether the tree has no leaves
+    pub fn is_empty(&self) -> bool {
+        self.layers[0].is_empty()
+    }
+
+    /// Root of the tree, the default hash for an empty tree
+    pub fn root(&self) -> Hash {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Inclusion proof of the leaf at `index`
+    pub fn proof(&self, mut index: usize) -> Option<Vec<Hash>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut proof = vec![];
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_proofs() {
+        assert_eq!(MerkleTree::new(vec![]).root(), Hash::default());
+
+        for count in 1..=9u128 {
+            let holders: Vec<(Pubkey, u128)> = (0..count)
+                .map(|amount| (Pubkey::new_unique(), amount * 100))
+                .collect();
+            let tree = MerkleTree::new(
+                holders
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (owner, amount))| balance_leaf(index as u64, owner, *amount))
+                    .collect(),
+            );
+            let root = tree.root();
+            for (index, (owner, amount)) in holders.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                let index = index as u64;
+                assert!(verify_proof(
+                    &proof,
+                    &root,
+                    balance_leaf(index, owner, *amount)
+                ));
+                assert!(!verify_proof(
+                    &proof,
+                    &root,
+                    balance_leaf(index, owner, amount + 1)
+                ));
+                assert!(!verify_proof(
+                    &proof,
+                    &root,
+                    balance_leaf(index + 1, owner, *amount)
+                ));
+            }
+            assert_eq!(tree.proof(holders.len()), None);
+        }
+    }
+}
@@ -2,8 +2,17 @@
 
 use crate::{
     error::{TokenError},
-    instruction::{is_valid_signer_index, AuthorityType, TokenInstruction, MAX_SIGNERS},
-    state::{Account, AccountState, Mint, MintMeta, Multisig},
+    get_group_address_and_bump_seed, get_group_member_address_and_bump_seed,
+    instruction::{
+        is_valid_signer_index, is_valid_signer_index_v2, AuthorityType, TokenInstruction,
+        MAX_SIGNERS, MAX_SIGNERS_V2,
+    },
+    state::{
+        Account, AccountState, Mint, MintMeta, Multisig, MultisigV2, PendingAuthority,
+        TokenGroup, TokenGroupMember,
+    },
+    get_pending_authority_address_and_bump_seed, PENDING_AUTHORITY_SEED,
+    TOKEN_GROUP_MEMBER_SEED, TOKEN_GROUP_SEED,
 };
 use num_traits::FromPrimitive;
 use huione_program::{
@@ -149,6 +158,119 @@ impl Processor {
         Ok(())
     }
 
+    /// Processes a [InitializeMultisigV2](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_multisig_v2(accounts: &[AccountInfo], m: u8) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let multisig_info_data_len = multisig_info.data_len();
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let mut multisig = MultisigV2::unpack_unchecked(&multisig_info.data.borrow())?;
+        if multisig.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(multisig_info.lamports(), multisig_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+
+        let signer_infos = account_info_iter.as_slice();
+        if MultisigV2::get_packed_len(signer_infos.len()) != multisig_info_data_len {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        if !is_valid_signer_index_v2(m as usize) || m as usize > signer_infos.len() {
+            return Err(TokenError::InvalidNumberOfRequiredSigners.into());
+        }
+        multisig.m = m;
+        multisig.signers = signer_infos.iter().map(|signer_info| *signer_info.key).collect();
+        multisig.is_initialized = true;
+
+        multisig.pack(&mut multisig_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [UpdateMultisig](enum.TokenInstruction.html) instruction.
+    pub fn process_update_multisig(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        m: u8,
+        n: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let remaining_infos = account_info_iter.as_slice();
+        if remaining_infos.len() < n as usize {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (new_signer_infos, signer_infos) = remaining_infos.split_at(n as usize);
+
+        let data_len = multisig_info.data_len();
+        if multisig_info.owner != program_id
+            || (data_len != Multisig::LEN && MultisigV2::capacity(data_len).is_none())
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // The multisig is its own authority
+        Self::validate_owner(program_id, multisig_info.key, multisig_info, signer_infos)?;
+
+        if !is_valid_signer_index_v2(n as usize) {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        if !is_valid_signer_index_v2(m as usize) || m > n {
+            return Err(TokenError::InvalidNumberOfRequiredSigners.into());
+        }
+        let multisig = MultisigV2 {
+            m,
+            is_initialized: true,
+            signers: new_signer_infos
+                .iter()
+                .map(|signer_info| *signer_info.key)
+                .collect(),
+        };
+
+        let new_data_len = MultisigV2::get_packed_len(n as usize);
+        if new_data_len != data_len {
+            let required_lamports = rent
+                .minimum_balance(new_data_len)
+                .saturating_sub(multisig_info.lamports());
+            if required_lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        payer_info.key,
+                        multisig_info.key,
+                        required_lamports,
+                    ),
+                    &[
+                        payer_info.clone(),
+                        multisig_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+            multisig_info.realloc(new_data_len, false)?;
+
+            // A smaller account needs less rent, the rest goes back to the payer
+            let excess_lamports = multisig_info
+                .lamports()
+                .saturating_sub(rent.minimum_balance(new_data_len));
+            if excess_lamports > 0 {
+                **multisig_info.lamports.borrow_mut() -= excess_lamports;
+                let payer_lamports = payer_info.lamports();
+                **payer_info.lamports.borrow_mut() = payer_lamports
+                    .checked_add(excess_lamports)
+                    .ok_or(TokenError::Overflow)?;
+            }
+
+        multisig.pack(&mut multisig_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
     /// Processes a [Transfer](enum.TokenInstruction.html) instruction.
     pub fn process_transfer(
         program_id: &Pubkey,
@@ -347,22 +469,71 @@ impl Processor {
         let account_info = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
 
+        let authority = Self::get_authority(account_info, &authority_type)?;
+        Self::validate_owner(
+            program_id,
+            &authority,
+            authority_info,
+            account_info_iter.as_slice(),
+        )?;
+        Self::apply_authority(account_info, authority_type, new_authority)
+    }
+
+    /// Returns the current authority of the given type of a mint, account or mint meta.
+    fn get_authority(
+        account_info: &AccountInfo,
+        authority_type: &AuthorityType,
+    ) -> Result<Pubkey, ProgramError> {
         if account_info.data_len() == Account::get_packed_len() {
-            let mut account = Account::unpack(&account_info.data.borrow())?;
+            let account = Account::unpack(&account_info.data.borrow())?;
 
             if account.is_frozen() {
                 return Err(TokenError::AccountFrozen.into());
             }
 
             match authority_type {
-                AuthorityType::AccountOwner => {
-                    Self::validate_owner(
-                        program_id,
-                        &account.owner,
-                        authority_info,
-                        account_info_iter.as_slice(),
-                    )?;
+                AuthorityType::AccountOwner => Ok(account.owner),
+                AuthorityType::CloseAccount => Ok(account.close_authority.unwrap_or(account.owner)),
+                _ => Err(TokenError::AuthorityTypeNotSupported.into()),
+            }
+        } else if account_info.data_len() == Mint::get_packed_len() {
+            let mint = Mint::unpack(&account_info.data.borrow())?;
+            match authority_type {
+                // Once a mint's supply is fixed, it cannot be undone by setting a new
+                // mint_authority
+                AuthorityType::MintTokens => mint
+                    .mint_authority
+                    .ok_or_else(|| TokenError::FixedSupply.into()),
+                // Once a mint's freeze authority is disabled, it cannot be re-enabled by
+                // setting a new freeze_authority
+                AuthorityType::FreezeAccount => mint
+                    .freeze_authority
+                    .ok_or_else(|| TokenError::MintCannotFreeze.into()),
+                _ => Err(TokenError::AuthorityTypeNotSupported.into()),
+            }
+        } else if account_info.data_len() == MintMeta::get_packed_len() {
+            let mint_meta = MintMeta::unpack(&account_info.data.borrow())?;
+            match authority_type {
+                AuthorityType::UpdateMeta => mint_meta
+                    .authority
+                    .ok_or_else(|| TokenError::OwnerMismatch.into()),
+                _ => Err(TokenError::AuthorityTypeNotSupported.into()),
+            }
+        } else {
+            Err(ProgramError::InvalidArgument)
+        }
+    }
 
+    /// Stores a new authority of the given type, once the current authority has been validated.
+    fn apply_authority(
+        account_info: &AccountInfo,
+        authority_type: AuthorityType,
+        new_authority: COption<Pubkey>,
+    ) -> ProgramResult {
+        if account_info.data_len() == Account::get_packed_len() {
+            let mut account = Account::unpack(&account_info.data.borrow())?;
+            match authority_type {
+                AuthorityType::AccountOwner => {
                     if let COption::Some(authority) = new_authority {
                         account.owner = authority;
                     } else {
@@ -377,13 +548,6 @@ impl Processor {
                     }
                 }
                 AuthorityType::CloseAccount => {
-                    let authority = account.close_authority.unwrap_or(account.owner);
-                    Self::validate_owner(
-                        program_id,
-                        &authority,
-                        authority_info,
-                        account_info_iter.as_slice(),
-                    )?;
                     account.close_authority = new_authority;
                 }
                 _ => {
@@ -395,31 +559,9 @@ impl Processor {
             let mut mint = Mint::unpack(&account_info.data.borrow())?;
             match authority_type {
                 AuthorityType::MintTokens => {
-                    // Once a mint's supply is fixed, it cannot be undone by setting a new
-                    // mint_authority
-                    let mint_authority = mint
-                        .mint_authority
-                        .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
-                    Self::validate_owner(
-                        program_id,
-                        &mint_authority,
-                        authority_info,
-                        account_info_iter.as_slice(),
-                    )?;
                     mint.mint_authority = new_authority;
                 }
                 AuthorityType::FreezeAccount => {
-                    // Once a mint's freeze authority is disabled, it cannot be re-enabled by
-                    // setting a new freeze_authority
-                    let freeze_authority = mint
-                        .freeze_authority
-                        .ok_or(Into::<ProgramError>::into(TokenError::MintCannotFreeze))?;
-                    Self::validate_owner(
-                        program_id,
-                        &freeze_authority,
-                        authority_info,
-                        account_info_iter.as_slice(),
-                    )?;
                     mint.freeze_authority = new_authority;
                 }
                 _ => {
@@ -431,18 +573,6 @@ impl Processor {
             let mut mint_meta = MintMeta::unpack(&account_info.data.borrow())?;
             match authority_type {
                 AuthorityType::UpdateMeta => {
-                    // Once a mint's supply is fixed, it cannot be undone by setting a new
-                    // mint_authority
-                    let authority = mint_meta
-                        .authority
-                        .ok_or(Into::<ProgramError>::into(TokenError::OwnerMismatch))?;
-                    Self::validate_owner(
-                        program_id,
-                        &authority,
-                        authority_info,
-                        account_info_iter.as_slice(),
-                    )?;
-
                     // meta_data update authority
                     if mint_meta.is_initialized {
                         mint_meta.authority = new_authority;
@@ -460,6 +590,144 @@ impl Processor {
         Ok(())
     }
 
+    /// Processes a [ProposeAuthority](enum.TokenInstruction.html) instruction.
+    pub fn process_propose_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority_type: AuthorityType,
+        new_authority: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let pending_info = next_account_info(account_info_iter)?;
+        let account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let (pending_address, bump_seed) = get_pending_authority_address_and_bump_seed(
+            account_info.key,
+            &authority_type,
+            program_id,
+        );
+        if pending_address != *pending_info.key {
+            msg!(
+                "Error: Pending authority address does not match seed derivation {:?} - {:?}",
+                pending_address,
+                *pending_info.key
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let authority = Self::get_authority(account_info, &authority_type)?;
+        Self::validate_owner(
+            program_id,
+            &authority,
+            authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        // The rent of a replaced proposal stays owed to whoever paid it
+        let mut payer = *payer_info.key;
+        if pending_info.owner != program_id {
+            Self::create_pda_account(
+                payer_info,
+                pending_info,
+                system_program_info,
+                rent,
+                PendingAuthority::LEN,
+                program_id,
+                &[
+                    PENDING_AUTHORITY_SEED,
+                    &account_info.key.to_bytes(),
+                    &[AuthorityType::into(&authority_type)],
+                    &[bump_seed],
+                ],
+            )?;
+        } else {
+            let outstanding = PendingAuthority::unpack_unchecked(&pending_info.data.borrow())?;
+            if outstanding.is_initialized {
+                payer = outstanding.payer;
+            }
+        }
+
+        // A new proposal replaces any outstanding one
+        let pending = PendingAuthority {
+            is_initialized: true,
+            target: *account_info.key,
+            authority_type,
+            current_authority: authority,
+            new_authority,
+            payer,
+        };
+        PendingAuthority::pack(pending, &mut pending_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [AcceptAuthority](enum.TokenInstruction.html) instruction.
+    pub fn process_accept_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority_type: AuthorityType,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let pending_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let new_authority_info = next_account_info(account_info_iter)?;
+
+        let (pending_address, _) = get_pending_authority_address_and_bump_seed(
+            account_info.key,
+            &authority_type,
+            program_id,
+        );
+        if pending_address != *pending_info.key {
+            msg!(
+                "Error: Pending authority address does not match seed derivation {:?} - {:?}",
+                pending_address,
+                *pending_info.key
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if pending_info.owner != program_id {
+            return Err(TokenError::UninitializedState.into());
+        }
+        let pending = PendingAuthority::unpack(&pending_info.data.borrow())?;
+        if *dest_account_info.key != pending.payer {
+            return Err(TokenError::PendingAuthorityPayerMismatch.into());
+        }
+
+        // The proposal is stale if the authority changed since it was made
+        if Self::get_authority(account_info, &authority_type)? != pending.current_authority {
+            return Err(TokenError::InvalidState.into());
+        }
+        Self::validate_owner(
+            program_id,
+            &pending.new_authority,
+            new_authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        Self::apply_authority(
+            account_info,
+            authority_type,
+            COption::Some(pending.new_authority),
+        )?;
+
+        let dest_starting_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
+            .checked_add(pending_info.lamports())
+            .ok_or(TokenError::Overflow)?;
+        **pending_info.lamports.borrow_mut() = 0;
+        pending_info.data.borrow_mut().fill(0);
+
+        Ok(())
+    }
+
     /// Processes a [MintTo](enum.TokenInstruction.html) instruction.
     pub fn process_mint_to(
         program_id: &Pubkey,
@@ -905,6 +1173,233 @@ impl Processor {
         Ok(())
     }
 
+    /// Processes an [InitializeGroup](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_group(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        update_authority: COption<Pubkey>,
+        max_size: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let group_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let mint_authority_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let (group_address, bump_seed) = get_group_address_and_bump_seed(mint_info.key, program_id);
+        if group_address != *group_info.key {
+            msg!(
+                "Error: Group address does not match seed derivation {:?} - {:?}",
+                group_address,
+                *group_info.key
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if mint_info.owner != program_id {
+            return Err(TokenError::InvalidMint.into());
+        }
+        let mint = Mint::unpack(&mint_info.data.borrow())
+            .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner(
+            program_id,
+            &mint_authority,
+            mint_authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        if group_info.owner != program_id {
+            Self::create_pda_account(
+                payer_info,
+                group_info,
+                system_program_info,
+                rent,
+                TokenGroup::LEN,
+                program_id,
+                &[TOKEN_GROUP_SEED, &mint_info.key.to_bytes(), &[bump_seed]],
+            )?;
+        }
+
+        let mut group = TokenGroup::unpack_unchecked(&group_info.data.borrow())?;
+        if group.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        group.is_initialized = true;
+        group.update_authority = update_authority;
+        group.mint = *mint_info.key;
+        group.size = 0;
+        group.max_size = max_size;
+        TokenGroup::pack(group, &mut group_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [InitializeMember](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_member(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let member_info = next_account_info(account_info_iter)?;
+        let member_mint_info = next_account_info(account_info_iter)?;
+        let member_mint_authority_info = next_account_info(account_info_iter)?;
+        let group_info = next_account_info(account_info_iter)?;
+        let group_update_authority_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let (member_address, bump_seed) =
+            get_group_member_address_and_bump_seed(member_mint_info.key, program_id);
+        if member_address != *member_info.key {
+            msg!(
+                "Error: Member address does not match seed derivation {:?} - {:?}",
+                member_address,
+                *member_info.key
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if member_mint_info.owner != program_id {
+            return Err(TokenError::InvalidMint.into());
+        }
+        let member_mint = Mint::unpack(&member_mint_info.data.borrow())
+            .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+        let member_mint_authority = member_mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner(
+            program_id,
+            &member_mint_authority,
+            member_mint_authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        if group_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut group = TokenGroup::unpack(&group_info.data.borrow())?;
+        let update_authority = group
+            .update_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::OwnerMismatch))?;
+        Self::validate_owner(
+            program_id,
+            &update_authority,
+            group_update_authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        let member_number = group.size.checked_add(1).ok_or(TokenError::Overflow)?;
+        if member_number > group.max_size {
+            return Err(TokenError::GroupMaxSizeExceeded.into());
+        }
+
+        if member_info.owner != program_id {
+            Self::create_pda_account(
+                payer_info,
+                member_info,
+                system_program_info,
+                rent,
+                TokenGroupMember::LEN,
+                program_id,
+                &[
+                    TOKEN_GROUP_MEMBER_SEED,
+                    &member_mint_info.key.to_bytes(),
+                    &[bump_seed],
+                ],
+            )?;
+        }
+
+        let mut member = TokenGroupMember::unpack_unchecked(&member_info.data.borrow())?;
+        if member.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        member.is_initialized = true;
+        member.mint = *member_mint_info.key;
+        member.group = *group_info.key;
+        member.member_number = member_number;
+        TokenGroupMember::pack(member, &mut member_info.data.borrow_mut())?;
+
+        group.size = member_number;
+        TokenGroup::pack(group, &mut group_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [UpdateGroupMaxSize](enum.TokenInstruction.html) instruction.
+    pub fn process_update_group_max_size(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_size: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let group_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        if group_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut group = TokenGroup::unpack(&group_info.data.borrow())?;
+        let update_authority = group
+            .update_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::OwnerMismatch))?;
+        Self::validate_owner(
+            program_id,
+            &update_authority,
+            authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        if max_size < group.size {
+            return Err(TokenError::InvalidGroupMaxSize.into());
+        }
+
+        group.max_size = max_size;
+        TokenGroup::pack(group, &mut group_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Funds, allocates and assigns a program derived account to the token program
+    fn create_pda_account<'a>(
+        payer_info: &AccountInfo<'a>,
+        new_account_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent: &Rent,
+        space: usize,
+        owner: &Pubkey,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let required_lamports = rent
+            .minimum_balance(space)
+            .saturating_sub(new_account_info.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, new_account_info.key, required_lamports),
+                &[
+                    payer_info.clone(),
+                    new_account_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(new_account_info.key, space as u64),
+            &[new_account_info.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(new_account_info.key, owner),
+            &[new_account_info.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        Ok(())
+    }
+
     /// Processes an [Instruction](enum.Instruction.html).
     pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
         let instruction = TokenInstruction::unpack(input)?;
@@ -1009,7 +1504,41 @@ impl Processor {
                 msg!("Instruction: InitializeMintMeta");
                 Self::process_init_mint_meta_account(accounts, symbol, name, icon)
             }
-        }
+            TokenInstruction::ProposeAuthority {
+                authority_type,
+                new_authority,
+            } => {
+                msg!("Instruction: ProposeAuthority");
+                Self::process_propose_authority(program_id, accounts, authority_type, new_authority)
+            }
+            TokenInstruction::AcceptAuthority { authority_type } => {
+                msg!("Instruction: AcceptAuthority");
+                Self::process_accept_authority(program_id, accounts, authority_type)
+            }
+            TokenInstruction::InitializeMultisigV2 { m } => {
+                msg!("Instruction: InitializeMultisigV2");
+                Self::process_initialize_multisig_v2(accounts, m)
+            }
+            TokenInstruction::UpdateMultisig { m, n } => {
+                msg!("Instruction: UpdateMultisig");
+                Self::process_update_multisig(program_id, accounts, m, n)
+            }
+            TokenInstruction::InitializeGroup {
+                update_authority,
+                max_size,
+            } => {
+                msg!("Instruction: InitializeGroup");
+                Self::process_initialize_group(program_id, accounts, update_authority, max_size)
+            }
+            TokenInstruction::InitializeMember => {
+                msg!("Instruction: InitializeMember");
+                Self::process_initialize_member(program_id, accounts)
+            }
+            TokenInstruction::UpdateGroupMaxSize { max_size } => {
+                msg!("Instruction: UpdateGroupMaxSize");
+                Self::process_update_group_max_size(program_id, accounts, max_size)
+            }
+        }
     }
 
     /// Validates owner(s) are present
@@ -1045,6 +1574,27 @@ impl Processor {
                 return Err(ProgramError::MissingRequiredSignature);
             }
             return Ok(());
+        } else if program_id == owner_account_info.owner
+            && MultisigV2::capacity(owner_account_info.data_len()).is_some()
+        {
+            let multisig = MultisigV2::unpack(&owner_account_info.data.borrow())?;
+            let mut num_signers = 0;
+            let mut matched = [false; MAX_SIGNERS_V2];
+            for signer in signers.iter() {
+                for (position, key) in multisig.signers.iter().enumerate() {
+                    if key == signer.key && !matched[position] {
+                        if !signer.is_signer {
+                            return Err(ProgramError::MissingRequiredSignature);
+                        }
+                        matched[position] = true;
+                        num_signers += 1;
+                    }
+                }
+            }
+            if num_signers < multisig.m {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            return Ok(());
         } else if !owner_account_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
@@ -1092,6 +1642,15 @@ impl PrintProgramError for TokenError {
             TokenError::NonNativeNotSupported => {
                 msg!("Error: Instruction does not support non-native tokens")
             }
+            TokenError::GroupMaxSizeExceeded => {
+                msg!("Error: Group has reached its maximum size")
+            }
+            TokenError::InvalidGroupMaxSize => {
+                msg!("Error: Group max size lower than its current size")
+            }
+            TokenError::PendingAuthorityPayerMismatch => {
+                msg!("Error: Destination is not the payer of the pending authority")
+            }
             // TokenError::OwnerNotSign => {
             //     msg!("Error: Instruction does not support non-native tokens")
             // }
@@ -1159,6 +1718,18 @@ mod tests {
         Rent::default().minimum_balance(Multisig::get_packed_len())
     }
 
+    fn group_minimum_balance() -> u128 {
+        Rent::default().minimum_balance(TokenGroup::get_packed_len())
+    }
+
+    fn group_member_minimum_balance() -> u128 {
+        Rent::default().minimum_balance(TokenGroupMember::get_packed_len())
+    }
+
+    fn pending_authority_minimum_balance() -> u128 {
+        Rent::default().minimum_balance(PendingAuthority::get_packed_len())
+    }
+
     #[test]
     fn test_print_error() {
         let error = return_token_error_as_program_error();
@@ -1179,6 +1750,27 @@ mod tests {
         assert_ne!(Account::get_packed_len(), 0);
         assert_ne!(Account::get_packed_len(), Multisig::get_packed_len());
         assert_ne!(Multisig::get_packed_len(), 0);
+        let lens = [
+            Mint::get_packed_len(),
+            MintMeta::get_packed_len(),
+            Account::get_packed_len(),
+            Multisig::get_packed_len(),
+            TokenGroup::get_packed_len(),
+            TokenGroupMember::get_packed_len(),
+            PendingAuthority::get_packed_len(),
+        ];
+        for (i, len) in lens.iter().enumerate() {
+            assert!(!lens[i + 1..].contains(len));
+            assert_eq!(MultisigV2::capacity(*len), None);
+        }
+        for n in MIN_SIGNERS..=MAX_SIGNERS_V2 {
+            assert_eq!(MultisigV2::capacity(MultisigV2::get_packed_len(n)), Some(n));
+        }
+        assert_eq!(MultisigV2::capacity(MultisigV2::get_packed_len(0)), None);
+        assert_eq!(
+            MultisigV2::capacity(MultisigV2::get_packed_len(MAX_SIGNERS_V2 + 1)),
+            None
+        );
     }
 
     #[test]
@@ -6340,5 +6932,760 @@ mod tests {
             )
         );
     }
-}
 
+    #[test]
+    fn test_token_group() {
+        let program_id = crate::id();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = HuiOneAccount::default();
+        let mut system_account = HuiOneAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = HuiOneAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = HuiOneAccount::default();
+        let group_mint_key = Pubkey::new_unique();
+        let mut group_mint_account =
+            HuiOneAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let member_mint_key = Pubkey::new_unique();
+        let mut member_mint_account =
+            HuiOneAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let member2_mint_key = Pubkey::new_unique();
+        let mut member2_mint_account =
+            HuiOneAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut group_account = HuiOneAccount::new(
+            group_minimum_balance(),
+            TokenGroup::get_packed_len(),
+            &program_id,
+        );
+        let mut member_account = HuiOneAccount::new(
+            group_member_minimum_balance(),
+            TokenGroupMember::get_packed_len(),
+            &program_id,
+        );
+        let mut member2_account = HuiOneAccount::new(
+            group_member_minimum_balance(),
+            TokenGroupMember::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mints
+        for mint_account in [
+            &mut group_mint_account,
+            &mut member_mint_account,
+            &mut member2_mint_account,
+        ] {
+            do_process_instruction(
+                initialize_mint(&program_id, &Pubkey::new_unique(), &owner_key, None, 2).unwrap(),
+                vec![mint_account, &mut rent_sysvar],
+            )
+            .unwrap();
+        }
+
+        // wrong mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                initialize_group(
+                    &program_id,
+                    &payer_key,
+                    &group_mint_key,
+                    &owner2_key,
+                    &[],
+                    Some(&owner_key),
+                    1
+                )
+                .unwrap(),
+                vec![
+                    &mut payer_account,
+                    &mut group_account,
+                    &mut group_mint_account,
+                    &mut owner2_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // mint authority did not sign
+        let mut instruction = initialize_group(
+            &program_id,
+            &payer_key,
+            &group_mint_key,
+            &owner_key,
+            &[],
+            Some(&owner_key),
+            1,
+        )
+        .unwrap();
+        instruction.accounts[3].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut payer_account,
+                    &mut group_account,
+                    &mut group_mint_account,
+                    &mut owner_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // create group
+        do_process_instruction(
+            initialize_group(
+                &program_id,
+                &payer_key,
+                &group_mint_key,
+                &owner_key,
+                &[],
+                Some(&owner_key),
+                1,
+            )
+            .unwrap(),
+            vec![
+                &mut payer_account,
+                &mut group_account,
+                &mut group_mint_account,
+                &mut owner_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let group = TokenGroup::unpack(&group_account.data).unwrap();
+        assert_eq!(group.update_authority, COption::Some(owner_key));
+        assert_eq!(group.mint, group_mint_key);
+        assert_eq!(group.size, 0);
+        assert_eq!(group.max_size, 1);
+
+        // create twice
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_group(
+                    &program_id,
+                    &payer_key,
+                    &group_mint_key,
+                    &owner_key,
+                    &[],
+                    None,
+                    1,
+                )
+                .unwrap(),
+                vec![
+                    &mut payer_account,
+                    &mut group_account,
+                    &mut group_mint_account,
+                    &mut owner_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // add member
+        do_process_instruction(
+            initialize_member(
+                &program_id,
+                &payer_key,
+                &member_mint_key,
+                &owner_key,
+                &group_mint_key,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut payer_account,
+                &mut member_account,
+                &mut member_mint_account,
+                &mut owner_account.clone(),
+                &mut group_account,
+                &mut owner_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let member = TokenGroupMember::unpack(&member_account.data).unwrap();
+        assert_eq!(member.mint, member_mint_key);
+        assert_eq!(member.group, crate::get_group_address(&group_mint_key));
+        assert_eq!(member.member_number, 1);
+        let group = TokenGroup::unpack(&group_account.data).unwrap();
+        assert_eq!(group.size, 1);
+
+        // group is full
+        assert_eq!(
+            Err(TokenError::GroupMaxSizeExceeded.into()),
+            do_process_instruction(
+                initialize_member(
+                    &program_id,
+                    &payer_key,
+                    &member2_mint_key,
+                    &owner_key,
+                    &group_mint_key,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![
+                    &mut payer_account,
+                    &mut member2_account,
+                    &mut member2_mint_account,
+                    &mut owner_account.clone(),
+                    &mut group_account,
+                    &mut owner_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // max size cannot drop below the current size
+        assert_eq!(
+            Err(TokenError::InvalidGroupMaxSize.into()),
+            do_process_instruction(
+                update_group_max_size(&program_id, &group_mint_key, &owner_key, &[], 0).unwrap(),
+                vec![&mut group_account, &mut owner_account],
+            )
+        );
+
+        // wrong update authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                update_group_max_size(&program_id, &group_mint_key, &owner2_key, &[], 2).unwrap(),
+                vec![&mut group_account, &mut owner2_account],
+            )
+        );
+
+        // grow group
+        do_process_instruction(
+            update_group_max_size(&program_id, &group_mint_key, &owner_key, &[], 2).unwrap(),
+            vec![&mut group_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // add second member
+        do_process_instruction(
+            initialize_member(
+                &program_id,
+                &payer_key,
+                &member2_mint_key,
+                &owner_key,
+                &group_mint_key,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut payer_account,
+                &mut member2_account,
+                &mut member2_mint_account,
+                &mut owner_account.clone(),
+                &mut group_account,
+                &mut owner_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let member = TokenGroupMember::unpack(&member2_account.data).unwrap();
+        assert_eq!(member.member_number, 2);
+
+        do_process_instruction(
+            update_group_max_size(&program_id, &group_mint_key, &owner_key, &[], 3).unwrap(),
+            vec![&mut group_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // a mint can only join once
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_member(
+                    &program_id,
+                    &payer_key,
+                    &member2_mint_key,
+                    &owner_key,
+                    &group_mint_key,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![
+                    &mut payer_account,
+                    &mut member2_account,
+                    &mut member2_mint_account,
+                    &mut owner_account.clone(),
+                    &mut group_account,
+                    &mut owner_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_two_step_authority() {
+        let program_id = crate::id();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = HuiOneAccount::default();
+        let mut system_account = HuiOneAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = HuiOneAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = HuiOneAccount::default();
+        let owner3_key = Pubkey::new_unique();
+        let mut owner3_account = HuiOneAccount::default();
+        let payer2_key = Pubkey::new_unique();
+        let mut payer2_account = HuiOneAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            HuiOneAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut pending_account = HuiOneAccount::new(
+            pending_authority_minimum_balance(),
+            PendingAuthority::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&owner_key), 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // nothing to accept
+        let mut unfunded_pending_account = HuiOneAccount::default();
+        assert_eq!(
+            Err(TokenError::UninitializedState.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &mint_key,
+                    &payer_key,
+                    AuthorityType::MintTokens,
+                    &owner2_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut unfunded_pending_account,
+                    &mut payer_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // wrong current authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                propose_authority(
+                    &program_id,
+                    &payer_key,
+                    &mint_key,
+                    &owner2_key,
+                    AuthorityType::MintTokens,
+                    &owner3_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![
+                    &mut payer_account,
+                    &mut pending_account,
+                    &mut mint_account,
+                    &mut owner3_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // invalid authority type for a mint
+        assert_eq!(
+            Err(TokenError::AuthorityTypeNotSupported.into()),
+            do_process_instruction(
+                propose_authority(
+                    &program_id,
+                    &payer_key,
+                    &mint_key,
+                    &owner2_key,
+                    AuthorityType::AccountOwner,
+                    &owner_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![
+                    &mut payer_account,
+                    &mut pending_account,
+                    &mut mint_account,
+                    &mut owner_account,
+                    &mut system_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // propose new mint authority
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &payer_key,
+                &mint_key,
+                &owner2_key,
+                AuthorityType::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut payer_account,
+                &mut pending_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.mint_authority, COption::Some(owner_key));
+        let pending = PendingAuthority::unpack(&pending_account.data).unwrap();
+        assert_eq!(pending.target, mint_key);
+        assert_eq!(pending.authority_type, AuthorityType::MintTokens);
+        assert_eq!(pending.current_authority, owner_key);
+        assert_eq!(pending.new_authority, owner2_key);
+        assert_eq!(pending.payer, payer_key);
+
+        // a replacing proposal keeps the original payer
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &payer2_key,
+                &mint_key,
+                &owner2_key,
+                AuthorityType::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut payer2_account,
+                &mut pending_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let pending = PendingAuthority::unpack(&pending_account.data).unwrap();
+        assert_eq!(pending.payer, payer_key);
+
+        // the rent goes back to the payer only
+        assert_eq!(
+            Err(TokenError::PendingAuthorityPayerMismatch.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &mint_key,
+                    &payer2_key,
+                    AuthorityType::MintTokens,
+                    &owner2_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut pending_account,
+                    &mut payer2_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // only the proposed authority can accept
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &mint_key,
+                    &payer_key,
+                    AuthorityType::MintTokens,
+                    &owner3_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut pending_account,
+                    &mut payer_account,
+                    &mut owner3_account,
+                ],
+            )
+        );
+
+        // the proposed authority must sign
+        let mut instruction = accept_authority(
+            &program_id,
+            &mint_key,
+            &payer_key,
+            AuthorityType::MintTokens,
+            &owner2_key,
+            &[],
+        )
+        .unwrap();
+        instruction.accounts[3].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut mint_account,
+                    &mut pending_account,
+                    &mut payer_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // accept
+        do_process_instruction(
+            accept_authority(
+                &program_id,
+                &mint_key,
+                &payer_key,
+                AuthorityType::MintTokens,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut pending_account,
+                &mut payer_account,
+                &mut owner2_account,
+            ],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.mint_authority, COption::Some(owner2_key));
+        assert_eq!(pending_account.lamports, 0);
+        assert_eq!(payer_account.lamports, pending_authority_minimum_balance());
+        assert!(!PendingAuthority::unpack_unchecked(&pending_account.data)
+            .unwrap()
+            .is_initialized);
+
+        // propose new freeze authority
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &payer_key,
+                &mint_key,
+                &owner3_key,
+                AuthorityType::FreezeAccount,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut payer_account,
+                &mut pending_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // freeze authority changes before the proposal is accepted
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                Some(&owner2_key),
+                AuthorityType::FreezeAccount,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // stale proposal
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &mint_key,
+                    &payer_key,
+                    AuthorityType::FreezeAccount,
+                    &owner3_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut pending_account,
+                    &mut payer_account,
+                    &mut owner3_account,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_multisig_v2() {
+        fn initialize(
+            multisig_key: &Pubkey,
+            multisig_account: &mut HuiOneAccount,
+            signer_keys: &[Pubkey],
+            m: u8,
+        ) -> ProgramResult {
+            let signer_key_refs: Vec<&Pubkey> = signer_keys.iter().collect();
+            let mut rent_sysvar = rent_sysvar();
+            let mut signer_accounts = vec![HuiOneAccount::default(); signer_keys.len()];
+            let mut accounts = vec![multisig_account, &mut rent_sysvar];
+            accounts.extend(signer_accounts.iter_mut());
+            do_process_instruction(
+                initialize_multisig_v2(&crate::id(), multisig_key, &signer_key_refs, m).unwrap(),
+                accounts,
+            )
+        }
+
+        fn update(
+            multisig_key: &Pubkey,
+            multisig_account: &mut HuiOneAccount,
+            new_signer_keys: &[Pubkey],
+            m: u8,
+            signer_keys: &[Pubkey],
+        ) -> ProgramResult {
+            let new_signer_key_refs: Vec<&Pubkey> = new_signer_keys.iter().collect();
+            let signer_key_refs: Vec<&Pubkey> = signer_keys.iter().collect();
+            let mut payer_account = HuiOneAccount::default();
+            let mut system_account = HuiOneAccount::default();
+            let mut rent_sysvar = rent_sysvar();
+            let mut new_signer_accounts = vec![HuiOneAccount::default(); new_signer_keys.len()];
+            let mut signer_accounts = vec![HuiOneAccount::default(); signer_keys.len()];
+            let mut accounts = vec![
+                multisig_account,
+                &mut payer_account,
+                &mut system_account,
+                &mut rent_sysvar,
+            ];
+            accounts.extend(new_signer_accounts.iter_mut());
+            accounts.extend(signer_accounts.iter_mut());
+            do_process_instruction(
+                update_multisig(
+                    &crate::id(),
+                    multisig_key,
+                    &Pubkey::new_unique(),
+                    &new_signer_key_refs,
+                    m,
+                    &signer_key_refs,
+                )
+                .unwrap(),
+                accounts,
+            )
+        }
+
+        let program_id = crate::id();
+        let multisig_key = Pubkey::new_unique();
+        let signer_keys: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+        let new_signer_keys: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+        let mut multisig_account = HuiOneAccount::new(
+            Rent::default().minimum_balance(MultisigV2::get_packed_len(20)),
+            MultisigV2::get_packed_len(20),
+            &program_id,
+        );
+
+        // account not sized for the signers
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+            initialize(&multisig_key, &mut multisig_account, &signer_keys[..19], 1)
+        );
+
+        // success
+        initialize(&multisig_key, &mut multisig_account, &signer_keys, 3).unwrap();
+        let multisig = MultisigV2::unpack(&multisig_account.data).unwrap();
+        assert_eq!(multisig.m, 3);
+        assert_eq!(multisig.signers, signer_keys);
+
+        // already initialized
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            initialize(&multisig_key, &mut multisig_account, &signer_keys, 3)
+        );
+
+        // update needs m of the current signers
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            update(
+                &multisig_key,
+                &mut multisig_account,
+                &new_signer_keys,
+                2,
+                &signer_keys[..2]
+            )
+        );
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            update(
+                &multisig_key,
+                &mut multisig_account,
+                &new_signer_keys,
+                2,
+                &new_signer_keys[..3]
+            )
+        );
+
+        // rotate the signers
+        update(
+            &multisig_key,
+            &mut multisig_account,
+            &new_signer_keys,
+            2,
+            &signer_keys[5..8],
+        )
+        .unwrap();
+        let multisig = MultisigV2::unpack(&multisig_account.data).unwrap();
+        assert_eq!(multisig.m, 2);
+        assert_eq!(multisig.signers, new_signer_keys);
+
+        // previous signers are no longer valid
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            update(
+                &multisig_key,
+                &mut multisig_account,
+                &signer_keys,
+                3,
+                &signer_keys[..3]
+            )
+        );
+        update(
+            &multisig_key,
+            &mut multisig_account,
+            &signer_keys,
+            3,
+            &new_signer_keys[18..],
+        )
+        .unwrap();
+        let multisig = MultisigV2::unpack(&multisig_account.data).unwrap();
+        assert_eq!(multisig.m, 3);
+        assert_eq!(multisig.signers, signer_keys);
+    }
+}
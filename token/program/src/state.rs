@@ -1,6 +1,6 @@
 //! State transition types
 use crate::{
-    instruction::MAX_SIGNERS,
+    instruction::{is_valid_signer_index_v2, AuthorityType, MAX_SIGNERS},
 };
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use num_enum::TryFromPrimitive;
@@ -325,6 +325,278 @@ impl Pack for Multisig {
     }
 }
 
+/// Resizable multisignature data, holding up to `MAX_SIGNERS_V2` signers.
+///
+/// The account is sized to fit exactly its `n` signers, so its length is
+/// `MultisigV2::get_packed_len(n)` and changes when the signer set is updated.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultisigV2 {
+    /// Number of signers required
+    pub m: u8,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Signer public keys
+    pub signers: Vec<Pubkey>,
+}
+impl IsInitialized for MultisigV2 {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl MultisigV2 {
+    /// Length of the header preceding the signers. Every packed length is `4 mod 32`, which
+    /// keeps it distinct from the lengths of the other account types.
+    pub const HEADER_LEN: usize = 4;
+    /// Version byte stored in the header
+    pub const VERSION: u8 = 2;
+
+    /// Packed length of a multisig with `n` signers
+    pub fn get_packed_len(n: usize) -> usize {
+        Self::HEADER_LEN + 32 * n
+    }
+
+    /// Number of signers fitting in an account of `len` bytes, if `len` is a valid
+    /// multisig v2 length
+    pub fn capacity(len: usize) -> Option<usize> {
+        if len < Self::HEADER_LEN || (len - Self::HEADER_LEN) % 32 != 0 {
+            return None;
+        }
+        let n = (len - Self::HEADER_LEN) / 32;
+        if is_valid_signer_index_v2(n) {
+            Some(n)
+        } else {
+            None
+        }
+    }
+
+    /// Unpack an initialized multisig
+    pub fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        let multisig = Self::unpack_unchecked(src)?;
+        if !multisig.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(multisig)
+    }
+
+    /// Unpack a multisig without checking that it is initialized
+    pub fn unpack_unchecked(src: &[u8]) -> Result<Self, ProgramError> {
+        let capacity = Self::capacity(src.len()).ok_or(ProgramError::InvalidAccountData)?;
+        let (header, signers_flat) = src.split_at(Self::HEADER_LEN);
+        let is_initialized = match header[2] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let n = header[1] as usize;
+        if is_initialized && (header[3] != Self::VERSION || n != capacity) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(MultisigV2 {
+            m: header[0],
+            is_initialized,
+            signers: signers_flat.chunks(32).take(n).map(Pubkey::new).collect(),
+        })
+    }
+
+    /// Pack into an account sized for exactly `self.signers.len()` signers
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::get_packed_len(self.signers.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, signers_flat) = dst.split_at_mut(Self::HEADER_LEN);
+        header.copy_from_slice(&[
+            self.m,
+            self.signers.len() as u8,
+            self.is_initialized as u8,
+            Self::VERSION,
+        ]);
+        for (src, dst) in self.signers.iter().zip(signers_flat.chunks_mut(32)) {
+            dst.copy_from_slice(src.as_ref());
+        }
+        Ok(())
+    }
+}
+
+/// Token group data, linking a family of mints under a single group mint.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TokenGroup {
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Optional authority to add members and update the group. If no update authority is
+    /// present then the group is frozen and no further members may be added.
+    pub update_authority: COption<Pubkey>,
+    /// The mint this group is associated with
+    pub mint: Pubkey,
+    /// The current number of group members
+    pub size: u32,
+    /// The maximum number of group members
+    pub max_size: u32,
+}
+impl Sealed for TokenGroup {}
+impl IsInitialized for TokenGroup {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for TokenGroup {
+    const LEN: usize = 77;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 77];
+        let (is_initialized, update_authority, mint, size, max_size) =
+            array_refs![src, 1, 36, 32, 4, 4];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(TokenGroup {
+            is_initialized,
+            update_authority: unpack_coption_key(update_authority)?,
+            mint: Pubkey::new_from_array(*mint),
+            size: u32::from_le_bytes(*size),
+            max_size: u32::from_le_bytes(*max_size),
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 77];
+        let (is_initialized_dst, update_authority_dst, mint_dst, size_dst, max_size_dst) =
+            mut_array_refs![dst, 1, 36, 32, 4, 4];
+        let &TokenGroup {
+            is_initialized,
+            ref update_authority,
+            ref mint,
+            size,
+            max_size,
+        } = self;
+        is_initialized_dst[0] = is_initialized as u8;
+        pack_coption_key(update_authority, update_authority_dst);
+        mint_dst.copy_from_slice(mint.as_ref());
+        *size_dst = size.to_le_bytes();
+        *max_size_dst = max_size.to_le_bytes();
+    }
+}
+
+/// Token group member data, linking a mint to the group it belongs to.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TokenGroupMember {
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// The member mint
+    pub mint: Pubkey,
+    /// The group account this mint is a member of
+    pub group: Pubkey,
+    /// The member number, starting at 1
+    pub member_number: u32,
+}
+impl TokenGroupMember {
+    /// Offset of the `group` field, used to filter the members of a group
+    pub const GROUP_OFFSET: usize = 33;
+}
+impl Sealed for TokenGroupMember {}
+impl IsInitialized for TokenGroupMember {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for TokenGroupMember {
+    const LEN: usize = 69;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 69];
+        let (is_initialized, mint, group, member_number) = array_refs![src, 1, 32, 32, 4];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(TokenGroupMember {
+            is_initialized,
+            mint: Pubkey::new_from_array(*mint),
+            group: Pubkey::new_from_array(*group),
+            member_number: u32::from_le_bytes(*member_number),
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 69];
+        let (is_initialized_dst, mint_dst, group_dst, member_number_dst) =
+            mut_array_refs![dst, 1, 32, 32, 4];
+        let &TokenGroupMember {
+            is_initialized,
+            ref mint,
+            ref group,
+            member_number,
+        } = self;
+        is_initialized_dst[0] = is_initialized as u8;
+        mint_dst.copy_from_slice(mint.as_ref());
+        group_dst.copy_from_slice(group.as_ref());
+        *member_number_dst = member_number.to_le_bytes();
+    }
+}
+
+/// Pending authority of a two-step authority transfer, waiting for the new authority to accept.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingAuthority {
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// The mint, account or mint meta whose authority is being transferred
+    pub target: Pubkey,
+    /// The type of authority being transferred
+    pub authority_type: AuthorityType,
+    /// The authority that made the proposal. The proposal lapses if it changes.
+    pub current_authority: Pubkey,
+    /// The proposed authority, which must sign to accept
+    pub new_authority: Pubkey,
+    /// The account that paid for the proposal, refunded when it is accepted
+    pub payer: Pubkey,
+}
+impl Sealed for PendingAuthority {}
+impl IsInitialized for PendingAuthority {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for PendingAuthority {
+    const LEN: usize = 130;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 130];
+        let (is_initialized, target, authority_type, current_authority, new_authority, payer) =
+            array_refs![src, 1, 32, 1, 32, 32, 32];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(PendingAuthority {
+            is_initialized,
+            target: Pubkey::new_from_array(*target),
+            authority_type: AuthorityType::from(authority_type[0])
+                .or(Err(ProgramError::InvalidAccountData))?,
+            current_authority: Pubkey::new_from_array(*current_authority),
+            new_authority: Pubkey::new_from_array(*new_authority),
+            payer: Pubkey::new_from_array(*payer),
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 130];
+        let (
+            is_initialized_dst,
+            target_dst,
+            authority_type_dst,
+            current_authority_dst,
+            new_authority_dst,
+            payer_dst,
+        ) = mut_array_refs![dst, 1, 32, 1, 32, 32, 32];
+        is_initialized_dst[0] = self.is_initialized as u8;
+        target_dst.copy_from_slice(self.target.as_ref());
+        authority_type_dst[0] = AuthorityType::into(&self.authority_type);
+        current_authority_dst.copy_from_slice(self.current_authority.as_ref());
+        new_authority_dst.copy_from_slice(self.new_authority.as_ref());
+        payer_dst.copy_from_slice(self.payer.as_ref());
+    }
+}
+
 // Helpers
 fn pack_coption_key(src: &COption<Pubkey>, dst: &mut [u8; 36]) {
     let (tag, body) = mut_array_refs![dst, 4, 32];
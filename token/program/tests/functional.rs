@@ -0,0 +1,680 @@
+// Mark this test as BPF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-bpf")]
+
+use hpl_token::{
+    error::TokenError,
+    get_group_address, get_group_member_address, id,
+    instruction::{
+        initialize_group, initialize_member, initialize_mint, initialize_multisig,
+        initialize_multisig_v2, update_multisig,
+    },
+    processor::Processor,
+    state::{Mint, Multisig, MultisigV2, TokenGroup, TokenGroupMember},
+};
+use huione_program::{
+    hash::Hash, instruction::InstructionError, program_option::COption, program_pack::Pack,
+    pubkey::Pubkey, system_instruction, system_program,
+};
+use huione_program_test::*;
+use huione_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+
+fn program_test() -> ProgramTest {
+    ProgramTest::new("hpl_token", id(), processor!(Processor::process))
+}
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    instructions: &[huione_program::instruction::Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client.process_transaction(transaction).await
+}
+
+fn keypairs(n: usize) -> Vec<Keypair> {
+    (0..n).map(|_| Keypair::new()).collect()
+}
+
+fn pubkeys(keypairs: &[Keypair]) -> Vec<Pubkey> {
+    keypairs.iter().map(|keypair| keypair.pubkey()).collect()
+}
+
+/// Creates a multisig requiring `m` of `signers`, in the v1 layout when `v1`
+async fn create_multisig(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    signers: &[Keypair],
+    m: u8,
+    v1: bool,
+) -> Pubkey {
+    let multisig = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    let signer_pubkeys = pubkeys(signers);
+    let signer_pubkeys = signer_pubkeys.iter().collect::<Vec<_>>();
+    let (len, initialize) = if v1 {
+        (
+            Multisig::LEN,
+            initialize_multisig(&id(), &multisig.pubkey(), &signer_pubkeys, m),
+        )
+    } else {
+        (
+            MultisigV2::get_packed_len(signers.len()),
+            initialize_multisig_v2(&id(), &multisig.pubkey(), &signer_pubkeys, m),
+        )
+    };
+    process(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &multisig.pubkey(),
+                rent.minimum_balance(len),
+                len as u64,
+                &id(),
+            ),
+            initialize.unwrap(),
+        ],
+        &[&multisig],
+    )
+    .await
+    .unwrap();
+    multisig.pubkey()
+}
+
+/// Replaces the signers of `multisig` with `new_signers`, `rent_payer` paying
+/// or receiving the rent difference
+#[allow(clippy::too_many_arguments)]
+async fn update(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    multisig: &Pubkey,
+    rent_payer: &Keypair,
+    new_signers: &[Keypair],
+    m: u8,
+    current_signers: &[&Keypair],
+) -> Result<(), TransportError> {
+    let new_signer_pubkeys = pubkeys(new_signers);
+    let new_signer_pubkeys = new_signer_pubkeys.iter().collect::<Vec<_>>();
+    let current_signer_pubkeys = current_signers
+        .iter()
+        .map(|signer| signer.pubkey())
+        .collect::<Vec<_>>();
+    let current_signer_pubkeys = current_signer_pubkeys.iter().collect::<Vec<_>>();
+    let mut signers = vec![rent_payer];
+    signers.extend_from_slice(current_signers);
+    process(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &[update_multisig(
+            &id(),
+            multisig,
+            &rent_payer.pubkey(),
+            &new_signer_pubkeys,
+            m,
+            &current_signer_pubkeys,
+        )
+        .unwrap()],
+        &signers,
+    )
+    .await
+}
+
+/// Funds a rent payer, returning it with its balance
+async fn rent_payer(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+) -> (Keypair, u128) {
+    let rent_payer = Keypair::new();
+    let lamports = 1_000_000_000;
+    process(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &rent_payer.pubkey(),
+            lamports,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    (rent_payer, lamports)
+}
+
+/// Checks that `multisig` is a rent exempt v2 account of exactly `signers`,
+/// returning its lamports
+async fn check_multisig(
+    banks_client: &mut BanksClient,
+    multisig: &Pubkey,
+    signers: &[Keypair],
+    m: u8,
+) -> u128 {
+    let rent = banks_client.get_rent().await.unwrap();
+    let account = banks_client.get_account(*multisig).await.unwrap().unwrap();
+    assert_eq!(
+        account.data.len(),
+        MultisigV2::get_packed_len(signers.len())
+    );
+    assert_eq!(account.lamports, rent.minimum_balance(account.data.len()));
+    let state = MultisigV2::unpack(&account.data).unwrap();
+    assert_eq!(state.m, m);
+    assert_eq!(state.signers, pubkeys(signers));
+    account.lamports
+}
+
+fn missing_signature(result: Result<(), TransportError>) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+// A transaction locks at most 64 accounts, including the fixed accounts and the
+// current signers of an update, so the large signer set stays below 64
+const LARGE_SIGNER_COUNT: usize = 48;
+
+#[tokio::test]
+async fn test_update_multisig_grow_and_shrink() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let (rent_payer, rent_payer_lamports) =
+        rent_payer(&mut banks_client, &payer, recent_blockhash).await;
+
+    let small_signers = keypairs(3);
+    let multisig = create_multisig(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &small_signers,
+        2,
+        false,
+    )
+    .await;
+    let small_lamports = check_multisig(&mut banks_client, &multisig, &small_signers, 2).await;
+
+    // grow, the payer funding the extra rent
+    let large_signers = keypairs(LARGE_SIGNER_COUNT);
+    update(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &multisig,
+        &rent_payer,
+        &large_signers,
+        3,
+        &[&small_signers[0], &small_signers[2]],
+    )
+    .await
+    .unwrap();
+    let large_lamports = check_multisig(&mut banks_client, &multisig, &large_signers, 3).await;
+    assert_eq!(
+        banks_client.get_balance(rent_payer.pubkey()).await.unwrap(),
+        rent_payer_lamports - (large_lamports - small_lamports)
+    );
+
+    // the replaced signers no longer authorize the multisig, nor do fewer
+    // than m of the new ones
+    missing_signature(
+        update(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &multisig,
+            &rent_payer,
+            &small_signers,
+            2,
+            &[&small_signers[0], &small_signers[1], &small_signers[2]],
+        )
+        .await,
+    );
+    missing_signature(
+        update(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &multisig,
+            &rent_payer,
+            &small_signers,
+            2,
+            &[&large_signers[0], &large_signers[1]],
+        )
+        .await,
+    );
+
+    // shrink, the payer getting the freed rent back
+    let tiny_signers = keypairs(2);
+    update(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &multisig,
+        &rent_payer,
+        &tiny_signers,
+        1,
+        &[&large_signers[5], &large_signers[10], &large_signers[47]],
+    )
+    .await
+    .unwrap();
+    let tiny_lamports = check_multisig(&mut banks_client, &multisig, &tiny_signers, 1).await;
+    assert_eq!(
+        tiny_lamports,
+        rent.minimum_balance(MultisigV2::get_packed_len(2))
+    );
+    assert_eq!(
+        banks_client.get_balance(rent_payer.pubkey()).await.unwrap(),
+        rent_payer_lamports + (small_lamports - tiny_lamports)
+    );
+
+    // the new signers authorize the multisig
+    missing_signature(
+        update(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &multisig,
+            &rent_payer,
+            &small_signers,
+            2,
+            &[&large_signers[0], &large_signers[1], &large_signers[2]],
+        )
+        .await,
+    );
+    update(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &multisig,
+        &rent_payer,
+        &small_signers,
+        2,
+        &[&tiny_signers[1]],
+    )
+    .await
+    .unwrap();
+    check_multisig(&mut banks_client, &multisig, &small_signers, 2).await;
+    assert_eq!(
+        banks_client.get_balance(rent_payer.pubkey()).await.unwrap(),
+        rent_payer_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_update_multisig_v1_to_v2() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let (rent_payer, rent_payer_lamports) =
+        rent_payer(&mut banks_client, &payer, recent_blockhash).await;
+
+    let v1_signers = keypairs(3);
+    let multisig = create_multisig(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &v1_signers,
+        2,
+        true,
+    )
+    .await;
+    let v1_account = banks_client.get_account(multisig).await.unwrap().unwrap();
+    assert_eq!(v1_account.data.len(), Multisig::LEN);
+    assert_eq!(v1_account.lamports, rent.minimum_balance(Multisig::LEN));
+
+    // the v1 signers authorize the conversion
+    let v2_signers = keypairs(5);
+    missing_signature(
+        update(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &multisig,
+            &rent_payer,
+            &v2_signers,
+            3,
+            &[&v1_signers[0]],
+        )
+        .await,
+    );
+    update(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &multisig,
+        &rent_payer,
+        &v2_signers,
+        3,
+        &[&v1_signers[0], &v1_signers[1]],
+    )
+    .await
+    .unwrap();
+
+    // five signers take less room than the fixed v1 layout
+    let v2_lamports = check_multisig(&mut banks_client, &multisig, &v2_signers, 3).await;
+    assert_eq!(
+        banks_client.get_balance(rent_payer.pubkey()).await.unwrap(),
+        rent_payer_lamports + (v1_account.lamports - v2_lamports)
+    );
+
+    // only the v2 signers authorize it from now on
+    missing_signature(
+        update(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &multisig,
+            &rent_payer,
+            &v1_signers,
+            2,
+            &[&v1_signers[0], &v1_signers[1], &v1_signers[2]],
+        )
+        .await,
+    );
+    update(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &multisig,
+        &rent_payer,
+        &v1_signers,
+        2,
+        &[&v2_signers[0], &v2_signers[2], &v2_signers[4]],
+    )
+    .await
+    .unwrap();
+    check_multisig(&mut banks_client, &multisig, &v1_signers, 2).await;
+}
+
+/// Creates a mint whose authority is the payer
+async fn create_mint(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        recent_blockhash,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &id(),
+            ),
+            initialize_mint(&id(), &mint.pubkey(), &payer.pubkey(), None, 0).unwrap(),
+        ],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+    mint.pubkey()
+}
+
+fn instruction_error(result: Result<(), TransportError>) -> InstructionError {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, error) => error,
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_group_and_member() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let update_authority = Keypair::new();
+    let group_mint = create_mint(&mut banks_client, &payer, recent_blockhash).await;
+    let group = get_group_address(&group_mint);
+
+    // Someone sent lamports to the group address before it was created
+    let prefunded_lamports = 1_000;
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &group,
+            prefunded_lamports,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let prefunded = banks_client.get_account(group).await.unwrap().unwrap();
+    assert_eq!(prefunded.owner, system_program::id());
+    assert_eq!(prefunded.lamports, prefunded_lamports);
+
+    // The group must live at the address derived from its mint
+    let mut instruction = initialize_group(
+        &id(),
+        &payer.pubkey(),
+        &group_mint,
+        &payer.pubkey(),
+        &[],
+        Some(&update_authority.pubkey()),
+        2,
+    )
+    .unwrap();
+    let mut wrong_address = instruction.clone();
+    wrong_address.accounts[1].pubkey = Pubkey::new_unique();
+    assert_eq!(
+        instruction_error(
+            process(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[wrong_address],
+                &[],
+            )
+            .await
+        ),
+        InstructionError::InvalidSeeds
+    );
+
+    // The payer tops the prefunded address up to the rent exempt minimum
+    let payer_lamports = banks_client.get_balance(payer.pubkey()).await.unwrap();
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[instruction.clone()],
+        &[],
+    )
+    .await
+    .unwrap();
+    let group_account = banks_client.get_account(group).await.unwrap().unwrap();
+    assert_eq!(group_account.owner, id());
+    assert_eq!(group_account.data.len(), TokenGroup::LEN);
+    assert_eq!(
+        group_account.lamports,
+        rent.minimum_balance(TokenGroup::LEN)
+    );
+    assert!(
+        payer_lamports - banks_client.get_balance(payer.pubkey()).await.unwrap()
+            >= rent.minimum_balance(TokenGroup::LEN) - prefunded_lamports
+    );
+    assert_eq!(
+        TokenGroup::unpack(&group_account.data).unwrap(),
+        TokenGroup {
+            is_initialized: true,
+            update_authority: COption::Some(update_authority.pubkey()),
+            mint: group_mint,
+            size: 0,
+            max_size: 2,
+        }
+    );
+
+    // A group is initialized once
+    let recent_blockhash = banks_client
+        .get_new_blockhash(&recent_blockhash)
+        .await
+        .unwrap()
+        .0;
+    instruction.data = initialize_group(
+        &id(),
+        &payer.pubkey(),
+        &group_mint,
+        &payer.pubkey(),
+        &[],
+        None,
+        5,
+    )
+    .unwrap()
+    .data;
+    assert_eq!(
+        instruction_error(
+            process(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[instruction],
+                &[],
+            )
+            .await
+        ),
+        InstructionError::Custom(TokenError::AlreadyInUse as u32)
+    );
+
+    // Members are created at the address derived from their mint, which has
+    // never held lamports
+    let member_mint = create_mint(&mut banks_client, &payer, recent_blockhash).await;
+    let member = get_group_member_address(&member_mint);
+    assert_eq!(banks_client.get_account(member).await.unwrap(), None);
+    let instruction = initialize_member(
+        &id(),
+        &payer.pubkey(),
+        &member_mint,
+        &payer.pubkey(),
+        &group_mint,
+        &update_authority.pubkey(),
+        &[],
+    )
+    .unwrap();
+    let mut wrong_address = instruction.clone();
+    wrong_address.accounts[1].pubkey = Pubkey::new_unique();
+    assert_eq!(
+        instruction_error(
+            process(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[wrong_address],
+                &[&update_authority],
+            )
+            .await
+        ),
+        InstructionError::InvalidSeeds
+    );
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[instruction],
+        &[&update_authority],
+    )
+    .await
+    .unwrap();
+    let member_account = banks_client.get_account(member).await.unwrap().unwrap();
+    assert_eq!(member_account.owner, id());
+    assert_eq!(member_account.data.len(), TokenGroupMember::LEN);
+    assert_eq!(
+        member_account.lamports,
+        rent.minimum_balance(TokenGroupMember::LEN)
+    );
+    assert_eq!(
+        TokenGroupMember::unpack(&member_account.data).unwrap(),
+        TokenGroupMember {
+            is_initialized: true,
+            mint: member_mint,
+            group,
+            member_number: 1,
+        }
+    );
+    let group_account = banks_client.get_account(group).await.unwrap().unwrap();
+    assert_eq!(TokenGroup::unpack(&group_account.data).unwrap().size, 1);
+}
+
+#[tokio::test]
+async fn test_initialize_member_of_group_without_update_authority() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let group_mint = create_mint(&mut banks_client, &payer, recent_blockhash).await;
+    process(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &[initialize_group(
+            &id(),
+            &payer.pubkey(),
+            &group_mint,
+            &payer.pubkey(),
+            &[],
+            None,
+            10,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+    let group_account = banks_client
+        .get_account(get_group_address(&group_mint))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        TokenGroup::unpack(&group_account.data)
+            .unwrap()
+            .update_authority,
+        COption::None
+    );
+
+    // No one can add members to the group
+    let member_mint = create_mint(&mut banks_client, &payer, recent_blockhash).await;
+    assert_eq!(
+        instruction_error(
+            process(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[initialize_member(
+                    &id(),
+                    &payer.pubkey(),
+                    &member_mint,
+                    &payer.pubkey(),
+                    &group_mint,
+                    &payer.pubkey(),
+                    &[],
+                )
+                .unwrap()],
+                &[],
+            )
+            .await
+        ),
+        InstructionError::Custom(TokenError::OwnerMismatch as u32)
+    );
+    assert_eq!(
+        banks_client
+            .get_account(get_group_member_address(&member_mint))
+            .await
+            .unwrap(),
+        None
+    );
+}